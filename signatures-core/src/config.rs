@@ -0,0 +1,357 @@
+// Everything a plan is made from in one place, so the command line, the
+// GUI and library users all check and build plans the same way.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::imposition::{BookletFold, FoldScheme, PageSize, PageSizeError, ReadingDirection};
+use crate::labels::LabelScheme;
+use crate::multi_up::MultiUpLayout;
+use crate::{BalanceStrategy, DocumentInfo, DocumentOptions, DOC_PAGES_PER_SHEET, DOC_PAGES_PER_SIGNATURE};
+
+// Constants
+const MILLIMETRES_PER_INCH: f64 = 25.4;
+const POINTS_PER_INCH: f64 = 72.0;
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct PagesPerSheetError {
+    pages_per_sheet: u32,
+}
+
+impl Error for PagesPerSheetError {}
+
+impl fmt::Display for PagesPerSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Sheets can carry 4, 8, 12 or 24 pages, 2, 4, 6 or 12 on each side, not {}.",
+            self.pages_per_sheet,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct FillerPageError {
+    page: u32,
+    first_page: u32,
+    last_page: u32,
+}
+
+impl Error for FillerPageError {}
+
+impl fmt::Display for FillerPageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The filler page, {}, isn't in the document, which runs from page {} to {}.",
+            self.page,
+            self.first_page,
+            self.last_page,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct PlanConfigError {
+    pub problems: Vec<Box<dyn Error>>,
+}
+
+impl Error for PlanConfigError {}
+
+impl fmt::Display for PlanConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // a single problem reads the same as it would on its own
+        if let [problem] = self.problems.as_slice() {
+            return write!(f, "{}", problem);
+        }
+        let problems: Vec<String> = self.problems.iter().map(|problem| problem.to_string()).collect();
+        write!(f, "There are {} problems with the plan! {}", problems.len(), problems.join(" "))
+    }
+}
+
+
+// Data structs
+// where the blank pages padding out the last signature go
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BlankPolicy {
+    #[default]
+    AtEnd,
+    // Still at the end, but each a copy of this page of the document, e.g.
+    // a colophon or a decorative flyleaf, rather than left blank. The end
+    // of the book is the only end that gets padding.
+    Filler(u32),
+}
+
+impl BlankPolicy {
+    pub fn padding(&self, document: &DocumentInfo) -> Vec<Option<u32>> {
+        // what goes on each padding page, in order, by page number
+        let page = match self {
+            BlankPolicy::AtEnd => None,
+            BlankPolicy::Filler(page) => Some(*page),
+        };
+        vec![page; document.num_blank_pages() as usize]
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Units {
+    #[default]
+    Millimetres,
+    Inches,
+    Points,
+}
+
+impl Units {
+    pub fn to_millimetres(&self, length: f64) -> f64 {
+        match self {
+            Units::Millimetres => length,
+            Units::Inches => length * MILLIMETRES_PER_INCH,
+            Units::Points => length * MILLIMETRES_PER_INCH / POINTS_PER_INCH,
+        }
+    }
+
+    pub fn from_millimetres(&self, length: f64) -> f64 {
+        length / self.to_millimetres(1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanConfig {
+    pub first_page: u32,
+    pub last_page: u32,
+    // press sheets with more than 4 pages are cut into strips of 4 pages,
+    // which are the sheets of the plan
+    pub pages_per_sheet: u32,
+    pub pages_per_signature: u32,
+    pub label_scheme: LabelScheme,
+    pub blank_policy: BlankPolicy,
+    // the page size, in units
+    pub page_size: PageSize,
+    pub units: Units,
+    pub booklet_fold: BookletFold,
+    // the pages in each signature, when they differ from one to the next,
+    // e.g. read from a collation formula; the page range has to hold them
+    pub gatherings: Vec<u32>,
+    // each sheet printed on one side only, see DocumentInfo::set_one_sided
+    pub one_sided: bool,
+    pub reading_direction: ReadingDirection,
+    pub fold_scheme: FoldScheme,
+    // how the pages are shared out between the signatures
+    pub balance: BalanceStrategy,
+}
+
+impl PlanConfig {
+    pub fn new(first_page: u32, last_page: u32) -> PlanConfig {
+        PlanConfig {
+            first_page,
+            last_page,
+            pages_per_sheet: DOC_PAGES_PER_SHEET,
+            pages_per_signature: DOC_PAGES_PER_SIGNATURE,
+            label_scheme: LabelScheme::default(),
+            blank_policy: BlankPolicy::default(),
+            page_size: PageSize::default(),
+            units: Units::default(),
+            booklet_fold: BookletFold::default(),
+            gatherings: Vec::new(),
+            one_sided: false,
+            reading_direction: ReadingDirection::default(),
+            fold_scheme: FoldScheme::default(),
+            balance: BalanceStrategy::default(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PlanConfigError> {
+        // Check everything, rather than stopping at the first problem, so
+        // they can all be fixed in one go.
+        let mut problems = crate::page_range_problems(self.first_page, self.last_page, self.pages_per_signature);
+        if MultiUpLayout::for_pages_per_sheet(self.pages_per_sheet).is_none() {
+            problems.push((PagesPerSheetError {pages_per_sheet: self.pages_per_sheet}).into());
+        }
+        if let BlankPolicy::Filler(page) = self.blank_policy {
+            if page < self.first_page || page > self.last_page {
+                problems.push((FillerPageError {page, first_page: self.first_page, last_page: self.last_page}).into());
+            }
+        }
+        if !(self.page_size.width > 0.0 && self.page_size.height > 0.0) {
+            let value = format!("{}x{}", self.page_size.width, self.page_size.height);
+            problems.push((PageSizeError {value}).into());
+        }
+        // the labels can only be checked against a plan that works out
+        if problems.is_empty() {
+            match self.document() {
+                Ok(mut document) => {
+                    if let Err(problem) = document.relabel(&self.label_scheme) {
+                        problems.push(problem);
+                    }
+                },
+                Err(problem) => problems.push(problem),
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(PlanConfigError {problems})
+        }
+    }
+
+    pub fn plan(&self) -> Result<DocumentInfo, Box<dyn Error>> {
+        self.validate()?;
+        let mut document = self.document()?;
+        document.relabel(&self.label_scheme)?;
+        Ok(document)
+    }
+
+    fn document(&self) -> Result<DocumentInfo, Box<dyn Error>> {
+        let mut document = if self.gatherings.is_empty() {
+            let options = DocumentOptions {
+                pages_per_signature: self.pages_per_signature,
+                balance: self.balance,
+                fold_scheme: self.fold_scheme,
+            };
+            DocumentInfo::new_with_options(self.first_page, self.last_page, &options)?
+        } else {
+            let mut document = DocumentInfo::with_gatherings(self.first_page, &self.gatherings)?;
+            document.set_fold_scheme(self.fold_scheme)?;
+            document
+        };
+        document.set_one_sided(self.one_sided);
+        document.set_reading_direction(self.reading_direction);
+        Ok(document)
+    }
+
+    pub fn layout(&self) -> Option<MultiUpLayout> {
+        // sheets folded more than once fill a press sheet by themselves
+        match self.fold_scheme {
+            FoldScheme::Folio => MultiUpLayout::for_pages_per_sheet(self.pages_per_sheet),
+            _ => Some(MultiUpLayout {rows: 1, strips_per_row: 1}),
+        }
+    }
+
+    pub fn page_size_mm(&self) -> PageSize {
+        PageSize {
+            width: self.units.to_millimetres(self.page_size.width),
+            height: self.units.to_millimetres(self.page_size.height),
+        }
+    }
+
+    pub fn sheet_size_mm(&self) -> PageSize {
+        // the plan's sheets are two pages wide and one high, or the other
+        // way round when folded at the top, and more of each when folded
+        // more than once
+        match self.fold_scheme {
+            FoldScheme::Folio => self.booklet_fold.sheet_size(self.page_size_mm()),
+            fold_scheme => fold_scheme.sheet_size(self.page_size_mm()),
+        }
+    }
+
+    pub fn press_sheet_size_mm(&self) -> PageSize {
+        // strips of the plan's sheets, rows of them side by side
+        let sheet = self.sheet_size_mm();
+        let layout = self.layout().unwrap_or(MultiUpLayout {rows: 1, strips_per_row: 1});
+        PageSize {
+            width: sheet.width * f64::from(layout.strips_per_row),
+            height: sheet.height * f64::from(layout.rows),
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_config() {
+        let document = PlanConfig::new(5, 26).plan().unwrap();
+        assert_eq!(document.num_sheets, 6);
+
+        let mut config = PlanConfig::new(1, 60);
+        config.pages_per_signature = 8;
+        config.label_scheme.sequence = vec!["i".to_string(), "ii".to_string()];
+        match config.plan() {
+            Ok(_) => panic!("Should have errored because there aren't enough labels!"),
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert_eq!(error_msg, "Not enough signature labels! The labels file has 2 but the document needs 8.");
+            },
+        }
+    }
+
+    #[test]
+    fn test_plan_config_one_sided() {
+        // 22 pages two to a sheet, with one blank rather than two
+        let mut config = PlanConfig::new(5, 26);
+        config.one_sided = true;
+        let document = config.plan().unwrap();
+        assert_eq!(document.num_sheets, 11);
+        assert_eq!(document.num_blank_pages(), 0);
+        assert_eq!(document.signature_leaves(), vec![("A".to_string(), 16), ("B".to_string(), 6)]);
+    }
+
+    #[test]
+    fn test_plan_config_fold_scheme() {
+        // 60 pages in quarto, a sheet to each signature
+        let mut config = PlanConfig::new(1, 60);
+        config.fold_scheme = FoldScheme::Quarto;
+        config.pages_per_signature = config.fold_scheme.default_signature_size();
+        let document = config.plan().unwrap();
+        assert_eq!((document.num_signatures, document.num_sheets), (8, 8));
+        // two A5 pages across and two down
+        assert_eq!(config.sheet_size_mm(), PageSize {width: 296.0, height: 420.0});
+        config.pages_per_signature = 12;
+        assert!(config.plan().is_err());
+    }
+
+    #[test]
+    fn test_plan_config_validate() {
+        let mut config = PlanConfig::new(0, 60);
+        config.pages_per_sheet = 10;
+        config.pages_per_signature = 10;
+        config.page_size = PageSize {width: 0.0, height: 210.0};
+        match config.validate() {
+            Ok(_) => panic!("Should have errored because of every problem at once!"),
+            Err(result_error) => {
+                assert_eq!(result_error.problems.len(), 4);
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("There are 4 problems with the plan! There is no page zero!"));
+                assert!(error_msg.contains("Sheets can carry 4, 8, 12 or 24 pages, 2, 4, 6 or 12 on each side, not 10."));
+            },
+        }
+        assert!(PlanConfig::new(1, 60).validate().is_ok());
+    }
+
+    #[test]
+    fn test_blank_policy() {
+        let mut config = PlanConfig::new(1, 126);
+        let document = config.plan().unwrap();
+        assert_eq!(config.blank_policy.padding(&document), vec![None, None]);
+        config.blank_policy = BlankPolicy::Filler(2);
+        assert_eq!(config.blank_policy.padding(&document), vec![Some(2), Some(2)]);
+
+        config.blank_policy = BlankPolicy::Filler(127);
+        match config.plan() {
+            Ok(_) => panic!("Should have errored because the filler page is past the end!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "The filler page, 127, isn't in the document, which runs from page 1 to 126.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_units() {
+        let mut config = PlanConfig::new(1, 60);
+        config.units = Units::Inches;
+        config.page_size = PageSize {width: 5.0, height: 8.0};
+        assert_eq!(config.page_size_mm(), PageSize {width: 127.0, height: 203.2});
+        assert_eq!(Units::Points.to_millimetres(72.0), 25.4);
+        assert_eq!(Units::Inches.from_millimetres(254.0), 10.0);
+
+        config.units = Units::Millimetres;
+        config.page_size = PageSize {width: 105.0, height: 148.0};
+        config.pages_per_sheet = 24;
+        assert_eq!(config.press_sheet_size_mm(), PageSize {width: 420.0, height: 444.0});
+    }
+}
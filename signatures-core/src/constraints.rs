@@ -0,0 +1,190 @@
+// Choosing a signature size that meets every constraint put on a plan, and
+// when none does, saying which of the constraints pull against each other,
+// so it's clear what to relax.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::{DOC_PAGES_PER_SHEET, DOC_PAGES_PER_SIGNATURE};
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct ConstraintConflictError {
+    // a smallest set of the constraints that can't all hold at once;
+    // dropping any one of them leaves a plan that works
+    pub conflict: Vec<Constraint>,
+}
+
+impl Error for ConstraintConflictError {}
+
+impl fmt::Display for ConstraintConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let constraints: Vec<String> = self.conflict.iter().map(|constraint| constraint.to_string()).collect();
+        if let [constraint] = constraints.as_slice() {
+            return write!(f, "No signature size can have {}!", constraint);
+        }
+        write!(
+            f,
+            "No signature size can have all of: {}. Relax one of them.",
+            constraints.join("; "),
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constraint {
+    // signatures of at least, or at most, this many pages
+    MinSize(u32),
+    MaxSize(u32),
+    // pages that have to be in the same signature, e.g. a plate section
+    KeepTogether(u32, u32),
+    // a page that has to be the first of its signature
+    StartsSignature(u32),
+    // a page that has to fall on a recto, e.g. a chapter opening
+    RectoStart(u32),
+    // a page a player turns from mid-piece, which mustn't be the recto
+    // opening a signature, where a score opens least flat
+    PageTurn(u32),
+}
+
+impl Constraint {
+    fn allows(&self, first_page: u32, pages_per_signature: u32) -> bool {
+        let signature_of = |page: u32| (page - first_page) / pages_per_signature;
+        match *self {
+            Constraint::MinSize(pages) => pages_per_signature >= pages,
+            Constraint::MaxSize(pages) => pages_per_signature <= pages,
+            Constraint::KeepTogether(from, to) => from >= first_page && signature_of(from) == signature_of(to),
+            Constraint::StartsSignature(page) => page >= first_page && (page - first_page).is_multiple_of(pages_per_signature),
+            // every signature starts on a recto, so no size moves a page off a verso
+            Constraint::RectoStart(page) => page >= first_page && (page - first_page).is_multiple_of(2),
+            Constraint::PageTurn(page) => page < first_page || !(page - first_page).is_multiple_of(pages_per_signature),
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constraint::MinSize(pages) => write!(f, "signatures of at least {} pages", pages),
+            Constraint::MaxSize(pages) => write!(f, "signatures of at most {} pages", pages),
+            Constraint::KeepTogether(from, to) => write!(f, "pages {} to {} in one signature", from, to),
+            Constraint::StartsSignature(page) => write!(f, "page {} starting a signature", page),
+            Constraint::RectoStart(page) => write!(f, "page {} on a recto", page),
+            Constraint::PageTurn(page) => write!(f, "a page turn at page {}, away from a signature's first page", page),
+        }
+    }
+}
+
+
+// Work
+pub fn choose_size(first_page: u32, last_page: u32, constraints: &[Constraint]) -> Result<u32, Box<dyn Error>> {
+    // The size meeting every constraint nearest the usual 16 pages, the
+    // smaller of two equally near.
+    let sizes = candidate_sizes(first_page, last_page, constraints);
+    let meets_all = |size: &u32, constraints: &[Constraint]| {
+        constraints.iter().all(|constraint| constraint.allows(first_page, *size))
+    };
+    if let Some(size) = sizes
+        .iter()
+        .filter(|size| meets_all(size, constraints))
+        .min_by_key(|size| (size.abs_diff(DOC_PAGES_PER_SIGNATURE), **size))
+    {
+        return Ok(*size);
+    }
+    // Take constraints away one at a time, keeping out any the rest still
+    // conflict without, until every one left is needed for the conflict.
+    let mut conflict = constraints.to_vec();
+    let mut i = 0;
+    while i < conflict.len() {
+        let mut without = conflict.clone();
+        without.remove(i);
+        if sizes.iter().any(|size| meets_all(size, &without)) {
+            i += 1;
+        } else {
+            conflict = without;
+        }
+    }
+    Err((ConstraintConflictError {conflict}).into())
+}
+
+fn candidate_sizes(first_page: u32, last_page: u32, constraints: &[Constraint]) -> Vec<u32> {
+    // Every whole number of sheets up to a signature holding the whole
+    // document, or the largest size asked for if that's bigger.
+    let num_pages = last_page - first_page + 1;
+    let largest = constraints
+        .iter()
+        .filter_map(|constraint| match constraint {
+            Constraint::MinSize(pages) | Constraint::MaxSize(pages) => Some(*pages),
+            _ => None,
+        })
+        .fold(num_pages.max(DOC_PAGES_PER_SIGNATURE), u32::max);
+    (1..=largest.div_ceil(DOC_PAGES_PER_SHEET)).map(|sheets| sheets * DOC_PAGES_PER_SHEET).collect()
+}
+
+pub fn parse_page_span(value: &str) -> Option<(u32, u32)> {
+    // e.g. "33-48"
+    let (from, to) = value.split_once('-')?;
+    let (from, to) = (from.trim().parse().ok()?, to.trim().parse().ok()?);
+    if from <= to {
+        Some((from, to))
+    } else {
+        None
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_size() {
+        assert_eq!(choose_size(1, 200, &[]).unwrap(), 16);
+        // 16 would split pages 9 to 24; 24 keeps them together
+        assert_eq!(choose_size(1, 200, &[Constraint::KeepTogether(9, 24)]).unwrap(), 24);
+        assert_eq!(choose_size(1, 200, &[Constraint::StartsSignature(41), Constraint::MinSize(12)]).unwrap(), 20);
+        assert_eq!(choose_size(5, 200, &[Constraint::MaxSize(8)]).unwrap(), 8);
+        // page 17 would open the second signature of 16, or the third of 8
+        assert_eq!(choose_size(1, 200, &[Constraint::PageTurn(17)]).unwrap(), 12);
+        assert_eq!(choose_size(1, 200, &[Constraint::MaxSize(8), Constraint::PageTurn(13)]).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_choose_size_conflict() {
+        let constraints = [
+            Constraint::MinSize(12),
+            Constraint::MaxSize(16),
+            Constraint::RectoStart(3),
+            Constraint::KeepTogether(9, 40),
+        ];
+        match choose_size(1, 200, &constraints) {
+            Ok(size) => panic!("Should have errored because pages 9 to 40 can't fit in 16! Got {}.", size),
+            Err(result_error) => {
+                assert_eq!(
+                    format!("{}", result_error),
+                    "No signature size can have all of: signatures of at most 16 pages; \
+                     pages 9 to 40 in one signature. Relax one of them.",
+                );
+                let conflict = &result_error.downcast_ref::<ConstraintConflictError>().unwrap().conflict;
+                assert_eq!(conflict, &vec![Constraint::MaxSize(16), Constraint::KeepTogether(9, 40)]);
+            },
+        }
+        // a page that falls on a verso whatever the size conflicts on its own
+        match choose_size(1, 200, &[Constraint::RectoStart(4), Constraint::MinSize(8)]) {
+            Ok(_) => panic!("Should have errored because page 4 is always a verso!"),
+            Err(result_error) => {
+                assert_eq!(format!("{}", result_error), "No signature size can have page 4 on a recto!");
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_page_span() {
+        assert_eq!(parse_page_span("33-48"), Some((33, 48)));
+        assert_eq!(parse_page_span("48-33"), None);
+        assert_eq!(parse_page_span("33"), None);
+    }
+}
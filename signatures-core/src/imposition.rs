@@ -0,0 +1,849 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{DocumentInfo, Signature, DOC_PAGES_PER_SHEET, DOC_PAGES_PER_SIGNATURE, ONE_SIDED_PAGES_PER_SHEET};
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct PageSizeError {
+    pub value: String,
+}
+
+impl Error for PageSizeError {}
+
+impl fmt::Display for PageSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Page size should look like WIDTHxHEIGHT in millimetres, e.g. 148x210. Got: {}", self.value)
+    }
+}
+
+#[derive(Debug)]
+pub struct TwoUpError {
+    pub fold_scheme: FoldScheme,
+}
+
+impl Error for TwoUpError {}
+
+impl fmt::Display for TwoUpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A sheet folded in {} has {} pages to a side, but this only lays sheets out two pages to a side! Use --format formes or the impose subcommand, or plan with --fold folio.",
+            self.fold_scheme.name(),
+            self.fold_scheme.pages_per_sheet() / 2,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Front,
+    Back,
+}
+
+impl Side {
+    pub fn forme(&self) -> &'static str {
+        // The front of each sheet carries its lowest page, which ends up on
+        // the outside once folded: the outer forme, in letterpress terms.
+        match self {
+            Side::Front => "outer",
+            Side::Back => "inner",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    Left,
+    Right,
+}
+
+// Which way the pages turn. A book read right to left, like manga or a
+// Hebrew or Arabic book, is bound on its right edge, so every sheet side is
+// the mirror image of a left to right book's.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReadingDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+// Which way the sheets fold: down the middle between two pages side by
+// side, for an ordinary booklet, or across the middle between two
+// landscape pages one above the other, for a "long" booklet bound at the
+// top like a calendar. A top fold puts each sheet side's left cell on top
+// and its right cell below.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BookletFold {
+    #[default]
+    Side,
+    Top,
+}
+
+impl BookletFold {
+    pub fn sheet_size(&self, page: PageSize) -> PageSize {
+        match self {
+            BookletFold::Side => PageSize {width: page.width * 2.0, height: page.height},
+            BookletFold::Top => PageSize {width: page.width, height: page.height * 2.0},
+        }
+    }
+
+    pub fn rotation(&self, side: Side, cell: Cell) -> u32 {
+        // The degrees a page is turned in its cell. Sheets folded at the top
+        // turn over on their long edge, which brings the back round upside
+        // down, so everything on it is printed the other way up. On the
+        // front, the page above the fold is upside down so that it reads the
+        // right way once folded behind.
+        match (self, side, cell) {
+            (BookletFold::Side, _, _) => 0,
+            (BookletFold::Top, Side::Front, Cell::Right) => 0,
+            (BookletFold::Top, _, _) => 180,
+        }
+    }
+
+    pub fn is_on_top(&self, side: Side, cell: Cell) -> bool {
+        // with the back upside down, its right cell is the one on top
+        *self == BookletFold::Top && (cell == Cell::Left) == (side == Side::Front)
+    }
+
+    pub fn duplex(&self) -> &'static str {
+        // the CUPS sides option for printing the sheets double-sided
+        match self {
+            BookletFold::Side => "two-sided-short-edge",
+            BookletFold::Top => "two-sided-long-edge",
+        }
+    }
+}
+
+// One fold of the sheet as it lies on the bench, halving it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fold {
+    // the top half goes under the bottom half
+    TopUnder,
+    // the left half goes under the right half
+    LeftUnder,
+}
+
+impl Fold {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Fold::TopUnder => "Fold the top half under",
+            Fold::LeftUnder => "Fold the left half under",
+        }
+    }
+}
+
+// How many times each sheet is folded before it's bound: once for a folio,
+// twice for a quarto, three times for an octavo and four times for a
+// sextodecimo, each fold doubling its pages. A signature of several folded
+// sheets has them nested inside each other, as the folio's are.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FoldScheme {
+    #[default]
+    Folio,
+    Quarto,
+    Octavo,
+    Sextodecimo,
+}
+
+impl FoldScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FoldScheme::Folio => "folio",
+            FoldScheme::Quarto => "quarto",
+            FoldScheme::Octavo => "octavo",
+            FoldScheme::Sextodecimo => "sextodecimo",
+        }
+    }
+
+    pub fn pages_per_sheet(&self) -> u32 {
+        match self {
+            FoldScheme::Folio => 4,
+            FoldScheme::Quarto => 8,
+            FoldScheme::Octavo => 16,
+            FoldScheme::Sextodecimo => 32,
+        }
+    }
+
+    pub fn folds(&self) -> Vec<Fold> {
+        // Folding the outer forme face up, each fold leaves the pages in
+        // order through the folded sheet, page 1 on top and every page head
+        // up. The last fold, down the spine, is always across the width.
+        match self {
+            FoldScheme::Folio => vec![Fold::LeftUnder],
+            FoldScheme::Quarto => vec![Fold::TopUnder, Fold::LeftUnder],
+            FoldScheme::Octavo => vec![Fold::LeftUnder, Fold::TopUnder, Fold::LeftUnder],
+            FoldScheme::Sextodecimo => vec![Fold::TopUnder, Fold::LeftUnder, Fold::TopUnder, Fold::LeftUnder],
+        }
+    }
+
+    pub fn layout(&self, side: Side) -> Vec<Vec<FormePage>> {
+        // Where each of a sheet's pages, counted from 1 through the folded
+        // sheet, is printed on the given side: rows from the top, as seen
+        // with that side face up. Worked out by unfolding the folded sheet
+        // one fold at a time, last fold first.
+        let upright = |page: u32| FormePage {page: Some(page), head_down: false};
+        let stack = (0..self.pages_per_sheet() / 2).map(|leaf| Layer {up: upright(2 * leaf + 1), down: upright(2 * leaf + 2)});
+        let mut cells = vec![vec![stack.collect::<Vec<Layer>>()]];
+        for fold in self.folds().iter().rev() {
+            cells = unfold(&cells, *fold);
+        }
+        // the inner forme is underneath, left to right reversed once the
+        // sheet is turned over
+        cells
+            .iter()
+            .map(|row| match side {
+                Side::Front => row.iter().map(|stack| stack[0].up).collect(),
+                Side::Back => row.iter().rev().map(|stack| stack[0].down).collect(),
+            })
+            .collect()
+    }
+
+    pub fn sheet_size(&self, page: PageSize) -> PageSize {
+        // as many pages across and down as the formes have
+        let layout = self.layout(Side::Front);
+        PageSize {width: page.width * layout[0].len() as f64, height: page.height * layout.len() as f64}
+    }
+
+    pub fn default_signature_size(&self) -> u32 {
+        // folios gathered in fours, as usual, and the rest a sheet apiece
+        match self {
+            FoldScheme::Folio => DOC_PAGES_PER_SIGNATURE,
+            _ => self.pages_per_sheet(),
+        }
+    }
+}
+
+// A folded sheet's pages, in reading order, with blanks past the end of
+// the signature as None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldedSheet {
+    pub signature_key: String,
+    pub sheet: u32,
+    pub pages: Vec<Option<u32>>,
+}
+
+// a cell of a forme: the page printed there, None for a blank, and whether
+// it stands head down so as to come out head up once folded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormePage {
+    pub page: Option<u32>,
+    pub head_down: bool,
+}
+
+// one thickness of a folded sheet, with the page facing up and the one
+// facing down, each with its head as seen when that side faces up
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Layer {
+    up: FormePage,
+    down: FormePage,
+}
+
+impl Layer {
+    fn turned_over(&self, fold: Fold) -> Layer {
+        // turning over top to bottom also turns the pages head to foot
+        let turn = |page: FormePage| match fold {
+            Fold::TopUnder => FormePage {page: page.page, head_down: !page.head_down},
+            Fold::LeftUnder => page,
+        };
+        Layer {up: turn(self.down), down: turn(self.up)}
+    }
+}
+
+// A side of one of the plan's sheets as it's printed, rows of pages from
+// the top: one row of two for a folio, more for sheets folded more often.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetForme {
+    pub signature_key: String,
+    pub sheet: u32,
+    pub side: Side,
+    pub rows: Vec<Vec<FormePage>>,
+}
+
+// a side of a sheet two pages wide, which is all of it for a folio
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetSide {
+    pub signature_key: String,
+    pub sheet: u32,
+    pub side: Side,
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+}
+
+// where a page is printed: the sheet side, and the row from the top and
+// column from the left of its forme
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLocation {
+    pub signature_key: String,
+    pub sheet: u32,
+    pub side: Side,
+    pub row: usize,
+    pub column: usize,
+}
+
+// the pages printed alongside a page: on the back of its leaf, and on the
+// same side of the sheet on the leaf joined to it at the fold, or beside it
+// when the sheets are cut rather than folded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conjugates {
+    pub page: u32,
+    pub backs_up_with: Option<u32>,
+    pub shares_side_with: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for PageSize {
+    fn default() -> PageSize {
+        // A5, which imposes two-up on A4 sheets
+        PageSize {width: 148.0, height: 210.0}
+    }
+}
+
+impl PageSize {
+    pub fn parse(value: &str) -> Result<PageSize, Box<dyn Error>> {
+        let page_size_error = || PageSizeError {value: value.to_string()};
+        let mut dimensions = value.split('x');
+        let (width, height) = match (dimensions.next(), dimensions.next(), dimensions.next()) {
+            (Some(width), Some(height), None) => (width.trim().parse(), height.trim().parse()),
+            _ => return Err(page_size_error().into()),
+        };
+        match (width, height) {
+            (Ok(width), Ok(height)) if width > 0.0 && height > 0.0 => Ok(PageSize {width, height}),
+            _ => Err(page_size_error().into()),
+        }
+    }
+}
+
+
+// Work
+pub fn impose(document: &DocumentInfo) -> Result<Vec<SheetSide>, TwoUpError> {
+    // Lay out every sheet side of the document, signature by signature, two
+    // pages to a side.
+    let mut sides = Vec::new();
+    for signature in &document.signatures {
+        sides.extend(signature_sides(document, signature)?);
+    }
+    Ok(sides)
+}
+
+pub fn impose_formes(document: &DocumentInfo) -> Vec<SheetForme> {
+    // Lay out every sheet side of the document as it goes on the press,
+    // however many times the sheets are folded.
+    document
+        .signatures
+        .iter()
+        .flat_map(|signature| signature_formes(document, signature))
+        .collect()
+}
+
+pub fn locate(document: &DocumentInfo, page: u32) -> Option<PageLocation> {
+    // Find the one sheet side a page is printed on, e.g. to reprint a
+    // spoiled page. Only the page's own signature needs imposing.
+    let signature = document
+        .signatures
+        .iter()
+        .find(|signature| signature.first_page <= page && page <= signature.last_page)?;
+    signature_formes(document, signature).into_iter().find_map(|forme| {
+        forme.rows.iter().enumerate().find_map(|(row, cells)| {
+            let column = cells.iter().position(|cell| cell.page == Some(page))?;
+            Some(PageLocation {signature_key: forme.signature_key.clone(), sheet: forme.sheet, side: forme.side, row, column})
+        })
+    })
+}
+
+pub fn conjugates(document: &DocumentInfo) -> Vec<Conjugates> {
+    // Pair up the pages on each folded sheet. Counting through it, each
+    // leaf's recto backs up with the verso after it, and the fold joins the
+    // leaf so many from the front to the leaf so many from the back, the
+    // pages on the same side of the sheet as far from the last page as the
+    // other is from the first.
+    let mut conjugates = Vec::new();
+    for signature in &document.signatures {
+        if document.one_sided {
+            for side in impose_one_sided(signature).iter().filter(|side| side.side == Side::Front) {
+                for (page, shares_side_with) in [(side.left, side.right), (side.right, side.left)] {
+                    if let Some(page) = page {
+                        conjugates.push(Conjugates {page, backs_up_with: None, shares_side_with});
+                    }
+                }
+            }
+            continue;
+        }
+        for sheet in folded_sheets(signature) {
+            let last = sheet.pages.len() - 1;
+            for (index, page) in sheet.pages.iter().enumerate() {
+                if let Some(page) = *page {
+                    conjugates.push(Conjugates {
+                        page,
+                        backs_up_with: sheet.pages[index ^ 1],
+                        shares_side_with: sheet.pages[last - index],
+                    });
+                }
+            }
+        }
+    }
+    conjugates.sort_by_key(|conjugate| conjugate.page);
+    conjugates
+}
+
+pub fn display_conjugates(document: &DocumentInfo) {
+    print!("{}", document.job().header(""));
+    println!("{:>6}{:>16}{:>24}", "Page", "Backs up with", "Shares sheet side with");
+    println!("#####################################");
+    let cell = |page: Option<u32>| page.map_or("blank".to_string(), |page| document.shown_page(page).to_string());
+    for conjugate in conjugates(document) {
+        println!(
+            "{:>6}{:>16}{:>24}",
+            document.shown_page(conjugate.page),
+            cell(conjugate.backs_up_with),
+            cell(conjugate.shares_side_with),
+        );
+    }
+    println!("#####################################");
+}
+
+pub fn display_formes(document: &DocumentInfo, fold: BookletFold) {
+    // each sheet's outer and inner forme, with the pages as they sit in the chase
+    print!("{}", document.job().header(""));
+    let cell = |page: Option<u32>| page.map_or("blank".to_string(), |page| document.shown_page(page).to_string());
+    let Ok(sides) = impose(document) else {
+        // rows from the top, noting the pages printed upside down
+        for forme in impose_formes(document) {
+            if forme.side == Side::Front {
+                println!(
+                    "Signature {}, sheet {}",
+                    document.label_scheme.display_key(&forme.signature_key),
+                    forme.sheet + 1,
+                );
+            }
+            let rows: Vec<String> = forme
+                .rows
+                .iter()
+                .map(|row| {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|forme_page| match forme_page.head_down {
+                            true => format!("{} (upside down)", cell(forme_page.page)),
+                            false => cell(forme_page.page),
+                        })
+                        .collect();
+                    cells.join(" | ")
+                })
+                .collect();
+            println!("  {} forme: {}", forme.side.forme(), rows.join(" / "));
+        }
+        println!("Back up each sheet turning it over from left to right");
+        println!("#####################################");
+        return;
+    };
+    for side in sides {
+        if side.side == Side::Front {
+            println!(
+                "Signature {}, sheet {}",
+                document.label_scheme.display_key(&side.signature_key),
+                side.sheet + 1,
+            );
+        }
+        if fold == BookletFold::Side {
+            println!("  {} forme: {} | {}", side.side.forme(), cell(side.left), cell(side.right));
+            continue;
+        }
+        // top then bottom, noting the pages printed upside down
+        let mut cells = [(Cell::Left, side.left), (Cell::Right, side.right)];
+        cells.sort_by_key(|(position, _)| !fold.is_on_top(side.side, *position));
+        let [top, bottom] = cells.map(|(position, page)| match fold.rotation(side.side, position) {
+            180 => format!("{} (upside down)", cell(page)),
+            _ => cell(page),
+        });
+        println!("  {} forme: {} / {}", side.side.forme(), top, bottom);
+    }
+    println!("Print both sides with sides={}", fold.duplex());
+    println!("#####################################");
+}
+
+pub fn signature_sides(document: &DocumentInfo, signature: &Signature) -> Result<Vec<SheetSide>, TwoUpError> {
+    // Two pages to a sheet side, as for a folio or sheets cut apart. Sheets
+    // folded more often have more, and are laid out by signature_formes.
+    let mut sides = if document.one_sided {
+        impose_one_sided(signature)
+    } else if document.fold_scheme == FoldScheme::Folio {
+        impose_folded(signature)
+    } else {
+        return Err(TwoUpError {fold_scheme: document.fold_scheme});
+    };
+    if document.reading_direction == ReadingDirection::RightToLeft {
+        for side in &mut sides {
+            std::mem::swap(&mut side.left, &mut side.right);
+        }
+    }
+    Ok(sides)
+}
+
+pub fn signature_formes(document: &DocumentInfo, signature: &Signature) -> Vec<SheetForme> {
+    // Sheets cut apart keep their one row of two pages. A book read right
+    // to left is the mirror image, row by row.
+    let mut formes = if document.one_sided {
+        impose_one_sided(signature)
+            .into_iter()
+            .map(|side| SheetForme {
+                signature_key: side.signature_key,
+                sheet: side.sheet,
+                side: side.side,
+                rows: vec![vec![
+                    FormePage {page: side.left, head_down: false},
+                    FormePage {page: side.right, head_down: false},
+                ]],
+            })
+            .collect()
+    } else {
+        impose_signature(signature)
+    };
+    if document.reading_direction == ReadingDirection::RightToLeft {
+        for forme in &mut formes {
+            for row in &mut forme.rows {
+                row.reverse();
+            }
+        }
+    }
+    formes
+}
+
+pub fn impose_signature(signature: &Signature) -> Vec<SheetForme> {
+    // Each folded sheet's pages go where its fold scheme puts the pages of
+    // a lone sheet, counted through the folded sheet.
+    let mut formes = Vec::new();
+    for sheet in folded_sheets(signature) {
+        for side in [Side::Front, Side::Back] {
+            let rows = signature
+                .fold_scheme
+                .layout(side)
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| FormePage {
+                            page: cell.page.and_then(|page| sheet.pages[page as usize - 1]),
+                            head_down: cell.head_down,
+                        })
+                        .collect()
+                })
+                .collect();
+            formes.push(SheetForme {signature_key: sheet.signature_key.clone(), sheet: sheet.sheet, side, rows});
+        }
+    }
+    formes
+}
+
+fn impose_folded(signature: &Signature) -> Vec<SheetSide> {
+    // The sheets of a signature are nested inside each other and folded
+    // once, so the outermost sheet carries the first two and last two pages.
+    // Page slots past the end of the signature are left blank.
+    let num_pages = signature.last_page - signature.first_page + 1;
+    let num_sheets = num_pages.div_ceil(DOC_PAGES_PER_SHEET);
+    let first = signature.first_page;
+    let last = first + (num_sheets * DOC_PAGES_PER_SHEET - 1);
+    let page = |number: u32| if number <= signature.last_page { Some(number) } else { None };
+    let mut sides = Vec::new();
+    for sheet in 0..num_sheets {
+        sides.push(SheetSide {
+            signature_key: signature.signature_key.clone(),
+            sheet,
+            side: Side::Front,
+            left: page(last - 2 * sheet),
+            right: page(first + 2 * sheet),
+        });
+        sides.push(SheetSide {
+            signature_key: signature.signature_key.clone(),
+            sheet,
+            side: Side::Back,
+            left: page(first + 2 * sheet + 1),
+            right: page(last - 2 * sheet - 1),
+        });
+    }
+    sides
+}
+
+pub fn folded_sheets(signature: &Signature) -> Vec<FoldedSheet> {
+    // Each folded sheet carries a run of pages from the front of the
+    // signature and a run from the back, the outermost the first and last.
+    let pages_per_sheet = signature.fold_scheme.pages_per_sheet();
+    let half = pages_per_sheet / 2;
+    let num_sheets = (signature.last_page - signature.first_page + 1).div_ceil(pages_per_sheet);
+    let first = signature.first_page;
+    let last = first + (num_sheets * pages_per_sheet - 1);
+    let page = |number: u32| if number <= signature.last_page { Some(number) } else { None };
+    (0..num_sheets)
+        .map(|sheet| {
+            let front = first + sheet * half..first + (sheet + 1) * half;
+            let back = last + 1 - (sheet + 1) * half..last + 1 - sheet * half;
+            FoldedSheet {signature_key: signature.signature_key.clone(), sheet, pages: front.chain(back).map(page).collect()}
+        })
+        .collect()
+}
+
+fn unfold(cells: &[Vec<Vec<Layer>>], fold: Fold) -> Vec<Vec<Vec<Layer>>> {
+    // Undo one fold, doubling the grid. The upper half of each stack stays
+    // where it is; the lower half was folded under, so it comes back turned
+    // over, in the mirror-image cell, with its layers the other way up.
+    let (rows, columns) = (cells.len(), cells[0].len());
+    let mut unfolded = match fold {
+        Fold::TopUnder => vec![vec![Vec::new(); columns]; 2 * rows],
+        Fold::LeftUnder => vec![vec![Vec::new(); 2 * columns]; rows],
+    };
+    for (row, stacks) in cells.iter().enumerate() {
+        for (column, stack) in stacks.iter().enumerate() {
+            let (staying, moving) = stack.split_at(stack.len() / 2);
+            let moving: Vec<Layer> = moving.iter().rev().map(|layer| layer.turned_over(fold)).collect();
+            match fold {
+                Fold::TopUnder => {
+                    unfolded[rows + row][column] = staying.to_vec();
+                    unfolded[rows - 1 - row][column] = moving;
+                }
+                Fold::LeftUnder => {
+                    unfolded[row][columns + column] = staying.to_vec();
+                    unfolded[row][columns - 1 - column] = moving;
+                }
+            }
+        }
+    }
+    unfolded
+}
+
+fn impose_one_sided(signature: &Signature) -> Vec<SheetSide> {
+    // Printed on one side and cut apart rather than folded, the sheets just
+    // take the pages two at a time in reading order, with their backs left
+    // unprinted, so there's no backing up to line up.
+    let num_sheets = (signature.last_page - signature.first_page + 1).div_ceil(ONE_SIDED_PAGES_PER_SHEET);
+    let page = |number: u32| if number <= signature.last_page { Some(number) } else { None };
+    let mut sides = Vec::new();
+    for sheet in 0..num_sheets {
+        let first = signature.first_page + ONE_SIDED_PAGES_PER_SHEET * sheet;
+        sides.push(SheetSide {
+            signature_key: signature.signature_key.clone(),
+            sheet,
+            side: Side::Front,
+            left: page(first),
+            right: page(first + 1),
+        });
+        sides.push(SheetSide {signature_key: signature.signature_key.clone(), sheet, side: Side::Back, left: None, right: None});
+    }
+    sides
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_booklet_fold() {
+        // outer sheet of 8 pages folded at the top: 8 over 1 on the front,
+        // 7 over 2 on the back, all but page 1 upside down
+        let side = BookletFold::Side;
+        let top = BookletFold::Top;
+        assert_eq!(side.rotation(Side::Back, Cell::Left), 0);
+        assert_eq!(top.rotation(Side::Front, Cell::Left), 180);
+        assert_eq!(top.rotation(Side::Front, Cell::Right), 0);
+        assert_eq!(top.rotation(Side::Back, Cell::Left), 180);
+        assert!(top.is_on_top(Side::Front, Cell::Left));
+        assert!(top.is_on_top(Side::Back, Cell::Right));
+        assert!(!side.is_on_top(Side::Front, Cell::Left));
+        assert_eq!(top.duplex(), "two-sided-long-edge");
+        assert_eq!(top.sheet_size(PageSize {width: 210.0, height: 148.0}), PageSize {width: 210.0, height: 296.0});
+        assert_eq!(side.sheet_size(PageSize::default()), PageSize {width: 296.0, height: 210.0});
+    }
+
+    fn pairs(sides: &[SheetSide]) -> Vec<(Option<u32>, Option<u32>)> {
+        sides.iter().map(|side| (side.left, side.right)).collect()
+    }
+
+    #[test]
+    fn test_impose() {
+        let document = DocumentInfo::new(1, 16).unwrap();
+        let sides = impose(&document).unwrap();
+        assert_eq!(sides.len(), 8);
+        assert_eq!(
+            pairs(&sides),
+            vec![
+                (Some(16), Some(1)), (Some(2), Some(15)),
+                (Some(14), Some(3)), (Some(4), Some(13)),
+                (Some(12), Some(5)), (Some(6), Some(11)),
+                (Some(10), Some(7)), (Some(8), Some(9)),
+            ],
+        );
+        assert_eq!(sides[2].sheet, 1);
+        assert_eq!(sides[2].side, Side::Front);
+        assert_eq!(sides[3].side, Side::Back);
+        assert_eq!(sides[2].side.forme(), "outer");
+        assert_eq!(sides[3].side.forme(), "inner");
+
+        // short last signature, with blanks at the back
+        let document = DocumentInfo::new(5, 26).unwrap();
+        let sides = impose(&document).unwrap();
+        assert_eq!(sides.len(), 12);
+        assert_eq!(sides[8].signature_key, "B");
+        assert_eq!(
+            pairs(&sides[8..]),
+            vec![(None, Some(21)), (Some(22), None), (Some(26), Some(23)), (Some(24), Some(25))],
+        );
+    }
+
+    fn forme_pages(forme: &SheetForme) -> Vec<Vec<Option<u32>>> {
+        forme.rows.iter().map(|row| row.iter().map(|cell| cell.page).collect()).collect()
+    }
+
+    #[test]
+    fn test_impose_octavo() {
+        // 20 pages in octavo: one signature of 16, then 4 pages padded out
+        // to a second whole sheet
+        let mut document = DocumentInfo::with_signature_size(1, 20, 16).unwrap();
+        document.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        let sheets = folded_sheets(&document.signatures[0]);
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].pages, (1..=16).map(Some).collect::<Vec<Option<u32>>>());
+        let sheets = folded_sheets(&document.signatures[1]);
+        assert_eq!(sheets[0].pages[..4], [Some(17), Some(18), Some(19), Some(20)]);
+        assert_eq!(sheets[0].pages[4..], [None; 12]);
+        // one sheet to a signature, four pages across and two down, the top
+        // row head down so the heads meet at the second fold
+        let formes = impose_formes(&document);
+        assert_eq!(formes.len(), 4);
+        let page = |pages: [u32; 4]| pages.iter().map(|&page| Some(page)).collect::<Vec<Option<u32>>>();
+        assert_eq!(forme_pages(&formes[0]), vec![page([5, 12, 9, 8]), page([4, 13, 16, 1])]);
+        assert_eq!(forme_pages(&formes[1]), vec![page([7, 10, 11, 6]), page([2, 15, 14, 3])]);
+        for forme in &formes[..2] {
+            assert!(forme.rows[0].iter().all(|cell| cell.head_down));
+            assert!(forme.rows[1].iter().all(|cell| !cell.head_down));
+        }
+        assert_eq!(forme_pages(&formes[2])[1], vec![Some(20), None, None, Some(17)]);
+        // and there's no laying them out two pages to a side
+        assert!(impose(&document).is_err());
+        // the conjugate leaves are joined at the folds, 1 to 16 and 2 to 15
+        let conjugates = conjugates(&document);
+        assert_eq!(conjugates[0], Conjugates {page: 1, backs_up_with: Some(2), shares_side_with: Some(16)});
+        assert_eq!(conjugates[1], Conjugates {page: 2, backs_up_with: Some(1), shares_side_with: Some(15)});
+        assert_eq!(conjugates[6], Conjugates {page: 7, backs_up_with: Some(8), shares_side_with: Some(10)});
+        assert_eq!(conjugates[17], Conjugates {page: 18, backs_up_with: Some(17), shares_side_with: None});
+        assert_eq!(
+            locate(&document, 9),
+            Some(PageLocation {signature_key: "A".to_string(), sheet: 0, side: Side::Front, row: 0, column: 2}),
+        );
+        assert_eq!(document.sheet_of(14).map(|location| (location.side, location.row, location.column)), Some((Side::Back, 1, 2)));
+    }
+
+    #[test]
+    fn test_fold_scheme() {
+        assert_eq!(FoldScheme::default().pages_per_sheet(), 4);
+        assert_eq!(FoldScheme::Folio.default_signature_size(), 16);
+        assert_eq!(FoldScheme::Quarto.default_signature_size(), 8);
+        assert_eq!(FoldScheme::Sextodecimo.pages_per_sheet(), 32);
+        // a signature of two quarto sheets, one inside the other
+        let mut document = DocumentInfo::with_signature_size(1, 16, 16).unwrap();
+        document.set_fold_scheme(FoldScheme::Quarto).unwrap();
+        let sheets = folded_sheets(&document.signatures[0]);
+        let pages: Vec<u32> = sheets[0].pages.iter().flatten().copied().collect();
+        assert_eq!(pages, vec![1, 2, 3, 4, 13, 14, 15, 16]);
+        assert_eq!(sheets[1].pages[0], Some(5));
+        // a lone quarto sheet's formes, the top rows head down
+        let local = |layout: Vec<Vec<FormePage>>| -> Vec<Vec<(u32, bool)>> {
+            layout.iter().map(|row| row.iter().map(|cell| (cell.page.unwrap(), cell.head_down)).collect()).collect()
+        };
+        assert_eq!(local(FoldScheme::Quarto.layout(Side::Front)), vec![vec![(5, true), (4, true)], vec![(8, false), (1, false)]]);
+        assert_eq!(local(FoldScheme::Quarto.layout(Side::Back)), vec![vec![(3, true), (6, true)], vec![(2, false), (7, false)]]);
+        assert_eq!(local(FoldScheme::Folio.layout(Side::Front)), vec![vec![(4, false), (1, false)]]);
+        // every page of a sextodecimo sheet printed once, four to a row
+        let layout = FoldScheme::Sextodecimo.layout(Side::Front);
+        assert_eq!((layout.len(), layout[0].len()), (4, 4));
+        assert_eq!(FoldScheme::Octavo.sheet_size(PageSize::default()), PageSize {width: 592.0, height: 420.0});
+        let mut pages: Vec<u32> = [Side::Front, Side::Back]
+            .iter()
+            .flat_map(|side| FoldScheme::Sextodecimo.layout(*side).concat())
+            .filter_map(|cell| cell.page)
+            .collect();
+        pages.sort_unstable();
+        assert_eq!(pages, (1..=32).collect::<Vec<u32>>());
+        // the outer quarto sheet's formes carry the signature's pages
+        let formes = signature_formes(&document, &document.signatures[0]);
+        assert_eq!(formes.len(), 4);
+        assert_eq!(forme_pages(&formes[0]), vec![vec![Some(13), Some(4)], vec![Some(16), Some(1)]]);
+    }
+
+    #[test]
+    fn test_impose_one_sided() {
+        // 1 2 | 3 4 | 5 -, backs blank
+        let mut document = DocumentInfo::new(1, 5).unwrap();
+        document.set_one_sided(true);
+        let sides = impose(&document).unwrap();
+        assert_eq!(sides.len(), 6);
+        assert_eq!(
+            pairs(&sides),
+            vec![(Some(1), Some(2)), (None, None), (Some(3), Some(4)), (None, None), (Some(5), None), (None, None)],
+        );
+        assert_eq!(sides[4].sheet, 2);
+        assert_eq!(conjugates(&document)[0], Conjugates {page: 1, backs_up_with: None, shares_side_with: Some(2)});
+    }
+
+    #[test]
+    fn test_impose_right_to_left() {
+        // page 1 on the left of the outer sheet, bound on the right
+        let mut document = DocumentInfo::new(1, 8).unwrap();
+        document.set_reading_direction(ReadingDirection::RightToLeft);
+        assert_eq!(
+            pairs(&impose(&document).unwrap()),
+            vec![(Some(1), Some(8)), (Some(7), Some(2)), (Some(3), Some(6)), (Some(5), Some(4))],
+        );
+        assert_eq!(locate(&document, 1).unwrap().column, 0);
+        // still backing up with the same pages
+        assert_eq!(conjugates(&document)[0], Conjugates {page: 1, backs_up_with: Some(2), shares_side_with: Some(8)});
+    }
+
+    #[test]
+    fn test_locate() {
+        let document = DocumentInfo::new(5, 26).unwrap();
+        assert_eq!(
+            locate(&document, 5),
+            Some(PageLocation {signature_key: "A".to_string(), sheet: 0, side: Side::Front, row: 0, column: 1}),
+        );
+        assert_eq!(
+            locate(&document, 17),
+            Some(PageLocation {signature_key: "A".to_string(), sheet: 1, side: Side::Back, row: 0, column: 1}),
+        );
+        assert_eq!(
+            locate(&document, 26),
+            Some(PageLocation {signature_key: "B".to_string(), sheet: 1, side: Side::Front, row: 0, column: 0}),
+        );
+        assert_eq!(locate(&document, 4), None);
+        assert_eq!(locate(&document, 27), None);
+    }
+
+    #[test]
+    fn test_conjugates() {
+        // - 1 | 2 - | 6 3 | 4 5
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let conjugates = conjugates(&document);
+        assert_eq!(conjugates.len(), 6);
+        assert_eq!(conjugates[0], Conjugates {page: 1, backs_up_with: Some(2), shares_side_with: None});
+        assert_eq!(conjugates[2], Conjugates {page: 3, backs_up_with: Some(4), shares_side_with: Some(6)});
+        assert_eq!(conjugates[5], Conjugates {page: 6, backs_up_with: Some(5), shares_side_with: Some(3)});
+    }
+
+    #[test]
+    fn test_page_size_parse() {
+        let page_size = PageSize::parse("148x210").unwrap();
+        assert_eq!(page_size, PageSize {width: 148.0, height: 210.0});
+        let page_size = PageSize::parse("215.9x279.4").unwrap();
+        assert_eq!(page_size, PageSize {width: 215.9, height: 279.4});
+        assert!(PageSize::parse("148").is_err());
+        assert!(PageSize::parse("0x210").is_err());
+        assert!(PageSize::parse("axb").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+// What a plan is for: the title, client, job number and due date, given on
+// the command line or in a project file, and carried into the header of
+// every report and the metadata of the PDFs written. Project files are
+// versioned, so one from a newer release is refused rather than misread.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::json::Json;
+
+// Constants
+// the current layout of project files; those without a version line are
+// version 1
+pub const PROJECT_VERSION: u32 = 1;
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct ProjectLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for ProjectLineError {}
+
+impl fmt::Display for ProjectLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the project file! Expected version, title, author, client, job or due, \
+             then a colon and the value, got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ProjectVersionError {
+    version: u32,
+}
+
+impl Error for ProjectVersionError {}
+
+impl fmt::Display for ProjectVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The project file is version {}, but this release reads up to version {}!",
+            self.version,
+            PROJECT_VERSION,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct DueDateError {
+    value: String,
+}
+
+impl Error for DueDateError {}
+
+impl fmt::Display for DueDateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Due dates should look like YYYY-MM-DD, e.g. 2026-11-01. Got: {}", self.value)
+    }
+}
+
+
+// Data structs
+// a line of a project file, kept to report it as it was written
+#[derive(Debug, Clone, PartialEq)]
+struct ProjectField {
+    line_number: usize,
+    line: String,
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JobInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub client: Option<String>,
+    pub job_id: Option<String>,
+    // YYYY-MM-DD
+    pub due: Option<String>,
+}
+
+impl JobInfo {
+    pub fn from_file(path: &str) -> Result<JobInfo, Box<dyn Error>> {
+        JobInfo::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<JobInfo, Box<dyn Error>> {
+        // One field per line, after the version:
+        //   version: 1
+        //   title: The Compleat Angler
+        //   client: Marriot Press
+        //   job: J-1041
+        //   due: 2026-11-01
+        // Blank lines and lines starting with # are ignored.
+        let fields = project_fields(contents)?;
+        let mut job = JobInfo::default();
+        for field in fields {
+            let value = field.value.clone();
+            match field.key.as_str() {
+                "title" => job.title = value,
+                "author" => job.author = value,
+                "client" => job.client = value,
+                "job" => job.job_id = value,
+                "due" => job.due = value.map(|value| parse_due_date(&value)).transpose()?,
+                _ => return Err((ProjectLineError {line_number: field.line_number, line: field.line}).into()),
+            }
+        }
+        Ok(job)
+    }
+
+    pub fn overridden_by(self, other: &JobInfo) -> JobInfo {
+        // the fields set in other, e.g. from flags, in place of these
+        JobInfo {
+            title: other.title.clone().or(self.title),
+            author: other.author.clone().or(self.author),
+            client: other.client.clone().or(self.client),
+            job_id: other.job_id.clone().or(self.job_id),
+            due: other.due.clone().or(self.due),
+        }
+    }
+
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        // the fields that are set, by name, in the order reports show them
+        [
+            ("Job", &self.job_id),
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Client", &self.client),
+            ("Due", &self.due),
+        ]
+        .iter()
+        .filter_map(|(name, value)| value.as_deref().map(|value| (*name, value)))
+        .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    pub fn header(&self, prefix: &str) -> String {
+        // a line per field, each after prefix, e.g. "# " for a script
+        self.fields()
+            .iter()
+            .map(|(name, value)| format!("{}{}: {}\n", prefix, name, value))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Json {
+        let field = |value: &Option<String>| value.as_deref().map_or(Json::Null, Json::string);
+        Json::object(vec![
+            ("job", field(&self.job_id)),
+            ("title", field(&self.title)),
+            ("author", field(&self.author)),
+            ("client", field(&self.client)),
+            ("due", field(&self.due)),
+        ])
+    }
+}
+
+
+// Work
+fn project_fields(contents: &str) -> Result<Vec<ProjectField>, Box<dyn Error>> {
+    // the fields after the version line, once the version is known to be one
+    // this release reads; a file without one is version 1
+    let mut fields = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = || ProjectLineError {line_number: i + 1, line: line.to_string()};
+        let (key, value) = line.split_once(':').ok_or_else(line_error)?;
+        let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        if key.trim() == "version" {
+            let version = value.and_then(|value| value.parse().ok()).ok_or_else(line_error)?;
+            if !(1..=PROJECT_VERSION).contains(&version) {
+                return Err((ProjectVersionError {version}).into());
+            }
+            continue;
+        }
+        fields.push(ProjectField {line_number: i + 1, line: line.to_string(), key: key.trim().to_string(), value});
+    }
+    Ok(fields)
+}
+
+pub fn parse_due_date(value: &str) -> Result<String, Box<dyn Error>> {
+    // a calendar date, YYYY-MM-DD, with the month and day in range
+    let due_date_error = || -> Box<dyn Error> { (DueDateError {value: value.to_string()}).into() };
+    let parts: Vec<&str> = value.trim().split('-').collect();
+    let numbers: Vec<u32> = match parts.as_slice() {
+        [year, month, day] if year.len() == 4 && month.len() == 2 && day.len() == 2 => {
+            parts.iter().map(|part| part.parse()).collect::<Result<_, _>>().map_err(|_| due_date_error())?
+        },
+        _ => return Err(due_date_error()),
+    };
+    match numbers.as_slice() {
+        [_, month, day] if (1..=12).contains(month) && (1..=31).contains(day) => Ok(value.trim().to_string()),
+        _ => Err(due_date_error()),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_project(job: &JobInfo) -> String {
+        // the fields that are set, in the current layout
+        let fields = [
+            ("title", &job.title),
+            ("author", &job.author),
+            ("client", &job.client),
+            ("job", &job.job_id),
+            ("due", &job.due),
+        ];
+        let mut project = format!("version: {}\n", PROJECT_VERSION);
+        for (key, value) in fields.iter() {
+            if let Some(value) = value {
+                project.push_str(&format!("{}: {}\n", key, value));
+            }
+        }
+        project
+    }
+
+    #[test]
+    fn test_job_info_parse() {
+        let job = JobInfo::parse(
+            "# the angler\nversion: 1\ntitle: The Compleat Angler\nclient: Marriot\n\njob: J-1041\ndue: 2026-11-01\n",
+        )
+        .unwrap();
+        assert_eq!(job.title.as_deref(), Some("The Compleat Angler"));
+        assert_eq!(job.due.as_deref(), Some("2026-11-01"));
+        assert_eq!(
+            job.header("# "),
+            "# Job: J-1041\n# Title: The Compleat Angler\n# Client: Marriot\n# Due: 2026-11-01\n",
+        );
+
+        let flags = JobInfo {title: Some("Poems".to_string()), ..JobInfo::default()};
+        let job = job.overridden_by(&flags);
+        assert_eq!(job.title.as_deref(), Some("Poems"));
+        assert_eq!(job.client.as_deref(), Some("Marriot"));
+        assert!(JobInfo::default().is_empty());
+
+        match JobInfo::parse("title: Poems\nprice: 12\n") {
+            Ok(_) => panic!("JobInfo::parse should have rejected an unknown field"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Couldn't read line 2 of the project file! Expected version, title, author, client, job or due, \
+                 then a colon and the value, got: price: 12",
+            ),
+        }
+        assert!(JobInfo::parse("due: next week\n").is_err());
+    }
+
+    #[test]
+    fn test_job_info_project_versions() {
+        // a file from before versions reads the same as one saying version 1
+        let job = JobInfo::parse("title: Poems\njob: J-1041\n").unwrap();
+        assert_eq!(job.job_id.as_deref(), Some("J-1041"));
+        assert_eq!(to_project(&job), "version: 1\ntitle: Poems\njob: J-1041\n");
+        assert_eq!(JobInfo::parse(&to_project(&job)).unwrap(), job);
+
+        assert!(JobInfo::parse("version: 0\n").is_err());
+        assert!(JobInfo::parse("version: two\n").is_err());
+        match JobInfo::parse("version: 2\ntitle: Poems\n") {
+            Ok(_) => panic!("JobInfo::parse should have rejected a file from a newer release"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "The project file is version 2, but this release reads up to version 1!",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_parse_due_date() {
+        assert_eq!(parse_due_date("2026-11-01").unwrap(), "2026-11-01");
+        assert!(parse_due_date("2026-13-01").is_err());
+        assert!(parse_due_date("1/11/2026").is_err());
+        match parse_due_date("26-11-01") {
+            Ok(_) => panic!("parse_due_date should have rejected a two digit year"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Due dates should look like YYYY-MM-DD, e.g. 2026-11-01. Got: 26-11-01",
+            ),
+        }
+    }
+}
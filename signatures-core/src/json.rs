@@ -0,0 +1,124 @@
+// A tiny JSON writer, enough for the crate's machine readable outputs
+// without pulling in any dependencies.
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    pub fn string(value: &str) -> Json {
+        Json::String(value.to_string())
+    }
+
+    pub fn page(page: Option<u32>) -> Json {
+        match page {
+            Some(page) => Json::Int(page as i64),
+            None => Json::Null,
+        }
+    }
+
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Int(value) => out.push_str(&value.to_string()),
+            Json::Float(value) => out.push_str(&format!("{:?}", value)),
+            Json::String(value) => out.push_str(&quote(value)),
+            Json::Array(values) if values.is_empty() => out.push_str("[]"),
+            Json::Array(values) => {
+                out.push_str("[\n");
+                for (i, value) in values.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    value.write(out, indent + 1);
+                    out.push_str(if i + 1 < values.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push(']');
+            },
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Object(fields) => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    out.push_str(&quote(key));
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                push_indent(out, indent);
+                out.push('}');
+            },
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+pub fn quote(value: &str) -> String {
+    // a JSON string literal, escaping quotes, backslashes and control characters
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("A"), "\"A\"");
+        assert_eq!(quote("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(quote("back\\slash"), "\"back\\\\slash\"");
+        assert_eq!(quote("\u{1}"), "\"\\u0001\"");
+        assert_eq!(quote("αβγ"), "\"αβγ\"");
+    }
+
+    #[test]
+    fn test_to_pretty_string() {
+        let json = Json::object(vec![
+            ("version", Json::Int(1)),
+            ("width", Json::Float(148.0)),
+            ("blank", Json::page(None)),
+            ("pages", Json::Array(vec![Json::page(Some(16)), Json::page(Some(1))])),
+            ("marks", Json::Array(vec![])),
+        ]);
+        assert_eq!(
+            json.to_pretty_string(),
+            "{\n  \"version\": 1,\n  \"width\": 148.0,\n  \"blank\": null,\n  \"pages\": [\n    16,\n    1\n  ],\n  \"marks\": []\n}\n",
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct NotEnoughLabelsError {
+    pub num_labels: usize,
+    pub num_signatures: usize,
+}
+
+impl Error for NotEnoughLabelsError {}
+
+impl fmt::Display for NotEnoughLabelsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Not enough signature labels! The labels file has {} but the document needs {}.",
+            self.num_labels,
+            self.num_signatures,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct NoLettersLeftError {
+    pub skip_letters: Vec<char>,
+}
+
+impl Error for NoLettersLeftError {}
+
+impl fmt::Display for NoLettersLeftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Skipping {:?} leaves no letters to label signatures with!", self.skip_letters)
+    }
+}
+
+#[derive(Debug)]
+pub struct SkipLetterError {
+    value: String,
+}
+
+impl Error for SkipLetterError {}
+
+impl fmt::Display for SkipLetterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Letters to skip should be single letters separated by commas, e.g. J,V,W. Got: {}", self.value)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownSignatureKeyError {
+    key: String,
+}
+
+impl Error for UnknownSignatureKeyError {}
+
+impl fmt::Display for UnknownSignatureKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} isn't a signature key in this labelling scheme!", self.key)
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alphabet {
+    #[default]
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+}
+
+impl Alphabet {
+    fn letters(&self) -> Vec<char> {
+        match self {
+            Alphabet::Latin => "ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect(),
+            // no final sigma
+            Alphabet::Greek => "αβγδεζηθικλμνξοπρστυφχψω".chars().collect(),
+            // no Ё, Й, Ъ, Ы or Ь, which never begin a word
+            Alphabet::Cyrillic => "АБВГДЕЖЗИКЛМНОПРСТУФХЦЧШЩЭЮЯ".chars().collect(),
+            // alef to tav, skipping the final forms of kaf, mem, nun, pe and tsadi
+            Alphabet::Hebrew => "אבגדהוזחטיכלמנסעפצקרשת".chars().collect(),
+        }
+    }
+
+    fn is_right_to_left(&self) -> bool {
+        *self == Alphabet::Hebrew
+    }
+}
+
+// how labels carry on once every letter has been used once
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Continuation {
+    // AA, AB, ... AZ, BA, ...
+    #[default]
+    Sequential,
+    // AA, BB, CC, ... then AAA, BBB, ...
+    Doubled,
+    // Aa, Bb, Cc, ... then Aaa, Bbb, ...
+    Paired,
+    // 2A, 2B, 2C, ... then 3A, 3B, ...
+    Numbered,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelScheme {
+    pub alphabet: Alphabet,
+    pub continuation: Continuation,
+    // letters of the alphabet left out of the labels, e.g. J, U and W
+    pub skip_letters: Vec<char>,
+    // explicit labels to use instead of the alphabet, in order
+    pub sequence: Vec<String>,
+    // marks a gathering of half the usual size, e.g. D½ or D2
+    pub half_suffix: Option<String>,
+}
+
+impl LabelScheme {
+    pub fn key(&self, signature_i: u32) -> String {
+        // Get the letter code that identifies a signature, carrying on after
+        // the last letter in the scheme's continuation style. An explicit
+        // sequence of labels is used as is.
+        if let Some(label) = self.sequence.get(signature_i as usize) {
+            return label.clone();
+        }
+        let letters = self.letters();
+        if letters.is_empty() {
+            return String::new();
+        }
+        let letter = letters[signature_i as usize % letters.len()];
+        let cycle = signature_i as usize / letters.len();
+        match self.continuation {
+            Continuation::Sequential => sequential_key(&letters, signature_i),
+            Continuation::Doubled => (0..=cycle).map(|_| letter).collect(),
+            Continuation::Paired => {
+                let lower: String = letter.to_lowercase().collect();
+                let mut key = letter.to_string();
+                for _ in 0..cycle {
+                    key.push_str(&lower);
+                }
+                key
+            },
+            Continuation::Numbered if cycle == 0 => letter.to_string(),
+            Continuation::Numbered => format!("{}{}", cycle + 1, letter),
+        }
+    }
+
+    pub fn parse_key(&self, key: &str) -> Result<u32, Box<dyn Error>> {
+        // The inverse of key(): find which signature a key belongs to.
+        // Letters may be in either case, except where the continuation style
+        // depends on it, since marks read off sheets are often written by hand.
+        let unknown_key = || -> Box<dyn Error> { (UnknownSignatureKeyError {key: key.to_string()}).into() };
+        let key = key.trim().trim_start_matches('\u{2067}').trim_end_matches('\u{2069}');
+        let key = match &self.half_suffix {
+            Some(suffix) => key.strip_suffix(suffix.as_str()).unwrap_or(key),
+            None => key,
+        };
+        if !self.sequence.is_empty() {
+            let position = self.sequence.iter().position(|label| label == key).ok_or_else(unknown_key)?;
+            return Ok(position as u32);
+        }
+        let letters = self.letters();
+        let n = letters.len() as u32;
+        let letter_i = |c: char| letters.iter().position(|letter| same_letter(*letter, c)).map(|i| i as u32);
+        let chars: Vec<char> = key.chars().collect();
+        let (first, rest) = chars.split_first().ok_or_else(unknown_key)?;
+        let index = match self.continuation {
+            Continuation::Sequential => chars.iter().try_fold(0u32, |value, c| {
+                value.checked_mul(n)?.checked_add(letter_i(*c)? + 1)
+            }).map(|value| value - 1),
+            Continuation::Doubled => rest
+                .iter()
+                .all(|c| same_letter(*c, *first))
+                .then(|| letter_i(*first))
+                .flatten()
+                .and_then(|letter| (rest.len() as u32).checked_mul(n)?.checked_add(letter)),
+            Continuation::Paired => rest
+                .iter()
+                .all(|c| same_letter(*c, *first) && !c.is_uppercase())
+                .then(|| letter_i(*first).filter(|_| !first.is_lowercase()))
+                .flatten()
+                .and_then(|letter| (rest.len() as u32).checked_mul(n)?.checked_add(letter)),
+            Continuation::Numbered => {
+                let (digits, letter) = key.split_at(key.find(|c: char| !c.is_ascii_digit()).unwrap_or(key.len()));
+                let cycle = match digits {
+                    "" => Some(1),
+                    _ => digits.parse::<u32>().ok().filter(|cycle| *cycle >= 2),
+                };
+                let mut letter = letter.chars();
+                match (cycle, letter.next(), letter.next()) {
+                    (Some(cycle), Some(letter), None) => {
+                        letter_i(letter).and_then(|letter| (cycle - 1).checked_mul(n)?.checked_add(letter))
+                    },
+                    _ => None,
+                }
+            },
+        };
+        index.ok_or_else(unknown_key)
+    }
+
+    pub fn half_key(&self, key: &str) -> String {
+        // the key of a half-size gathering, if the scheme marks them
+        format!("{}{}", key, self.half_suffix.as_deref().unwrap_or(""))
+    }
+
+    pub fn letters(&self) -> Vec<char> {
+        // the alphabet, less any skipped letters (in either case)
+        self.alphabet
+            .letters()
+            .into_iter()
+            .filter(|letter| !self.skip_letters.iter().any(|skip| same_letter(*skip, *letter)))
+            .collect()
+    }
+
+    pub fn display_key(&self, key: &str) -> String {
+        // Right-to-left keys are wrapped in Unicode directional isolates so
+        // they read correctly inside left-to-right report lines.
+        if self.alphabet.is_right_to_left() {
+            format!("\u{2067}{}\u{2069}", key)
+        } else {
+            key.to_string()
+        }
+    }
+}
+
+
+
+// Work
+pub fn parse_skip_letters(value: &str) -> Result<Vec<char>, Box<dyn Error>> {
+    // e.g. "J,V,W" or "I, U, W"
+    let mut letters = Vec::new();
+    for letter in value.split(',') {
+        let mut chars = letter.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => letters.push(c),
+            _ => return Err((SkipLetterError {value: value.to_string()}).into()),
+        }
+    }
+    Ok(letters)
+}
+
+fn sequential_key(letters: &[char], signature_i: u32) -> String {
+    // AA, AB, ... like spreadsheet columns
+    let mut key = Vec::new();
+    let mut i = signature_i as usize;
+    loop {
+        key.push(letters[i % letters.len()]);
+        i /= letters.len();
+        if i == 0 {
+            break;
+        }
+        i -= 1;
+    }
+    key.iter().rev().collect() // needs to be reversed since we're appending to the right
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a.to_lowercase().eq(b.to_lowercase())
+}
+
+pub fn read_label_file(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(parse_label_sequence(&fs::read_to_string(path)?))
+}
+
+pub fn parse_label_sequence(contents: &str) -> Vec<String> {
+    // one label per line, skipping blank lines
+    contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greek_keys() {
+        let scheme = LabelScheme {alphabet: Alphabet::Greek, ..LabelScheme::default()};
+        assert_eq!(scheme.key(0), "α");
+        assert_eq!(scheme.key(17), "σ");
+        assert_eq!(scheme.key(23), "ω");
+        assert_eq!(scheme.key(24), "αα");
+        assert_eq!(scheme.key(25), "αβ");
+        assert_eq!(scheme.key(48), "βα");
+    }
+
+    #[test]
+    fn test_cyrillic_keys() {
+        let scheme = LabelScheme {alphabet: Alphabet::Cyrillic, ..LabelScheme::default()};
+        assert_eq!(scheme.key(0), "А");
+        assert_eq!(scheme.key(8), "И");
+        assert_eq!(scheme.key(9), "К");
+        assert_eq!(scheme.key(27), "Я");
+        assert_eq!(scheme.key(28), "АА");
+        assert_eq!(scheme.key(57), "ББ");
+    }
+
+    #[test]
+    fn test_hebrew_keys() {
+        let scheme = LabelScheme {alphabet: Alphabet::Hebrew, ..LabelScheme::default()};
+        assert_eq!(scheme.key(0), "א");
+        assert_eq!(scheme.key(10), "כ");
+        assert_eq!(scheme.key(11), "ל");
+        assert_eq!(scheme.key(21), "ת");
+        assert_eq!(scheme.key(22), "אא");
+        assert_eq!(scheme.display_key("אב"), "\u{2067}אב\u{2069}");
+        assert_eq!(LabelScheme::default().display_key("AB"), "AB");
+    }
+
+    #[test]
+    fn test_skip_letters() {
+        let scheme = LabelScheme {skip_letters: parse_skip_letters("J,U,W").unwrap(), ..LabelScheme::default()};
+        assert_eq!(scheme.letters().len(), 23);
+        assert_eq!(scheme.key(8), "I");
+        assert_eq!(scheme.key(9), "K");
+        assert_eq!(scheme.key(18), "T");
+        assert_eq!(scheme.key(19), "V");
+        assert_eq!(scheme.key(20), "X");
+        assert_eq!(scheme.key(23), "AA");
+
+        // case doesn't matter
+        let scheme = LabelScheme {skip_letters: parse_skip_letters("i, u ,w").unwrap(), ..LabelScheme::default()};
+        assert_eq!(scheme.key(8), "J");
+
+        assert!(parse_skip_letters("J,VW").is_err());
+        assert!(parse_skip_letters("J,,W").is_err());
+    }
+
+    #[test]
+    fn test_continuation() {
+        let keys = |continuation| {
+            let scheme = LabelScheme {continuation, ..LabelScheme::default()};
+            vec![scheme.key(0), scheme.key(25), scheme.key(26), scheme.key(27), scheme.key(52)]
+        };
+        assert_eq!(keys(Continuation::Sequential), vec!["A", "Z", "AA", "AB", "BA"]);
+        assert_eq!(keys(Continuation::Doubled), vec!["A", "Z", "AA", "BB", "AAA"]);
+        assert_eq!(keys(Continuation::Paired), vec!["A", "Z", "Aa", "Bb", "Aaa"]);
+        assert_eq!(keys(Continuation::Numbered), vec!["A", "Z", "2A", "2B", "3A"]);
+    }
+
+    #[test]
+    fn test_parse_key() {
+        for continuation in [Continuation::Sequential, Continuation::Doubled, Continuation::Paired, Continuation::Numbered] {
+            let scheme = LabelScheme {continuation, skip_letters: vec!['J'], ..LabelScheme::default()};
+            for i in [0, 1, 24, 25, 26, 50, 700, 5000] {
+                assert_eq!(scheme.parse_key(&scheme.key(i)).unwrap(), i);
+            }
+        }
+        let scheme = LabelScheme::default();
+        assert_eq!(scheme.parse_key("ab").unwrap(), 27);
+        assert_eq!(scheme.parse_key(" Z ").unwrap(), 25);
+        let scheme = LabelScheme {alphabet: Alphabet::Hebrew, ..LabelScheme::default()};
+        assert_eq!(scheme.parse_key(&scheme.display_key("אב")).unwrap(), 23);
+
+        let scheme = LabelScheme {skip_letters: vec!['J'], ..LabelScheme::default()};
+        match scheme.parse_key("J") {
+            Ok(_) => panic!("J shouldn't be a signature key when it's skipped"),
+            Err(error) => assert_eq!(format!("{}", error), "J isn't a signature key in this labelling scheme!"),
+        }
+        assert!(scheme.parse_key("").is_err());
+        assert!(scheme.parse_key("A1").is_err());
+        assert!(LabelScheme::default().parse_key("ZZZZZZZZZZZZ").is_err());
+        let scheme = LabelScheme {continuation: Continuation::Doubled, ..LabelScheme::default()};
+        assert!(scheme.parse_key("AB").is_err());
+        let scheme = LabelScheme {continuation: Continuation::Paired, ..LabelScheme::default()};
+        assert!(scheme.parse_key("AA").is_err());
+        let scheme = LabelScheme {continuation: Continuation::Numbered, ..LabelScheme::default()};
+        assert!(scheme.parse_key("1A").is_err());
+        let scheme = LabelScheme {sequence: vec!["*".to_string(), "A".to_string()], ..LabelScheme::default()};
+        assert_eq!(scheme.parse_key("A").unwrap(), 1);
+        assert!(scheme.parse_key("B").is_err());
+    }
+
+    #[test]
+    fn test_label_sequence() {
+        let sequence = parse_label_sequence("π\n  A  \n\nB\n*\n");
+        assert_eq!(sequence, vec!["π", "A", "B", "*"]);
+        let scheme = LabelScheme {sequence, ..LabelScheme::default()};
+        assert_eq!(scheme.key(0), "π");
+        assert_eq!(scheme.key(3), "*");
+    }
+}
@@ -0,0 +1,1597 @@
+// The planning itself: the signatures, sheets and pages of a book worked out
+// from its page range, with nothing beyond the standard library, so it can
+// be built into other software. The command line tool is layered on top.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use imposition::{FoldScheme, ReadingDirection, SheetForme};
+use job::JobInfo;
+use json::Json;
+use labels::LabelScheme;
+
+pub mod config;
+pub mod constraints;
+pub mod imposition;
+pub mod job;
+pub mod json;
+pub mod labels;
+pub mod multi_up;
+
+// Constants
+pub const DOC_PAGES_PER_SHEET: u32 = 4;
+pub const DOC_PAGES_PER_SIGNATURE: u32 = 16;
+// printed on one side only, a sheet carries two pages side by side
+pub const ONE_SIDED_PAGES_PER_SHEET: u32 = 2;
+// 64-bit FNV-1a, for checksums that stay the same between builds
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+// for the leaf counts in collation formulae
+pub const SUPERSCRIPT_DIGITS: [char; 10] = ['\u{2070}', '\u{b9}', '\u{b2}', '\u{b3}', '\u{2074}', '\u{2075}', '\u{2076}', '\u{2077}', '\u{2078}', '\u{2079}'];
+
+
+// Custom errors
+#[derive(Debug)]
+pub struct NeedTwoArgumentsError {
+    received_args: Vec<String>,
+}
+
+impl Error for NeedTwoArgumentsError {}
+
+impl fmt::Display for NeedTwoArgumentsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Need at least two arguments to run! Got: {:?}", &self.received_args[1..])
+    }
+}
+
+#[derive(Debug)]
+pub struct PageZeroError;
+
+impl Error for PageZeroError {}
+
+impl fmt::Display for PageZeroError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "There is no page zero! Received 0 as the first page number.")
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidSignatureSizeError {
+    pages_per_signature: u32,
+}
+
+impl Error for InvalidSignatureSizeError {}
+
+impl fmt::Display for InvalidSignatureSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Signature size must be a positive multiple of {} pages! Got {}.",
+            DOC_PAGES_PER_SHEET,
+            self.pages_per_signature,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct FoldSchemeError {
+    fold_scheme: FoldScheme,
+    pages_per_signature: u32,
+}
+
+impl Error for FoldSchemeError {}
+
+impl fmt::Display for FoldSchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A sheet folded in {} has {} pages, so signatures must be a multiple of {} pages! Got {}.",
+            self.fold_scheme.name(),
+            self.fold_scheme.pages_per_sheet(),
+            self.fold_scheme.pages_per_sheet(),
+            self.pages_per_signature,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct SecondNumberGreaterError {
+    first_number: u32,
+    second_number: u32,
+}
+
+impl Error for SecondNumberGreaterError {}
+
+impl fmt::Display for SecondNumberGreaterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The second number must be greater than or equal to the first! {} > {}.",
+            self.first_number,
+            self.second_number,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct OverflowError {
+    pub first_page: u32,
+    pub last_page: u32,
+}
+
+impl Error for OverflowError {}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Pages {} to {} are too many to plan! Page numbers, blanks included, can't go past {}.",
+            self.first_page,
+            self.last_page,
+            u32::MAX,
+        )
+    }
+}
+
+
+// Data structs
+// How the pages are shared out between the signatures, a whole folded sheet
+// at a time, so the last isn't left a runt of a sheet or two.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BalanceStrategy {
+    // every signature full but the last, which takes what's left
+    #[default]
+    None,
+    // as many signatures, differing by no more than a sheet, the larger first
+    Even,
+    // a last signature under half the size of the rest bound into the one
+    // before, which grows by as much
+    MinimizeLast,
+}
+
+impl BalanceStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BalanceStrategy::None => "none",
+            BalanceStrategy::Even => "even",
+            BalanceStrategy::MinimizeLast => "last",
+        }
+    }
+}
+
+// what DocumentInfo::new_with_options plans with, the usual plan by default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DocumentOptions {
+    pub pages_per_signature: u32,
+    pub balance: BalanceStrategy,
+    pub fold_scheme: FoldScheme,
+}
+
+impl Default for DocumentOptions {
+    fn default() -> DocumentOptions {
+        DocumentOptions {
+            pages_per_signature: DOC_PAGES_PER_SIGNATURE,
+            balance: BalanceStrategy::default(),
+            fold_scheme: FoldScheme::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signature {
+   first_page: u32,
+   last_page: u32,
+   signature_key: String,
+   #[cfg_attr(feature = "serde", serde(default))]
+   fold_scheme: FoldScheme,
+}
+
+impl Signature {
+    pub fn first_page(&self) -> u32 {
+        self.first_page
+    }
+
+    pub fn last_page(&self) -> u32 {
+        self.last_page
+    }
+
+    pub fn num_pages(&self) -> u32 {
+        self.last_page - self.first_page + 1
+    }
+
+    pub fn key(&self) -> &str {
+        &self.signature_key
+    }
+
+    pub fn pages(&self) -> RangeInclusive<u32> {
+        // every page number in the gathering, first to last, as the plan
+        // counts them (see DocumentInfo::shown_page for zero indexing)
+        self.first_page..=self.last_page
+    }
+
+    pub fn imposition(&self) -> Vec<SheetForme> {
+        // The pages to lay on each side of the gathering's sheets, outermost
+        // sheet first and its front before its back, folded as the plan's
+        // fold scheme says and printed on both sides, with blanks past the
+        // last page as None.
+        imposition::impose_signature(self)
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentInfo {
+    first_page: u32,
+    num_pages: u32,
+    num_sheets: u32,
+    num_signatures: u32,
+    pages_per_signature: u32,
+    signatures: Vec<Signature>,
+    label_scheme: LabelScheme,
+    // report page numbers counting from 0, as PDF libraries do
+    zero_indexed: bool,
+    // shown at the head of the reports
+    job: JobInfo,
+    // each sheet printed on its front only and cut into two leaves, e.g.
+    // for a stab binding, rather than printed both sides and folded
+    one_sided: bool,
+    reading_direction: ReadingDirection,
+    #[cfg_attr(feature = "serde", serde(default))]
+    fold_scheme: FoldScheme,
+}
+
+impl DocumentInfo {
+    pub fn new(first_number: u32, second_number: u32) -> Result<DocumentInfo, Box<dyn Error>> {
+        DocumentInfo::with_signature_size(first_number, second_number, DOC_PAGES_PER_SIGNATURE)
+    }
+
+    pub fn with_signature_size(
+        first_number: u32,
+        second_number: u32,
+        pages_per_signature: u32,
+    ) -> Result<DocumentInfo, Box<dyn Error>> {
+        let options = DocumentOptions {pages_per_signature, ..DocumentOptions::default()};
+        DocumentInfo::new_with_options(first_number, second_number, &options)
+    }
+
+    pub fn new_with_options(
+        first_number: u32,
+        second_number: u32,
+        options: &DocumentOptions,
+    ) -> Result<DocumentInfo, Box<dyn Error>> {
+        // Calculate the number of pages, sheets and signatures in the
+        // document. Every page number worked out later on, up to the end of
+        // a last signature padded out with blanks, has to fit in a u32, so
+        // that's checked once here.
+        let pages_per_signature = options.pages_per_signature;
+        if let Some(problem) = page_range_problems(first_number, second_number, pages_per_signature).into_iter().next() {
+            return Err(problem);
+        }
+        let overflow = || OverflowError {first_page: first_number, last_page: second_number};
+        let num_pages = (second_number - first_number).checked_add(1).ok_or_else(overflow)?;
+        let num_sheets = num_pages.div_ceil(DOC_PAGES_PER_SHEET);
+        let num_signatures = num_pages.div_ceil(pages_per_signature);
+        num_signatures
+            .checked_mul(pages_per_signature)
+            .and_then(|padded_pages| padded_pages.checked_add(first_number - 1))
+            .ok_or_else(overflow)?;
+        // balancing moves whole folded sheets, so the signatures have to be
+        // made of them before anything is moved
+        if !pages_per_signature.is_multiple_of(options.fold_scheme.pages_per_sheet()) {
+            return Err((FoldSchemeError {fold_scheme: options.fold_scheme, pages_per_signature}).into());
+        }
+        let signatures = get_signatures(first_number, num_pages, num_signatures, pages_per_signature);
+        let signatures = balance_signatures(signatures, options);
+        let mut document = DocumentInfo {
+            first_page: first_number,
+            num_pages,
+            num_sheets,
+            num_signatures: signatures.len() as u32,
+            pages_per_signature,
+            signatures,
+            label_scheme: LabelScheme::default(),
+            zero_indexed: false,
+            job: JobInfo::default(),
+            one_sided: false,
+            reading_direction: ReadingDirection::default(),
+            fold_scheme: FoldScheme::default(),
+        };
+        document.set_fold_scheme(options.fold_scheme)?;
+        Ok(document)
+    }
+
+    pub fn with_gatherings(first_number: u32, gatherings: &[u32]) -> Result<DocumentInfo, Box<dyn Error>> {
+        // A plan of signatures each with its own number of pages, in order,
+        // e.g. from a collation formula. The largest stands as the
+        // signature size.
+        let pages_per_signature = gatherings.iter().copied().max().unwrap_or(0);
+        let invalid = gatherings.iter().find(|pages| **pages == 0 || !pages.is_multiple_of(DOC_PAGES_PER_SHEET));
+        if let Some(pages_per_signature) = invalid {
+            return Err((InvalidSignatureSizeError {pages_per_signature: *pages_per_signature}).into());
+        }
+        let overflow = || OverflowError {first_page: first_number, last_page: u32::MAX};
+        let num_pages = gatherings.iter().try_fold(0u32, |total, pages| total.checked_add(*pages)).ok_or_else(overflow)?;
+        let second_number = num_pages.checked_add(first_number).ok_or_else(overflow)?.saturating_sub(1);
+        if let Some(problem) = page_range_problems(first_number, second_number, pages_per_signature).into_iter().next() {
+            return Err(problem);
+        }
+        let mut signatures = Vec::new();
+        let mut first_page = first_number;
+        for (i, pages) in gatherings.iter().enumerate() {
+            signatures.push(Signature {
+                first_page,
+                last_page: first_page + pages - 1,
+                signature_key: get_signature_key(i as u32),
+                fold_scheme: FoldScheme::default(),
+            });
+            first_page += pages;
+        }
+        Ok(DocumentInfo {
+            first_page: first_number,
+            num_pages,
+            num_sheets: num_pages / DOC_PAGES_PER_SHEET,
+            num_signatures: signatures.len() as u32,
+            pages_per_signature,
+            signatures,
+            label_scheme: LabelScheme::default(),
+            zero_indexed: false,
+            job: JobInfo::default(),
+            one_sided: false,
+            reading_direction: ReadingDirection::default(),
+            fold_scheme: FoldScheme::default(),
+        })
+    }
+
+    pub fn first_page(&self) -> u32 {
+        self.first_page
+    }
+
+    pub fn last_page(&self) -> u32 {
+        self.first_page + (self.num_pages - 1)
+    }
+
+    pub fn num_pages(&self) -> u32 {
+        self.num_pages
+    }
+
+    pub fn num_sheets(&self) -> u32 {
+        self.num_sheets
+    }
+
+    pub fn num_signatures(&self) -> u32 {
+        self.num_signatures
+    }
+
+    pub fn pages_per_signature(&self) -> u32 {
+        self.pages_per_signature
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    pub fn label_scheme(&self) -> &LabelScheme {
+        &self.label_scheme
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Signature> {
+        self.signatures.iter()
+    }
+
+    pub fn num_leaves(&self) -> u32 {
+        self.num_sheets * self.leaves_per_sheet()
+    }
+
+    pub fn pages_per_sheet(&self) -> u32 {
+        if self.one_sided {
+            ONE_SIDED_PAGES_PER_SHEET
+        } else {
+            self.fold_scheme.pages_per_sheet()
+        }
+    }
+
+    pub fn leaves_per_sheet(&self) -> u32 {
+        // a sheet cut once into two leaves, or folded into half as many
+        // leaves as it has pages
+        if self.one_sided {
+            2
+        } else {
+            self.pages_per_sheet() / 2
+        }
+    }
+
+    pub fn is_one_sided(&self) -> bool {
+        self.one_sided
+    }
+
+    pub fn num_rectos(&self) -> u32 {
+        // Printed pages that fall on the front of a leaf. Signatures always
+        // start on a recto, so these are every other page from the first,
+        // or every page when the backs are left unprinted.
+        if self.one_sided {
+            self.num_pages
+        } else {
+            self.num_pages.div_ceil(2)
+        }
+    }
+
+    pub fn num_versos(&self) -> u32 {
+        // printed pages that fall on the back of a leaf
+        if self.one_sided {
+            0
+        } else {
+            self.num_pages / 2
+        }
+    }
+
+    pub fn num_printed_pages(&self) -> u32 {
+        self.num_pages
+    }
+
+    pub fn num_blank_pages(&self) -> u32 {
+        // blank pages left over on the last sheet
+        self.num_sheets * self.pages_per_sheet() - self.num_pages
+    }
+
+    pub fn blank_page_ratio(&self) -> f64 {
+        // the share of the page slots on the sheets left blank, from 0 to 1
+        f64::from(self.num_blank_pages()) / (f64::from(self.num_sheets) * f64::from(self.pages_per_sheet()))
+    }
+
+    pub fn sheet_utilisation(&self) -> f64 {
+        // the percentage of the page slots on the sheets that are printed
+        100.0 * (1.0 - self.blank_page_ratio())
+    }
+
+    pub fn pages_short_of_full_signature(&self) -> u32 {
+        // how many more pages would fill the last signature
+        self.signatures.last().map_or(0, |signature| {
+            self.pages_per_signature.saturating_sub(signature.last_page - signature.first_page + 1)
+        })
+    }
+
+    pub fn page_count_is_ideal(&self) -> bool {
+        // every signature full, so none is folded from fewer sheets
+        self.pages_short_of_full_signature() == 0
+    }
+
+    pub fn signature_leaves(&self) -> Vec<(String, u32)> {
+        // the number of leaves in each signature, by key
+        self.signatures
+            .iter()
+            .map(|signature| {
+                let num_pages = signature.last_page - signature.first_page + 1;
+                (signature.signature_key.clone(), num_pages.div_ceil(self.pages_per_sheet()) * self.leaves_per_sheet())
+            })
+            .collect()
+    }
+
+    pub fn collation_formula(&self) -> String {
+        // The bibliographer's statement of the gatherings, e.g. A–G⁸ H⁴:
+        // runs of signatures with the same number of leaves, with the leaves
+        // as a superscript.
+        let mut runs: Vec<(String, String, u32)> = Vec::new();
+        for (key, leaves) in self.signature_leaves() {
+            let key = self.label_scheme.display_key(&key);
+            match runs.last_mut() {
+                Some(run) if run.2 == leaves => run.1 = key,
+                _ => runs.push((key.clone(), key, leaves)),
+            }
+        }
+        runs.iter()
+            .map(|(first, last, leaves)| {
+                if first == last {
+                    format!("{}{}", first, superscript(*leaves))
+                } else {
+                    format!("{}\u{2013}{}{}", first, last, superscript(*leaves))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    pub fn sheet_of(&self, page: u32) -> Option<imposition::PageLocation> {
+        // the signature, sheet, side, row and column a page is printed on, or None
+        // if the page isn't in the document
+        imposition::locate(self, page)
+    }
+
+    pub fn conjugate_of(&self, page: u32) -> Option<imposition::Conjugates> {
+        // the pages backing up and sharing a sheet side with a page, or None
+        // if the page isn't in the document
+        imposition::conjugates(self).into_iter().find(|conjugate| conjugate.page == page)
+    }
+
+    pub fn set_zero_indexed(&mut self, zero_indexed: bool) {
+        // Page numbers are always counted from 1 inside the plan; this only
+        // changes how they're reported.
+        self.zero_indexed = zero_indexed;
+    }
+
+    pub fn set_one_sided(&mut self, one_sided: bool) {
+        // The signatures stay the same; only the sheets they're printed on
+        // change, with half as many pages on each and no padding out to a
+        // sheet's back.
+        self.one_sided = one_sided;
+        self.num_sheets = self.num_pages.div_ceil(self.pages_per_sheet());
+    }
+
+    pub fn set_fold_scheme(&mut self, fold_scheme: FoldScheme) -> Result<(), Box<dyn Error>> {
+        // Every signature has to be made of whole folded sheets, apart from
+        // the last, which is padded out with blanks.
+        let pages_per_sheet = fold_scheme.pages_per_sheet();
+        let mut sizes = vec![self.pages_per_signature];
+        sizes.extend(self.signatures.iter().rev().skip(1).map(|signature| signature.last_page - signature.first_page + 1));
+        if let Some(&pages_per_signature) = sizes.iter().find(|pages| !pages.is_multiple_of(pages_per_sheet)) {
+            return Err((FoldSchemeError {fold_scheme, pages_per_signature}).into());
+        }
+        self.fold_scheme = fold_scheme;
+        for signature in &mut self.signatures {
+            signature.fold_scheme = fold_scheme;
+        }
+        self.num_sheets = self.num_pages.div_ceil(self.pages_per_sheet());
+        Ok(())
+    }
+
+    pub fn fold_scheme(&self) -> FoldScheme {
+        self.fold_scheme
+    }
+
+    pub fn set_reading_direction(&mut self, direction: ReadingDirection) {
+        // the same signatures, imposed the other way round
+        self.reading_direction = direction;
+    }
+
+    pub fn reading_direction(&self) -> ReadingDirection {
+        self.reading_direction
+    }
+
+    pub fn set_job(&mut self, job: JobInfo) {
+        self.job = job;
+    }
+
+    pub fn job(&self) -> &JobInfo {
+        &self.job
+    }
+
+    pub fn is_zero_indexed(&self) -> bool {
+        self.zero_indexed
+    }
+
+    pub fn shown_page(&self, page: u32) -> u32 {
+        if self.zero_indexed {
+            page - 1
+        } else {
+            page
+        }
+    }
+
+    pub fn relabel(&mut self, scheme: &LabelScheme) -> Result<(), Box<dyn Error>> {
+        // Re-key the signatures using a different label scheme, making sure
+        // there are letters left to use, and that an explicit list of labels
+        // has one for every signature.
+        if scheme.sequence.is_empty() && scheme.letters().is_empty() {
+            return Err((labels::NoLettersLeftError {skip_letters: scheme.skip_letters.clone()}).into());
+        }
+        if !scheme.sequence.is_empty() && scheme.sequence.len() < self.signatures.len() {
+            return Err((labels::NotEnoughLabelsError {
+                num_labels: scheme.sequence.len(),
+                num_signatures: self.signatures.len(),
+            }).into());
+        }
+        // A gathering of half as many sheets as the rest keeps its place in
+        // the sequence but is marked as such, rather than passing for a
+        // whole signature.
+        let pages_per_sheet = self.fold_scheme.pages_per_sheet();
+        let sheets_per_signature = self.pages_per_signature / pages_per_sheet;
+        for (i, signature) in self.signatures.iter_mut().enumerate() {
+            let num_sheets = (signature.last_page - signature.first_page + 1).div_ceil(pages_per_sheet);
+            let is_half = sheets_per_signature.is_multiple_of(2) && num_sheets == sheets_per_signature / 2;
+            signature.signature_key = if is_half { scheme.half_key(&scheme.key(i as u32)) } else { scheme.key(i as u32) };
+        }
+        self.label_scheme = scheme.clone();
+        Ok(())
+    }
+
+    pub fn display(&self) {
+        print!("{}", self);
+    }
+
+    pub fn plain_text(&self) -> String {
+        // The same plan for screen readers and speech output: whole
+        // sentences, one fact per line, and no decorations to read out.
+        let count = |number: u32, noun: &str| match number {
+            1 => format!("1 {}", noun),
+            _ => format!("{} {}s", number, noun),
+        };
+        let mut lines: Vec<String> = self.job.fields().iter().map(|(name, value)| format!("{}: {}.", name, value)).collect();
+        lines.extend([
+            format!("The document has {} to print.", count(self.num_pages, "page")),
+            format!("Printing it takes {}.", count(self.num_sheets, "sheet")),
+            format!(
+                "It is bound in {}, each of up to {}.",
+                count(self.num_signatures, "signature"),
+                count(self.pages_per_signature / self.pages_per_sheet(), "sheet"),
+            ),
+            format!("{} left blank.", match self.num_blank_pages() {
+                0 => "No pages are".to_string(),
+                1 => "1 page is".to_string(),
+                blanks => format!("{} pages are", blanks),
+            }),
+        ]);
+        for signature in &self.signatures {
+            lines.push(format!(
+                "Signature {} begins at page {} and ends at page {}.",
+                signature.signature_key,
+                self.shown_page(signature.first_page),
+                self.shown_page(signature.last_page),
+            ));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    pub fn plain_v1(&self) -> String {
+        // A fixed layout for scripts, which will not change; anything new
+        // goes in a plain-v2. One record per line, fields separated by a
+        // tab, in this order:
+        //   format plain-v1
+        //   first_page N, last_page N, num_pages N, num_sheets N,
+        //   num_signatures N, pages_per_signature N, num_blank_pages N
+        //   signature KEY FIRST LAST, once for each signature in order
+        let mut lines = vec![
+            "format\tplain-v1".to_string(),
+            format!("first_page\t{}", self.shown_page(self.first_page)),
+            format!("last_page\t{}", self.shown_page(self.last_page())),
+            format!("num_pages\t{}", self.num_pages),
+            format!("num_sheets\t{}", self.num_sheets),
+            format!("num_signatures\t{}", self.num_signatures),
+            format!("pages_per_signature\t{}", self.pages_per_signature),
+            format!("num_blank_pages\t{}", self.num_blank_pages()),
+        ];
+        for signature in &self.signatures {
+            lines.push(format!(
+                "signature\t{}\t{}\t{}",
+                signature.signature_key,
+                self.shown_page(signature.first_page),
+                self.shown_page(signature.last_page),
+            ));
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        // The signature table for a spreadsheet: a header row, then a row
+        // for each signature with its pages and the sheets it's folded from.
+        let mut csv = csv_line(&["Signature", "First page", "Last page", "Pages", "Sheets"]);
+        for signature in &self.signatures {
+            csv.push_str(&csv_line(&[
+                &self.label_scheme.display_key(&signature.signature_key),
+                &self.shown_page(signature.first_page).to_string(),
+                &self.shown_page(signature.last_page).to_string(),
+                &signature.num_pages().to_string(),
+                &signature.num_pages().div_ceil(self.pages_per_sheet()).to_string(),
+            ]));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        // the plan summary for scripts, with the same numbers as display(),
+        // and the job it's for if that's been given
+        let mut json = self.plan_json();
+        if let Json::Object(fields) = &mut json {
+            if !self.job.is_empty() {
+                fields.insert(0, ("job".to_string(), self.job.to_json()));
+            }
+        }
+        json.to_pretty_string()
+    }
+
+    fn plan_json(&self) -> Json {
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|signature| {
+                Json::object(vec![
+                    ("key", Json::string(&signature.signature_key)),
+                    ("first_page", Json::Int(self.shown_page(signature.first_page) as i64)),
+                    ("last_page", Json::Int(self.shown_page(signature.last_page) as i64)),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("first_page", Json::Int(self.shown_page(self.first_page) as i64)),
+            ("last_page", Json::Int(self.shown_page(self.last_page()) as i64)),
+            ("num_pages", Json::Int(self.num_pages as i64)),
+            ("num_sheets", Json::Int(self.num_sheets as i64)),
+            ("num_signatures", Json::Int(self.num_signatures as i64)),
+            ("pages_per_signature", Json::Int(self.pages_per_signature as i64)),
+            ("num_blank_pages", Json::Int(self.num_blank_pages() as i64)),
+            ("signatures", Json::Array(signatures)),
+        ])
+    }
+
+    pub fn checksum(&self) -> String {
+        // a short fingerprint of the plan, to tell which plan a file came
+        // from, the same whichever job it's for
+        format!("{:016x}", fnv1a(self.plan_json().to_pretty_string().as_bytes()))
+    }
+}
+
+impl<'a> IntoIterator for &'a DocumentInfo {
+    type Item = &'a Signature;
+    type IntoIter = std::slice::Iter<'a, Signature>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for DocumentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.job.is_empty() {
+            write!(f, "{}", self.job.header(""))?;
+            writeln!(f, "#####################################")?;
+        }
+        writeln!(f, "Number of document pages to print: {}", self.num_pages)?;
+        writeln!(f, "Number of sheets to print: {}", self.num_sheets)?;
+        if self.one_sided {
+            writeln!(f, "Printed on one side of each sheet")?;
+        }
+        if self.reading_direction == ReadingDirection::RightToLeft {
+            writeln!(f, "Read right to left, bound on the right")?;
+        }
+        if self.fold_scheme != FoldScheme::Folio && !self.one_sided {
+            writeln!(f, "Folded in {}, {} pages to a sheet", self.fold_scheme.name(), self.pages_per_sheet())?;
+        }
+        writeln!(
+            f,
+            "Number of {}-sheet signatures to bind: {}",
+            self.pages_per_signature / self.pages_per_sheet(),
+            self.num_signatures,
+        )?;
+        writeln!(
+            f,
+            "Sheet utilisation: {:.1}% ({} blank pages, {:.1}%)",
+            self.sheet_utilisation(),
+            self.num_blank_pages(),
+            100.0 * self.blank_page_ratio(),
+        )?;
+        if !self.page_count_is_ideal() {
+            writeln!(f, "Pages short of a full last signature: {}", self.pages_short_of_full_signature())?;
+        }
+        writeln!(f, "#####################################")?;
+        for signature in &self.signatures {
+            writeln!(
+                f,
+                "Signature {}. First page: {}, last page: {}",
+                self.label_scheme.display_key(&signature.signature_key),
+                self.shown_page(signature.first_page),
+                self.shown_page(signature.last_page),
+            )?;
+        }
+        writeln!(f, "#####################################")
+    }
+}
+
+
+// Work
+fn page_range_problems(first_number: u32, second_number: u32, pages_per_signature: u32) -> Vec<Box<dyn Error>> {
+    let mut problems: Vec<Box<dyn Error>> = Vec::new();
+    if first_number == 0 {
+        problems.push(PageZeroError.into());
+    }
+    if second_number < first_number {
+        problems.push((SecondNumberGreaterError {first_number, second_number}).into());
+    }
+    if pages_per_signature == 0 || !pages_per_signature.is_multiple_of(DOC_PAGES_PER_SHEET) {
+        problems.push((InvalidSignatureSizeError {pages_per_signature}).into());
+    }
+    problems
+}
+
+fn superscript(number: u32) -> String {
+    number.to_string().chars().map(|digit| SUPERSCRIPT_DIGITS[digit as usize - '0' as usize]).collect()
+}
+
+pub fn parse_args(all_args: Vec<String>) -> Result<(u32, u32), Box<dyn Error>> {
+    // Convert the command line arguments to the numbers we need and
+    // make sure they are sensible.
+    parse_page_range(all_args, false)
+}
+
+pub fn parse_zero_indexed_args(all_args: Vec<String>) -> Result<(u32, u32), Box<dyn Error>> {
+    // The same, for page numbers counted from 0. They come back counted
+    // from 1, ready for DocumentInfo::new.
+    parse_page_range(all_args, true)
+}
+
+fn parse_page_range(all_args: Vec<String>, zero_indexed: bool) -> Result<(u32, u32), Box<dyn Error>> {
+    let args = &all_args[1..]; // 0th element is name of the binary
+    if args.len() < 2 {
+        return Err((NeedTwoArgumentsError {received_args: all_args}).into());
+    }
+    let first_arg = &args[0];
+    let second_arg = &args[1];
+    let first_number: u32 = first_arg.parse()?;
+    let second_number: u32 = second_arg.parse()?;
+    if first_number == 0 && !zero_indexed {
+        return Err(PageZeroError.into());
+    }
+    if second_number < first_number {
+        return Err((SecondNumberGreaterError {first_number, second_number}).into());
+    }
+    if zero_indexed {
+        let overflow = || OverflowError {first_page: first_number, last_page: second_number};
+        let first_number = first_number.checked_add(1).ok_or_else(overflow)?;
+        return Ok((first_number, second_number.checked_add(1).ok_or_else(overflow)?));
+    }
+    Ok((first_number, second_number))
+}
+
+pub fn parse_signature_sizes(value: &str) -> Result<Vec<u32>, Box<dyn Error>> {
+    // Parse a comma separated list of signature sizes, e.g. "16,20,24",
+    // making sure each one can actually be folded from whole sheets.
+    value.split(',').map(parse_signature_size).collect()
+}
+
+pub fn parse_signature_size(value: &str) -> Result<u32, Box<dyn Error>> {
+    // e.g. "24", which has to be a whole number of sheets
+    let pages_per_signature: u32 = value.trim().parse()?;
+    if pages_per_signature == 0 || !pages_per_signature.is_multiple_of(DOC_PAGES_PER_SHEET) {
+        return Err((InvalidSignatureSizeError {pages_per_signature}).into());
+    }
+    Ok(pages_per_signature)
+}
+
+pub fn csv_line(fields: &[&str]) -> String {
+    // fields with commas, quotes or line breaks quoted, quotes doubled
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME))
+}
+
+fn get_signatures(
+    first_page_of_document: u32,
+    num_pages: u32,
+    num_signatures: u32,
+    pages_per_signature: u32,
+) -> Vec<Signature> {
+    // get the starting and ending pages of each signature in the document
+    let last_page_of_document = first_page_of_document + (num_pages - 1);
+    let mut signatures: Vec<Signature> = Vec::new();
+    for i in 0..num_signatures {
+        let last_page_of_signature = ((i + 1) * pages_per_signature) + (first_page_of_document - 1);
+        let signature = Signature {
+            first_page: (pages_per_signature * i) + first_page_of_document,
+            last_page: if last_page_of_signature < last_page_of_document {
+                last_page_of_signature
+            } else {
+                last_page_of_document
+            },
+            signature_key: get_signature_key(i),
+            fold_scheme: FoldScheme::default(),
+        };
+        signatures.push(signature);
+    }
+    signatures
+}
+
+fn balance_signatures(signatures: Vec<Signature>, options: &DocumentOptions) -> Vec<Signature> {
+    // The same pages in the same order, moved between signatures a whole
+    // folded sheet at a time. Blanks on the last sheet stay at the end.
+    let mut sizes: Vec<u32> = signatures.iter().map(Signature::num_pages).collect();
+    let num_pages: u32 = sizes.iter().sum();
+    let pages_per_sheet = options.fold_scheme.pages_per_sheet();
+    match options.balance {
+        BalanceStrategy::None => return signatures,
+        BalanceStrategy::Even => {
+            let num_sheets = num_pages.div_ceil(pages_per_sheet);
+            let num_signatures = sizes.len() as u32;
+            sizes = (0..num_signatures)
+                .map(|i| (num_sheets / num_signatures + u32::from(i < num_sheets % num_signatures)) * pages_per_sheet)
+                .collect();
+            // the blanks come off the end, and a signature left with no
+            // pages is dropped
+            let mut blanks = num_sheets * pages_per_sheet - num_pages;
+            for size in sizes.iter_mut().rev() {
+                let taken = blanks.min(*size);
+                *size -= taken;
+                blanks -= taken;
+            }
+            sizes.retain(|&pages| pages > 0);
+        },
+        BalanceStrategy::MinimizeLast => {
+            if sizes.len() > 1 && sizes[sizes.len() - 1] * 2 < options.pages_per_signature {
+                let last = sizes.pop().unwrap_or(0);
+                if let Some(before) = sizes.last_mut() {
+                    *before += last;
+                }
+            }
+        },
+    }
+    let mut first_page = signatures[0].first_page;
+    let mut balanced = Vec::new();
+    for (i, pages) in sizes.into_iter().enumerate() {
+        balanced.push(Signature {
+            first_page,
+            last_page: first_page + pages - 1,
+            signature_key: get_signature_key(i as u32),
+            fold_scheme: FoldScheme::default(),
+        });
+        first_page += pages;
+    }
+    balanced
+}
+
+fn get_signature_key(signature_i: u32) -> String {
+    // get the letter code that identifies each signature
+    LabelScheme::default().key(signature_i)
+}
+
+pub fn parse_signature_key(key: &str) -> Result<u32, Box<dyn Error>> {
+    // the index of the signature a default key belongs to, e.g. "AB" is 27;
+    // see LabelScheme::parse_key for other labelling schemes
+    LabelScheme::default().parse_key(key)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_signature_key() {
+        assert_eq!(get_signature_key(0), "A");
+        assert_eq!(get_signature_key(1), "B");
+        assert_eq!(get_signature_key(2), "C");
+        assert_eq!(get_signature_key(25), "Z");
+        assert_eq!(get_signature_key(26), "AA");
+        assert_eq!(get_signature_key(27), "AB");
+        assert_eq!(get_signature_key(51), "AZ");
+        assert_eq!(get_signature_key(52), "BA");
+        assert_eq!(get_signature_key(78), "CA");
+        assert_eq!(get_signature_key(701), "ZZ");
+        assert_eq!(get_signature_key(702), "AAA");
+        assert_eq!(get_signature_key(703), "AAB");
+        assert_eq!(parse_signature_key("AAB").unwrap(), 703);
+    }
+
+    #[test]
+    fn test_document_info_relabel() {
+        let mut document_info = DocumentInfo::new(1, 60).unwrap();
+        let scheme = LabelScheme {alphabet: labels::Alphabet::Greek, ..LabelScheme::default()};
+        document_info.relabel(&scheme).unwrap();
+        let keys: Vec<&str> = document_info.signatures.iter().map(|signature| signature.signature_key.as_str()).collect();
+        assert_eq!(keys, vec!["α", "β", "γ", "δ"]);
+
+        // an explicit list of labels has to cover every signature
+        let scheme = LabelScheme {sequence: labels::parse_label_sequence("π\nA\nB\n"), ..LabelScheme::default()};
+        match document_info.relabel(&scheme) {
+            Ok(()) => panic!("Should have errored because there are only 3 labels for 4 signatures!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Not enough signature labels! The labels file has 3 but the document needs 4.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_efficiency() {
+        // 126 pages: 32 sheets with 2 blank pages, 2 short of 8 full signatures
+        let document_info = DocumentInfo::new(1, 126).unwrap();
+        assert!((document_info.blank_page_ratio() - 2.0 / 128.0).abs() < 1e-12);
+        assert!((document_info.sheet_utilisation() - 98.4375).abs() < 1e-9);
+        assert_eq!(document_info.pages_short_of_full_signature(), 2);
+        assert!(!document_info.page_count_is_ideal());
+        let text = document_info.to_string();
+        assert!(text.contains("Sheet utilisation: 98.4% (2 blank pages, 1.6%)\nPages short of a full last signature: 2\n"));
+
+        let document_info = DocumentInfo::new(1, 128).unwrap();
+        assert!(document_info.page_count_is_ideal());
+        assert_eq!(document_info.sheet_utilisation(), 100.0);
+        assert!(!document_info.to_string().contains("short"));
+        assert_eq!(DocumentInfo::new(1, 60).unwrap().pages_short_of_full_signature(), 4);
+    }
+
+    #[test]
+    fn test_signature_pages() {
+        let document_info = DocumentInfo::new(5, 26).unwrap();
+        let signatures = document_info.signatures();
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].pages().collect::<Vec<u32>>(), (5..=20).collect::<Vec<u32>>());
+        assert_eq!(signatures[1].pages().collect::<Vec<u32>>(), vec![21, 22, 23, 24, 25, 26]);
+    }
+
+    #[test]
+    fn test_signature_accessors() {
+        let document_info = DocumentInfo::new(5, 26).unwrap();
+        assert_eq!((document_info.first_page(), document_info.last_page(), document_info.num_pages()), (5, 26, 22));
+        assert_eq!((document_info.num_sheets(), document_info.num_signatures(), document_info.pages_per_signature()), (6, 2, 16));
+        let signatures: Vec<(&str, u32, u32, u32)> = (&document_info)
+            .into_iter()
+            .map(|signature| (signature.key(), signature.first_page(), signature.last_page(), signature.num_pages()))
+            .collect();
+        assert_eq!(signatures, vec![("A", 5, 20, 16), ("B", 21, 26, 6)]);
+        let mut num_pages = 0;
+        for signature in &document_info {
+            num_pages += signature.num_pages();
+        }
+        assert_eq!(num_pages, document_info.num_pages());
+    }
+
+    #[test]
+    fn test_signature_imposition() {
+        let document_info = DocumentInfo::new(1, 22).unwrap();
+        let signatures = document_info.signatures();
+        let pairs: Vec<(Option<u32>, Option<u32>)> =
+            signatures[0].imposition().iter().map(|forme| (forme.rows[0][0].page, forme.rows[0][1].page)).collect();
+        assert_eq!(pairs[..4], [(Some(16), Some(1)), (Some(2), Some(15)), (Some(14), Some(3)), (Some(4), Some(13))]);
+        assert_eq!(pairs.len(), 8);
+        // the last signature's 6 pages fill two sheets, with two blanks
+        let formes = signatures[1].imposition();
+        assert_eq!((formes[0].signature_key.as_str(), formes[0].side), ("B", imposition::Side::Front));
+        assert_eq!((formes[0].rows[0][0].page, formes[0].rows[0][1].page), (None, Some(17)));
+        assert_eq!((formes[3].rows[0][0].page, formes[3].rows[0][1].page), (Some(20), Some(21)));
+    }
+
+    #[test]
+    fn test_document_info_with_gatherings() {
+        let document_info = DocumentInfo::with_gatherings(1, &[16, 16, 8, 24]).unwrap();
+        assert_eq!(document_info.num_pages, 64);
+        assert_eq!(document_info.num_sheets, 16);
+        assert_eq!(document_info.pages_per_signature, 24);
+        let pages: Vec<(u32, u32)> =
+            document_info.signatures.iter().map(|signature| (signature.first_page, signature.last_page)).collect();
+        assert_eq!(pages, vec![(1, 16), (17, 32), (33, 40), (41, 64)]);
+        assert_eq!(document_info.collation_formula(), "A\u{2013}B\u{2078} C\u{2074} D\u{b9}\u{b2}");
+        match DocumentInfo::with_gatherings(1, &[16, 6]) {
+            Ok(_) => panic!("Should have errored because 6 pages can't be folded from whole sheets!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Signature size must be a positive multiple of 4 pages! Got 6.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_half_signature() {
+        // 56 pages: three signatures of four sheets and one of two
+        let mut document_info = DocumentInfo::new(1, 56).unwrap();
+        assert_eq!(document_info.collation_formula(), "A\u{2013}C\u{2078} D\u{2074}");
+        let scheme = LabelScheme {half_suffix: Some("\u{bd}".to_string()), ..LabelScheme::default()};
+        document_info.relabel(&scheme).unwrap();
+        assert_eq!(document_info.signatures[3].signature_key, "D\u{bd}");
+        assert_eq!(document_info.collation_formula(), "A\u{2013}C\u{2078} D\u{bd}\u{2074}");
+        assert_eq!(scheme.parse_key("D\u{bd}").unwrap(), 3);
+
+        // a short signature that isn't half the size isn't marked
+        let mut document_info = DocumentInfo::new(1, 60).unwrap();
+        let scheme = LabelScheme {half_suffix: Some("2".to_string()), ..LabelScheme::default()};
+        document_info.relabel(&scheme).unwrap();
+        assert_eq!(document_info.signatures[3].signature_key, "D");
+        assert_eq!(DocumentInfo::new(1, 160).unwrap().collation_formula(), "A\u{2013}J\u{2078}");
+
+        // in octavo, signatures of 32 pages are two sheets, so a last
+        // gathering of 10 pages is one sheet, a half
+        let mut document_info = DocumentInfo::with_signature_size(1, 42, 32).unwrap();
+        document_info.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        let scheme = LabelScheme {half_suffix: Some("\u{bd}".to_string()), ..LabelScheme::default()};
+        document_info.relabel(&scheme).unwrap();
+        assert_eq!(document_info.signatures[0].signature_key, "A");
+        assert_eq!(document_info.signatures[1].signature_key, "B\u{bd}");
+    }
+
+    #[test]
+    fn test_document_info_new_extreme_inputs() {
+        match DocumentInfo::new(1, u32::MAX) {
+            Ok(_) => panic!("1 to u32::MAX should overflow"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Pages 1 to 4294967295 are too many to plan! Page numbers, blanks included, can't go past 4294967295.",
+            ),
+        }
+        // the last signature would need blanks numbered past u32::MAX
+        assert!(DocumentInfo::new(u32::MAX - 3, u32::MAX).is_err());
+        // but a full one right at the end fits
+        let document_info = DocumentInfo::new(u32::MAX - 15, u32::MAX).unwrap();
+        assert_eq!(document_info.num_sheets, 4);
+        assert_eq!(imposition::impose(&document_info).unwrap().len(), 8);
+        // past 2^24 pages, where f32 can no longer count every page, the
+        // integer ceiling divisions are still exact
+        let document_info = DocumentInfo::new(1, 16_777_217).unwrap();
+        assert_eq!(document_info.num_sheets, 4_194_305);
+        assert_eq!(document_info.num_signatures, 1_048_577);
+        assert_eq!(document_info.num_blank_pages(), 3);
+
+        assert!(DocumentInfo::new(0, 16).is_err());
+        assert!(DocumentInfo::new(16, 1).is_err());
+        assert!(DocumentInfo::with_signature_size(1, 16, 0).is_err());
+        assert!(DocumentInfo::with_signature_size(1, 16, 6).is_err());
+        let document_info = DocumentInfo::with_signature_size(1, 60, 24).unwrap();
+        assert_eq!(document_info.num_sheets, 15);
+        assert_eq!(document_info.num_signatures, 3);
+        assert_eq!(document_info.signatures[1].first_page, 25);
+    }
+
+    #[test]
+    fn test_document_info_zero_indexed() {
+        let mut document_info = DocumentInfo::new(1, 20).unwrap();
+        document_info.set_zero_indexed(true);
+        let text = document_info.to_string();
+        assert!(text.contains("Signature A. First page: 0, last page: 15\n"));
+        assert!(text.contains("Signature B. First page: 16, last page: 19\n"));
+        assert!(document_info.to_json().contains("\"first_page\": 0,\n  \"last_page\": 19,\n"));
+        // the API still counts from 1
+        assert_eq!(document_info.sheet_of(1).unwrap().sheet, 0);
+    }
+
+    #[test]
+    fn test_document_info_leaves() {
+        // 22 pages: a full signature of 4 sheets, then 6 pages on 2 sheets
+        let document_info = DocumentInfo::new(5, 26).unwrap();
+        assert_eq!(document_info.num_leaves(), 12);
+        assert_eq!(document_info.num_rectos(), 11);
+        assert_eq!(document_info.num_versos(), 11);
+        assert_eq!(document_info.num_printed_pages(), 22);
+        assert_eq!(document_info.num_blank_pages(), 2);
+        assert_eq!(
+            document_info.signature_leaves(),
+            vec![("A".to_string(), 8), ("B".to_string(), 4)],
+        );
+
+        let document_info = DocumentInfo::new(1, 5).unwrap();
+        assert_eq!(document_info.num_leaves(), 4);
+        assert_eq!(document_info.num_rectos(), 3);
+        assert_eq!(document_info.num_versos(), 2);
+        assert_eq!(document_info.num_blank_pages(), 3);
+    }
+
+    #[test]
+    fn test_document_info_fold_scheme() {
+        // 40 pages in octavo: two sheets and a half-empty third
+        let mut document_info = DocumentInfo::with_signature_size(1, 40, 16).unwrap();
+        document_info.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        assert_eq!(document_info.pages_per_sheet(), 16);
+        assert_eq!((document_info.num_sheets, document_info.num_leaves()), (3, 24));
+        assert_eq!(document_info.num_blank_pages(), 8);
+        assert_eq!(document_info.collation_formula(), "A\u{2013}C\u{2078}");
+        assert!(format!("{}", document_info).contains("Folded in octavo, 16 pages to a sheet\n"));
+        match document_info.set_fold_scheme(FoldScheme::Sextodecimo) {
+            Ok(_) => panic!("Should have errored because a 16-page signature can't be folded from 32-page sheets!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "A sheet folded in sextodecimo has 32 pages, so signatures must be a multiple of 32 pages! Got 16.",
+            ),
+        }
+        assert_eq!(document_info.fold_scheme(), FoldScheme::Octavo);
+    }
+
+    #[test]
+    fn test_document_info_balance() {
+        let sizes = |document: &DocumentInfo| document.iter().map(Signature::num_pages).collect::<Vec<u32>>();
+        // 68 pages of 16: four full signatures and a one-sheet runt
+        let options = DocumentOptions::default();
+        assert_eq!(sizes(&DocumentInfo::new_with_options(1, 68, &options).unwrap()), vec![16, 16, 16, 16, 4]);
+        let even = DocumentOptions {balance: BalanceStrategy::Even, ..options};
+        let document_info = DocumentInfo::new_with_options(1, 68, &even).unwrap();
+        assert_eq!(sizes(&document_info), vec![16, 16, 12, 12, 12]);
+        assert_eq!((document_info.num_sheets, document_info.num_signatures), (17, 5));
+        assert_eq!(document_info.signatures[2].key(), "C");
+        let last = DocumentOptions {balance: BalanceStrategy::MinimizeLast, ..options};
+        let document_info = DocumentInfo::new_with_options(1, 68, &last).unwrap();
+        assert_eq!(sizes(&document_info), vec![16, 16, 16, 20]);
+        assert_eq!(document_info.num_signatures, 4);
+
+        // blanks stay at the end, and a last signature of half size stays
+        assert_eq!(sizes(&DocumentInfo::new_with_options(1, 66, &even).unwrap()), vec![16, 16, 12, 12, 10]);
+        assert_eq!(sizes(&DocumentInfo::new_with_options(1, 58, &last).unwrap()), vec![16, 16, 16, 10]);
+
+        // a 16-page sheet at a time in octavo, rather than 48, 48, 4
+        let octavo = DocumentOptions {pages_per_signature: 48, balance: BalanceStrategy::Even, fold_scheme: FoldScheme::Octavo};
+        let document_info = DocumentInfo::new_with_options(1, 100, &octavo).unwrap();
+        assert_eq!(sizes(&document_info), vec![48, 32, 20]);
+        assert_eq!(document_info.fold_scheme(), FoldScheme::Octavo);
+
+        // signatures too small for the fold are refused before balancing,
+        // as for 1 100 --signature-size 16 --fold sextodecimo --balance even
+        let too_small = DocumentOptions {pages_per_signature: 16, balance: BalanceStrategy::Even, fold_scheme: FoldScheme::Sextodecimo};
+        match DocumentInfo::new_with_options(1, 100, &too_small) {
+            Ok(_) => panic!("16-page signatures can't be folded in sextodecimo!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "A sheet folded in sextodecimo has 32 pages, so signatures must be a multiple of 32 pages! Got 16.",
+            ),
+        }
+        // and a signature left empty once the blanks are taken off is dropped
+        let signatures = get_signatures(1, 33, 3, 16);
+        let balanced = balance_signatures(signatures, &DocumentOptions {pages_per_signature: 16, balance: BalanceStrategy::Even, fold_scheme: FoldScheme::Sextodecimo});
+        assert_eq!(balanced.iter().map(Signature::num_pages).collect::<Vec<u32>>(), vec![32, 1]);
+    }
+
+    #[test]
+    fn test_document_info_one_sided() {
+        // 5 pages two to a sheet: 3 sheets, 6 leaves, every page a recto
+        let mut document_info = DocumentInfo::new(1, 5).unwrap();
+        document_info.set_one_sided(true);
+        assert_eq!(document_info.pages_per_sheet(), 2);
+        assert_eq!(document_info.num_sheets, 3);
+        assert_eq!(document_info.num_leaves(), 6);
+        assert_eq!(document_info.num_rectos(), 5);
+        assert_eq!(document_info.num_versos(), 0);
+        assert_eq!(document_info.num_blank_pages(), 1);
+        assert!(document_info.to_string().contains("Printed on one side of each sheet\n"));
+        assert!(document_info.to_string().contains("Number of 8-sheet signatures to bind: 1\n"));
+        document_info.set_one_sided(false);
+        assert_eq!(document_info.num_sheets, 2);
+    }
+
+    #[test]
+    fn test_document_info_sheet_of() {
+        // page 11 of 1-16 is on the back of the third sheet, to the right of 6
+        let document_info = DocumentInfo::new(1, 16).unwrap();
+        let location = document_info.sheet_of(11).unwrap();
+        assert_eq!(location.signature_key, "A");
+        assert_eq!(location.sheet, 2);
+        assert_eq!(location.side, imposition::Side::Back);
+        assert_eq!((location.row, location.column), (0, 1));
+        assert!(document_info.sheet_of(17).is_none());
+    }
+
+    #[test]
+    fn test_document_info_to_json() {
+        let json = DocumentInfo::new(5, 26).unwrap().to_json();
+        assert!(json.starts_with("{\n  \"first_page\": 5,\n  \"last_page\": 26,\n  \"num_pages\": 22,\n"));
+        assert!(json.contains("  \"num_blank_pages\": 2,\n"));
+        assert!(json.contains("{\n      \"key\": \"B\",\n      \"first_page\": 21,\n      \"last_page\": 26\n    }"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_document_info_serde() {
+        let mut document_info = DocumentInfo::new(5, 26).unwrap();
+        document_info.set_one_sided(true);
+        let json = serde_json::to_string(&document_info).unwrap();
+        assert!(json.contains("\"signature_key\":\"B\""));
+        let read: DocumentInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.to_string(), document_info.to_string());
+        assert_eq!(read.signatures()[1].pages(), 21..=26);
+    }
+
+    #[test]
+    fn test_document_info_job() {
+        let mut document_info = DocumentInfo::new(5, 26).unwrap();
+        let checksum = document_info.checksum();
+        document_info.set_job(JobInfo {job_id: Some("J-1041".to_string()), ..JobInfo::default()});
+        assert!(document_info.to_json().starts_with("{\n  \"job\": {\n    \"job\": \"J-1041\",\n    \"title\": null,"));
+        assert!(document_info.to_string().starts_with("Job: J-1041\n####"));
+        assert!(document_info.plain_text().starts_with("Job: J-1041.\nThe document has 22 pages to print.\n"));
+        // the plan is the same plan whatever job it's for
+        assert_eq!(document_info.checksum(), checksum);
+    }
+
+    #[test]
+    fn test_document_info_checksum() {
+        let checksum = DocumentInfo::new(5, 26).unwrap().checksum();
+        assert_eq!(checksum.len(), 16);
+        assert_eq!(checksum, DocumentInfo::new(5, 26).unwrap().checksum());
+        assert_ne!(checksum, DocumentInfo::new(5, 27).unwrap().checksum());
+    }
+
+    #[test]
+    fn test_document_info_plain_v1() {
+        // the layout is fixed: this test should never need changing
+        assert_eq!(
+            DocumentInfo::new(5, 26).unwrap().plain_v1(),
+            "format\tplain-v1\n\
+             first_page\t5\n\
+             last_page\t26\n\
+             num_pages\t22\n\
+             num_sheets\t6\n\
+             num_signatures\t2\n\
+             pages_per_signature\t16\n\
+             num_blank_pages\t2\n\
+             signature\tA\t5\t20\n\
+             signature\tB\t21\t26\n",
+        );
+    }
+
+    #[test]
+    fn test_document_info_to_csv() {
+        let mut document_info = DocumentInfo::new(5, 26).unwrap();
+        assert_eq!(
+            document_info.to_csv(),
+            "Signature,First page,Last page,Pages,Sheets\r\nA,5,20,16,4\r\nB,21,26,6,2\r\n",
+        );
+        document_info.set_zero_indexed(true);
+        document_info.set_one_sided(true);
+        assert!(document_info.to_csv().ends_with("\r\nB,20,25,6,3\r\n"));
+    }
+
+    #[test]
+    fn test_document_info_plain_text() {
+        let plain = DocumentInfo::new(5, 26).unwrap().plain_text();
+        assert_eq!(
+            plain,
+            "The document has 22 pages to print.\n\
+             Printing it takes 6 sheets.\n\
+             It is bound in 2 signatures, each of up to 4 sheets.\n\
+             2 pages are left blank.\n\
+             Signature A begins at page 5 and ends at page 20.\n\
+             Signature B begins at page 21 and ends at page 26.\n",
+        );
+        let plain = DocumentInfo::new(1, 3).unwrap().plain_text();
+        assert!(plain.contains("It is bound in 1 signature, each of up to 4 sheets.\n1 page is left blank.\n"));
+    }
+
+    #[test]
+    fn test_document_info_conjugate_of() {
+        let document_info = DocumentInfo::new(1, 16).unwrap();
+        let conjugate = document_info.conjugate_of(11).unwrap();
+        assert_eq!(conjugate.backs_up_with, Some(12));
+        assert_eq!(conjugate.shares_side_with, Some(6));
+        assert!(document_info.conjugate_of(17).is_none());
+    }
+
+    #[test]
+    fn test_get_signatures() {
+        // simple example
+        let first_number = 1;
+        let num_pages = 16;
+        let num_signatures = 1;
+        let signatures = get_signatures(first_number, num_pages, num_signatures, DOC_PAGES_PER_SIGNATURE);
+        assert!(signatures.len() == 1);
+        assert!(signatures[0].first_page == 1);
+        assert!(signatures[0].last_page == 16);
+
+        // not using all the pages of a signature
+        let first_number = 1;
+        let num_pages = 9;
+        let num_signatures = 1;
+        let signatures = get_signatures(first_number, num_pages, num_signatures, DOC_PAGES_PER_SIGNATURE);
+        assert!(signatures.len() == 1);
+        assert!(signatures[0].first_page == 1);
+        assert!(signatures[0].last_page == 9);
+
+        // more than one signature
+        let first_number = 1;
+        let num_pages = 19;
+        let num_signatures = 2;
+        let signatures = get_signatures(first_number, num_pages, num_signatures, DOC_PAGES_PER_SIGNATURE);
+        assert!(signatures.len() == 2);
+        assert!(signatures[0].first_page == 1);
+        assert!(signatures[0].last_page == 16);
+        assert!(signatures[1].first_page == 17);
+        assert!(signatures[1].last_page == 19);
+
+        // not starting from the first page
+        let first_number = 5;
+        let num_pages = 19;
+        let num_signatures = 2;
+        let signatures = get_signatures(first_number, num_pages, num_signatures, DOC_PAGES_PER_SIGNATURE);
+        assert!(signatures.len() == 2);
+        println!("{:?}", signatures);
+        assert!(signatures[0].first_page == 5);
+        assert!(signatures[0].last_page == 20);
+        assert!(signatures[1].first_page == 21);
+        assert!(signatures[1].last_page == 23);
+    }
+
+    #[test]
+    fn test_document_info_new() {
+        // smallest possible
+        let document_info = DocumentInfo::new(1, 1).unwrap();
+        assert_eq!(document_info.num_pages, 1);
+        assert_eq!(document_info.num_sheets, 1);
+        assert_eq!(document_info.num_signatures, 1);
+
+        // full sheet
+        let document_info = DocumentInfo::new(1, 4).unwrap();
+        assert_eq!(document_info.num_pages, 4);
+        assert_eq!(document_info.num_sheets, 1);
+        assert_eq!(document_info.num_signatures, 1);
+
+        // not starting at 1
+        let document_info = DocumentInfo::new(7, 8).unwrap();
+        assert_eq!(document_info.num_pages, 2);
+        assert_eq!(document_info.num_sheets, 1);
+        assert_eq!(document_info.num_signatures, 1);
+
+        // larger one
+        let document_info = DocumentInfo::new(1, 60).unwrap();
+        assert_eq!(document_info.num_pages, 60);
+        assert_eq!(document_info.num_sheets, 15);
+        assert_eq!(document_info.num_signatures, 4);
+        
+        // larger one not starting at 1
+        let document_info = DocumentInfo::new(12, 30).unwrap();
+        assert_eq!(document_info.num_pages, 19);
+        assert_eq!(document_info.num_sheets, 5);
+        assert_eq!(document_info.num_signatures, 2);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let error_msg = "parse_args should be returning Ok.";
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "1".to_string(),
+            "60".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                assert_eq!(first_number, 1);
+                assert_eq!(second_number, 60);
+            },
+            Err(result_error) => panic!("{} Returned Err('{}').", error_msg, result_error),
+        }
+
+        // can be the same number twice
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "33".to_string(),
+            "33".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                assert_eq!(first_number, 33);
+                assert_eq!(second_number, 33);
+            },
+            Err(result_error) => panic!("{} Returned Err('{}').", error_msg, result_error),
+        }
+
+        // doesn't matter if it gets extra args
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "5".to_string(),
+            "185".to_string(),
+            "asdfasdfad".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                assert_eq!(first_number, 5);
+                assert_eq!(second_number, 185);
+            },
+            Err(result_error) => panic!("{} Returned Err('{}').", error_msg, result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_args_insufficient_args() {
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "5".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                panic!(
+                    "Should have errored because of insufficient arguments! Got Ok(({}, {})).",
+                    first_number,
+                    second_number,
+                );
+            },
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("Need at least two arguments to run!"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_args_first_arg_not_number() {
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "asdfasd".to_string(),
+            "60".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                panic!(
+                    "Should have errored because the first arg is not a number! Got Ok(({}, {})).",
+                    first_number,
+                    second_number,
+                );
+            },
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("invalid digit found in string"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_args_second_arg_not_number() {
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "345".to_string(),
+            "asdfa60".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                panic!(
+                    "Should have errored because the second arg is not a number! Got Ok(({}, {})).",
+                    first_number,
+                    second_number,
+                );
+            },
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("invalid digit found in string"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_args_first_arg_zero() {
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "0".to_string(),
+            "60".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                panic!(
+                    "Should have errored because the first arg is zero! Got Ok(({}, {})).",
+                    first_number,
+                    second_number,
+                );
+            },
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("There is no page zero!"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_args_second_arg_smaller() {
+        let result = parse_args(vec![
+            "target/debug/rust-signatures".to_string(),
+            "33".to_string(),
+            "32".to_string(),
+        ]);
+        match result {
+            Ok((first_number, second_number)) => {
+                panic!(
+                    "Should have errored because first arg > second arg! Got Ok(({}, {})).",
+                    first_number,
+                    second_number,
+                );
+            },
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("The second number must be greater than"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_zero_indexed_args() {
+        let args = vec!["target/debug/rust-signatures".to_string(), "0".to_string(), "59".to_string()];
+        assert_eq!(parse_zero_indexed_args(args).unwrap(), (1, 60));
+        let args = vec!["target/debug/rust-signatures".to_string(), "5".to_string(), "4".to_string()];
+        assert!(parse_zero_indexed_args(args).is_err());
+        let args = vec!["target/debug/rust-signatures".to_string(), "0".to_string(), u32::MAX.to_string()];
+        assert!(parse_zero_indexed_args(args).is_err());
+        let args = vec!["target/debug/rust-signatures".to_string(), u32::MAX.to_string(), u32::MAX.to_string()];
+        match parse_zero_indexed_args(args) {
+            Ok(_) => panic!("Should have errored because page 4294967295 counted from 0 is past the last u32!"),
+            Err(result_error) => assert!(result_error.is::<OverflowError>()),
+        }
+    }
+}
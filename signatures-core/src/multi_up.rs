@@ -0,0 +1,185 @@
+// Printing several of the plan's sheets on one bigger press sheet, 4, 6 or
+// 12 pages to a side, as for duodecimo work or odd press sheets. The press
+// sheet is printed, cut into strips of 2 pages a side, and each strip is
+// then one of the plan's sheets, folded and gathered as usual.
+
+use crate::imposition::{self, SheetSide, Side, TwoUpError};
+use crate::DocumentInfo;
+
+// Constants
+// strips are stacked 3 high, or 2 for 4 pages a side
+const STRIP_ROWS: u32 = 3;
+const FOUR_UP_ROWS: u32 = 2;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultiUpLayout {
+    pub rows: u32,
+    // strips side by side in each row
+    pub strips_per_row: u32,
+}
+
+impl MultiUpLayout {
+    pub fn for_pages_per_sheet(pages_per_sheet: u32) -> Option<MultiUpLayout> {
+        // 2, 4, 6 or 12 pages on each side
+        match pages_per_sheet {
+            4 => Some(MultiUpLayout {rows: 1, strips_per_row: 1}),
+            8 => Some(MultiUpLayout {rows: FOUR_UP_ROWS, strips_per_row: 1}),
+            12 => Some(MultiUpLayout {rows: STRIP_ROWS, strips_per_row: 1}),
+            24 => Some(MultiUpLayout {rows: STRIP_ROWS, strips_per_row: 2}),
+            _ => None,
+        }
+    }
+
+    pub fn num_strips(&self) -> u32 {
+        self.rows * self.strips_per_row
+    }
+}
+
+// which of the plan's sheets a strip of the press sheet is
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strip {
+    pub signature_key: String,
+    pub sheet: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PressSheetSide {
+    pub press_sheet: u32,
+    pub side: Side,
+    // rows of pages as they sit on the side, None for blanks
+    pub cells: Vec<Vec<Option<u32>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PressSheet {
+    pub number: u32,
+    // the strips in the order they come off the cut, row by row from the
+    // top left of the front; None where the press sheet has room to spare
+    pub strips: Vec<Option<Strip>>,
+    pub front: PressSheetSide,
+    pub back: PressSheetSide,
+}
+
+
+// Work
+pub fn impose_multi_up(document: &DocumentInfo, layout: MultiUpLayout) -> Result<Vec<PressSheet>, TwoUpError> {
+    // the strips are the plan's sheets two pages to a side
+    Ok(multi_up_sheets(&imposition::impose(document)?, layout))
+}
+
+pub fn multi_up_sheets(sides: &[SheetSide], layout: MultiUpLayout) -> Vec<PressSheet> {
+    // Take the plan's sheets in order, a press sheet's worth at a time.
+    // Turning the press sheet over left to right mirrors each row, so on the
+    // back the strips in a row come in the opposite order; each strip's own
+    // back already has its pages the right way round for turning over.
+    let sheets: Vec<(SheetSide, SheetSide)> = sides
+        .chunks(2)
+        .map(|sides| (sides[0].clone(), sides[1].clone()))
+        .collect();
+    let per_row = layout.strips_per_row as usize;
+    let mut press_sheets = Vec::new();
+    for (number, group) in sheets.chunks(layout.num_strips() as usize).enumerate() {
+        let strip = |i: usize| group.get(i);
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for row in 0..layout.rows as usize {
+            let mut front_row = Vec::new();
+            let mut back_row = Vec::new();
+            for col in 0..per_row {
+                let front_strip = strip(row * per_row + col);
+                front_row.push(front_strip.and_then(|(front, _)| front.left));
+                front_row.push(front_strip.and_then(|(front, _)| front.right));
+                let back_strip = strip(row * per_row + (per_row - 1 - col));
+                back_row.push(back_strip.and_then(|(_, back)| back.left));
+                back_row.push(back_strip.and_then(|(_, back)| back.right));
+            }
+            front.push(front_row);
+            back.push(back_row);
+        }
+        let strips = (0..layout.num_strips() as usize)
+            .map(|i| strip(i).map(|(front, _)| Strip {signature_key: front.signature_key.clone(), sheet: front.sheet}))
+            .collect();
+        let number = number as u32;
+        press_sheets.push(PressSheet {
+            number,
+            strips,
+            front: PressSheetSide {press_sheet: number, side: Side::Front, cells: front},
+            back: PressSheetSide {press_sheet: number, side: Side::Back, cells: back},
+        });
+    }
+    press_sheets
+}
+
+pub fn display_multi_up(document: &DocumentInfo, layout: MultiUpLayout, press_sheets: &[PressSheet]) {
+    let cell = |page: &Option<u32>| page.map_or("-".to_string(), |page| document.shown_page(page).to_string());
+    let cuts = match layout.strips_per_row {
+        1 => format!("Cut it across into {} strips", layout.rows),
+        _ => format!("Cut it across into {} rows and down the middle of each", layout.rows),
+    };
+    for press_sheet in press_sheets {
+        for side in [&press_sheet.front, &press_sheet.back] {
+            println!("Press sheet {}, {}:", press_sheet.number + 1, if side.side == Side::Front { "front" } else { "back" });
+            for row in &side.cells {
+                let pages: Vec<String> = row.iter().map(|page| format!("{:>5}", cell(page))).collect();
+                println!("{}", pages.join(""));
+            }
+        }
+        let strips: Vec<String> = press_sheet
+            .strips
+            .iter()
+            .flatten()
+            .map(|strip| format!("{} sheet {}", document.label_scheme.display_key(&strip.signature_key), strip.sheet + 1))
+            .collect();
+        println!("{}, giving {}.", cuts, strips.join(", "));
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_up_layout() {
+        assert_eq!(MultiUpLayout::for_pages_per_sheet(4).unwrap().num_strips(), 1);
+        assert_eq!(MultiUpLayout::for_pages_per_sheet(8).unwrap().num_strips(), 2);
+        assert_eq!(MultiUpLayout::for_pages_per_sheet(12).unwrap().num_strips(), 3);
+        assert_eq!(MultiUpLayout::for_pages_per_sheet(24).unwrap().num_strips(), 6);
+        assert_eq!(MultiUpLayout::for_pages_per_sheet(16), None);
+    }
+
+    #[test]
+    fn test_impose_six_up() {
+        // a 12 page gathering of 3 sheets, cut from one press sheet
+        let document = DocumentInfo::with_signature_size(1, 12, 12).unwrap();
+        let press_sheets = impose_multi_up(&document, MultiUpLayout::for_pages_per_sheet(12).unwrap()).unwrap();
+        assert_eq!(press_sheets.len(), 1);
+        let sheet = &press_sheets[0];
+        assert_eq!(
+            sheet.front.cells,
+            vec![vec![Some(12), Some(1)], vec![Some(10), Some(3)], vec![Some(8), Some(5)]],
+        );
+        assert_eq!(
+            sheet.back.cells,
+            vec![vec![Some(2), Some(11)], vec![Some(4), Some(9)], vec![Some(6), Some(7)]],
+        );
+        assert_eq!(sheet.strips[2], Some(Strip {signature_key: "A".to_string(), sheet: 2}));
+    }
+
+    #[test]
+    fn test_impose_twelve_up() {
+        // 2 press sheets: 6 strips of A, then 2 of B and 4 spare
+        let document = DocumentInfo::with_signature_size(1, 32, 24).unwrap();
+        let press_sheets = impose_multi_up(&document, MultiUpLayout::for_pages_per_sheet(24).unwrap()).unwrap();
+        assert_eq!(press_sheets.len(), 2);
+        // the back of a row mirrors the front, strip by strip
+        assert_eq!(press_sheets[0].front.cells[0], vec![Some(24), Some(1), Some(22), Some(3)]);
+        assert_eq!(press_sheets[0].back.cells[0], vec![Some(4), Some(21), Some(2), Some(23)]);
+        assert_eq!(press_sheets[1].front.cells[0], vec![Some(32), Some(25), Some(30), Some(27)]);
+        assert_eq!(press_sheets[1].front.cells[1], vec![None, None, None, None]);
+        assert_eq!(press_sheets[1].strips.iter().flatten().count(), 2);
+    }
+}
@@ -0,0 +1,170 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::imposition;
+use crate::{DocumentInfo, DOC_PAGES_PER_SHEET};
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct CellsLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for CellsLineError {}
+
+impl fmt::Display for CellsLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the cells file! Expected the left and right page numbers, or - for a blank, got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct NoMatchingPlanError {
+    num_sides: usize,
+}
+
+impl Error for NoMatchingPlanError {}
+
+impl fmt::Display for NoMatchingPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "None of the signature sizes this tool can plan matches the {} sheet sides given.",
+            self.num_sides,
+        )
+    }
+}
+
+
+// Data structs
+// the left and right page of one sheet side, None for a blank
+pub type SideCells = (Option<u32>, Option<u32>);
+
+#[derive(Debug)]
+pub struct Analysis {
+    pub document: DocumentInfo,
+}
+
+impl Analysis {
+    pub fn display(&self) {
+        println!("Scheme: 2-up, sheets folded once and nested");
+        if self.document.num_signatures() == 1 {
+            println!(
+                "Pages per signature: {} or more (the whole job is one signature)",
+                self.document.pages_per_signature(),
+            );
+        } else {
+            println!("Pages per signature: {}", self.document.pages_per_signature());
+        }
+        self.document.display();
+    }
+}
+
+
+// Work
+pub fn read_cells(path: &str) -> Result<Vec<SideCells>, Box<dyn Error>> {
+    parse_cells(&fs::read_to_string(path)?)
+}
+
+pub fn parse_cells(contents: &str) -> Result<Vec<SideCells>, Box<dyn Error>> {
+    // Read the page numbers found on an imposed PDF, one sheet side per line
+    // with the left page then the right page, e.g. "16 1", using - for a
+    // blank. Blank lines and lines starting with # are ignored.
+    let mut cells = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = || CellsLineError {line_number: i + 1, line: line.to_string()};
+        let pages: Vec<&str> = line.split_whitespace().collect();
+        if pages.len() != 2 {
+            return Err(line_error().into());
+        }
+        let left = parse_cell(pages[0]).ok_or_else(line_error)?;
+        let right = parse_cell(pages[1]).ok_or_else(line_error)?;
+        cells.push((left, right));
+    }
+    Ok(cells)
+}
+
+fn parse_cell(cell: &str) -> Option<Option<u32>> {
+    if cell == "-" {
+        return Some(None);
+    }
+    match cell.parse() {
+        Ok(0) | Err(_) => None,
+        Ok(page) => Some(Some(page)),
+    }
+}
+
+pub fn analyse(cells: &[SideCells]) -> Result<Analysis, Box<dyn Error>> {
+    // Guess the plan behind an imposed document by re-imposing the same pages
+    // with every signature size that could fit on the sheets given, and
+    // taking the smallest one that reproduces the layout exactly.
+    let no_match = || NoMatchingPlanError {num_sides: cells.len()};
+    let pages = cells.iter().flat_map(|&(left, right)| left.into_iter().chain(right));
+    let first_page = pages.clone().min().ok_or_else(no_match)?;
+    let last_page = pages.max().ok_or_else(no_match)?;
+    // every sheet has two sides, so there are at most half as many sheets
+    let max_sheets = u32::try_from(cells.len() / 2).map_err(|_| no_match())?;
+    (1..=max_sheets)
+        .filter_map(|sheets: u32| {
+            let pages_per_signature = sheets.checked_mul(DOC_PAGES_PER_SHEET)?;
+            DocumentInfo::with_signature_size(first_page, last_page, pages_per_signature).ok()
+        })
+        .find(|document| {
+            imposition::impose(document).is_ok_and(|sides| {
+                sides.len() == cells.len() && sides.iter().zip(cells).all(|(side, &cell)| (side.left, side.right) == cell)
+            })
+        })
+        .map(|document| Analysis {document})
+        .ok_or_else(|| no_match().into())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells_of(document: &DocumentInfo) -> Vec<SideCells> {
+        imposition::impose(document).unwrap().iter().map(|side| (side.left, side.right)).collect()
+    }
+
+    #[test]
+    fn test_parse_cells() {
+        let cells = parse_cells("# sheet 1\n16 1\n2 15\n\n- 17\n").unwrap();
+        assert_eq!(cells, vec![(Some(16), Some(1)), (Some(2), Some(15)), (None, Some(17))]);
+        assert!(parse_cells("16\n").is_err());
+        assert!(parse_cells("16 x\n").is_err());
+        assert!(parse_cells("0 1\n").is_err());
+    }
+
+    #[test]
+    fn test_analyse() {
+        let document = DocumentInfo::with_signature_size(1, 60, 12).unwrap();
+        let analysis = analyse(&cells_of(&document)).unwrap();
+        assert_eq!(analysis.document.pages_per_signature(), 12);
+        assert_eq!(analysis.document.first_page(), 1);
+        assert_eq!(analysis.document.num_pages(), 60);
+        assert_eq!(analysis.document.num_signatures(), 5);
+
+        // a single short signature is reported with the smallest size that fits
+        let analysis = analyse(&cells_of(&DocumentInfo::new(3, 8).unwrap())).unwrap();
+        assert_eq!(analysis.document.pages_per_signature(), 8);
+        assert_eq!(analysis.document.num_signatures(), 1);
+
+        // pages in an order no plan produces
+        let result = analyse(&[(Some(1), Some(2)), (Some(3), Some(4))]);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,380 @@
+// Planning a day's jobs in one go from a jobs file, with totals across all
+// of them so the paper for the whole batch can be pulled at once.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::page_count;
+use crate::press::{self, Stocks};
+use crate::DocumentInfo;
+
+// Constants
+// the state file sits next to the jobs file, named after it
+const STATE_SUFFIX: &str = ".state";
+const DONE: &str = "done";
+// jobs waiting between stages of the pipeline, so a fast stage can't run
+// far ahead of a slow one
+const STAGE_QUEUE_LENGTH: usize = 16;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct JobLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for JobLineError {}
+
+impl fmt::Display for JobLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the jobs file! Expected \"name: first-last\" or \"name: book.pdf\", optionally followed by \"on stock\", got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobPages {
+    Range(u32, u32),
+    // a PDF to plan all the pages of
+    Pdf(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Job {
+    pub name: String,
+    pub pages: JobPages,
+    // the stock the whole job is printed on, in place of the stocks file
+    pub stock: Option<String>,
+}
+
+impl Job {
+    pub fn page_range(&self) -> Result<(u32, u32), Box<dyn Error>> {
+        match &self.pages {
+            JobPages::Range(first, last) => Ok((*first, *last)),
+            JobPages::Pdf(path) => Ok((1, page_count::page_count(path)?)),
+        }
+    }
+
+    pub fn plan(&self) -> Result<DocumentInfo, Box<dyn Error>> {
+        let (first, last) = self.page_range()?;
+        DocumentInfo::new(first, last)
+    }
+
+    fn key(&self) -> String {
+        // the job as written in the jobs file, so an edited job is done again
+        let pages = match &self.pages {
+            JobPages::Range(first, last) => format!("{}-{}", first, last),
+            JobPages::Pdf(path) => path.clone(),
+        };
+        match &self.stock {
+            Some(stock) => format!("{}: {} on {}", self.name, pages, stock),
+            None => format!("{}: {}", self.name, pages),
+        }
+    }
+}
+
+// which jobs of a batch are finished, kept in a file as they finish so a
+// batch cut short can carry on where it stopped
+#[derive(Debug)]
+pub struct BatchState {
+    file: File,
+    done: Vec<String>,
+}
+
+impl BatchState {
+    pub fn state_path(jobs_path: &str) -> String {
+        format!("{}{}", jobs_path, STATE_SUFFIX)
+    }
+
+    pub fn start(jobs_path: &str, resume: bool) -> Result<BatchState, Box<dyn Error>> {
+        // Resuming carries on from the jobs already done, if there are any;
+        // otherwise the batch starts again from nothing.
+        let path = BatchState::state_path(jobs_path);
+        let done = match fs::read_to_string(&path) {
+            Ok(contents) if resume => contents
+                .lines()
+                .filter_map(|line| line.strip_prefix(DONE).and_then(|key| key.strip_prefix('\t')))
+                .map(|key| key.to_string())
+                .collect(),
+            Ok(_) => Vec::new(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let file = OpenOptions::new().create(true).append(resume).write(true).truncate(!resume).open(&path)?;
+        Ok(BatchState {file, done})
+    }
+
+    pub fn is_done(&self, job: &Job) -> bool {
+        self.done.contains(&job.key())
+    }
+
+    pub fn mark_done(&mut self, job: &Job) -> Result<(), Box<dyn Error>> {
+        // on the disk before going on, in case the power goes
+        writeln!(self.file, "{}\t{}", DONE, job.key())?;
+        self.file.sync_all()?;
+        self.done.push(job.key());
+        Ok(())
+    }
+}
+
+// how many threads work on each stage of planning a batch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pipeline {
+    // reading the PDFs for their page counts
+    pub readers: usize,
+    pub planners: usize,
+}
+
+impl Default for Pipeline {
+    fn default() -> Pipeline {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Pipeline {readers: threads, planners: threads}
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub num_jobs: usize,
+    pub num_sheets: u32,
+    pub num_signatures: u32,
+    // sheets needed of each stock, in the order the stocks are first needed
+    pub stock_sheets: Vec<(String, u32)>,
+}
+
+
+// Work
+pub fn read_jobs(path: &str) -> Result<Vec<Job>, Box<dyn Error>> {
+    parse_jobs(&fs::read_to_string(path)?)
+}
+
+pub fn parse_jobs(contents: &str) -> Result<Vec<Job>, Box<dyn Error>> {
+    // Read the jobs, one per line:
+    //   The Compleat Angler: 1-212
+    //   Poems: poems.pdf on 100gsm Munken Cream, SRA3
+    // Blank lines and lines starting with # are ignored.
+    let mut jobs = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_error = || JobLineError {line_number: i + 1, line: line.to_string()};
+        let (name, value) = match line.find(':') {
+            Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+            None => return Err(line_error().into()),
+        };
+        let (pages, stock) = match value.split_once(" on ") {
+            Some((pages, stock)) => (pages.trim(), Some(stock.trim().to_string())),
+            None => (value, None),
+        };
+        if name.is_empty() || stock.as_deref() == Some("") {
+            return Err(line_error().into());
+        }
+        let pages = if page_count::is_pdf_path(pages) {
+            JobPages::Pdf(pages.to_string())
+        } else {
+            match pages.split_once('-').map(|(first, last)| (first.trim().parse(), last.trim().parse())) {
+                Some((Ok(first), Ok(last))) => JobPages::Range(first, last),
+                _ => return Err(line_error().into()),
+            }
+        };
+        jobs.push(Job {name: name.to_string(), pages, stock});
+    }
+    Ok(jobs)
+}
+
+pub fn plan_jobs(jobs: Vec<Job>, pipeline: Pipeline) -> Vec<(Job, Option<DocumentInfo>)> {
+    // Read and plan the jobs in stages, each with its own threads, joined
+    // by short queues, and hand them back in their original order. Errors
+    // can't be sent between threads, so a job that fails comes back with
+    // no plan, to plan again for its error.
+    let num_jobs = jobs.len();
+    let (job_sender, job_receiver) = mpsc::sync_channel(STAGE_QUEUE_LENGTH);
+    let (read_sender, read_receiver) = mpsc::sync_channel(STAGE_QUEUE_LENGTH);
+    let (plan_sender, plan_receiver) = mpsc::sync_channel(STAGE_QUEUE_LENGTH);
+    let job_receiver = Arc::new(Mutex::new(job_receiver));
+    let read_receiver = Arc::new(Mutex::new(read_receiver));
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            for job in jobs.into_iter().enumerate() {
+                if job_sender.send(job).is_err() {
+                    break;
+                }
+            }
+        });
+        for _ in 0..pipeline.readers.max(1) {
+            let (receiver, sender) = (Arc::clone(&job_receiver), read_sender.clone());
+            scope.spawn(move || {
+                while let Some((index, job)) = next(&receiver) {
+                    let page_range = job.page_range().ok();
+                    if sender.send((index, job, page_range)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(read_sender);
+        for _ in 0..pipeline.planners.max(1) {
+            let (receiver, sender) = (Arc::clone(&read_receiver), plan_sender.clone());
+            scope.spawn(move || {
+                while let Some((index, job, page_range)) = next(&receiver) {
+                    let plan = page_range.and_then(|(first, last)| DocumentInfo::new(first, last).ok());
+                    if sender.send((index, job, plan)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(plan_sender);
+        let mut planned: Vec<Option<(Job, Option<DocumentInfo>)>> = (0..num_jobs).map(|_| None).collect();
+        for (index, job, plan) in plan_receiver {
+            planned[index] = Some((job, plan));
+        }
+        planned.into_iter().flatten().collect()
+    })
+}
+
+fn next<T>(receiver: &Mutex<Receiver<T>>) -> Option<T> {
+    // the next item for whichever of a stage's threads asks first
+    receiver.lock().ok()?.recv().ok()
+}
+
+pub fn summarise(plans: &[(Job, DocumentInfo)], stocks: &Stocks) -> BatchSummary {
+    // Add up the jobs, sharing out each one's sheets between its stocks
+    // the same way the press run order does.
+    let mut summary = BatchSummary {num_jobs: plans.len(), ..BatchSummary::default()};
+    for (job, document) in plans {
+        summary.num_sheets += document.num_sheets();
+        summary.num_signatures += document.num_signatures();
+        let runs: Vec<(String, u32)> = match &job.stock {
+            Some(stock) => vec![(stock.clone(), document.num_sheets())],
+            None => press::run_order(document, stocks)
+                .iter()
+                .map(|run| (run.stock.clone(), run.num_sheets()))
+                .collect(),
+        };
+        for (stock, sheets) in runs {
+            match summary.stock_sheets.iter_mut().find(|(known, _)| *known == stock) {
+                Some((_, total)) => *total += sheets,
+                None => summary.stock_sheets.push((stock, sheets)),
+            }
+        }
+    }
+    summary
+}
+
+pub fn display_summary(summary: &BatchSummary) {
+    println!("Batch summary:");
+    println!("Jobs: {}", summary.num_jobs);
+    println!("Total sheets to print: {}", summary.num_sheets);
+    println!("Total signatures to bind: {}", summary.num_signatures);
+    for (stock, sheets) in &summary.stock_sheets {
+        println!("   {}: {} sheets", stock, sheets);
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_parse_jobs() {
+        let jobs = parse_jobs("# Monday\nThe Compleat Angler: 1-212\n\nPoems: poems.pdf on 100gsm cream\n").unwrap();
+        assert_eq!(
+            jobs,
+            vec![
+                Job {name: "The Compleat Angler".to_string(), pages: JobPages::Range(1, 212), stock: None},
+                Job {
+                    name: "Poems".to_string(),
+                    pages: JobPages::Pdf("poems.pdf".to_string()),
+                    stock: Some("100gsm cream".to_string()),
+                },
+            ],
+        );
+
+        match parse_jobs("Angler: 1 to 212\n") {
+            Ok(_) => panic!("parse_jobs should have rejected a line without a page range"),
+            Err(result_error) => assert!(
+                format!("{}", result_error).starts_with("Couldn't read line 1 of the jobs file!"),
+            ),
+        }
+        assert!(parse_jobs(": 1-20\n").is_err());
+        assert!(parse_jobs("Angler: 1-20 on \n").is_err());
+    }
+
+    #[test]
+    fn test_plan_jobs() {
+        // in order whatever order the threads finish in, and a job that
+        // can't be planned comes back without a plan
+        let jobs = parse_jobs("Angler: 1-40\nPoems: 1-22\nBackwards: 20-1\nTracts: 5-8\n").unwrap();
+        let sheets = |planned: Vec<(Job, Option<DocumentInfo>)>| -> Vec<(String, Option<u32>)> {
+            planned.into_iter().map(|(job, plan)| (job.name, plan.map(|plan| plan.num_sheets()))).collect()
+        };
+        let expected = vec![
+            ("Angler".to_string(), Some(10)),
+            ("Poems".to_string(), Some(6)),
+            ("Backwards".to_string(), None),
+            ("Tracts".to_string(), Some(1)),
+        ];
+        assert_eq!(sheets(plan_jobs(jobs.clone(), Pipeline {readers: 3, planners: 2})), expected);
+        assert_eq!(sheets(plan_jobs(jobs, Pipeline {readers: 1, planners: 1})), expected);
+    }
+
+    #[test]
+    fn test_batch_state() {
+        let jobs_path = env::temp_dir().join("rust-signatures-test-jobs.txt").to_string_lossy().to_string();
+        let jobs = parse_jobs("Angler: 1-40\nPoems: 1-22 on gloss\n").unwrap();
+        let mut state = BatchState::start(&jobs_path, false).unwrap();
+        assert!(!state.is_done(&jobs[0]));
+        state.mark_done(&jobs[0]).unwrap();
+        assert!(state.is_done(&jobs[0]));
+        drop(state);
+        assert_eq!(fs::read_to_string(BatchState::state_path(&jobs_path)).unwrap(), "done\tAngler: 1-40\n");
+
+        // resuming remembers, starting again forgets
+        let mut state = BatchState::start(&jobs_path, true).unwrap();
+        assert!(state.is_done(&jobs[0]));
+        assert!(!state.is_done(&jobs[1]));
+        state.mark_done(&jobs[1]).unwrap();
+        drop(state);
+        assert_eq!(
+            fs::read_to_string(BatchState::state_path(&jobs_path)).unwrap(),
+            "done\tAngler: 1-40\ndone\tPoems: 1-22 on gloss\n",
+        );
+        let state = BatchState::start(&jobs_path, false).unwrap();
+        assert!(!state.is_done(&jobs[0]));
+        fs::remove_file(BatchState::state_path(&jobs_path)).unwrap();
+    }
+
+    #[test]
+    fn test_summarise() {
+        // 10 sheets in 3 signatures, then 6 sheets in 2
+        let jobs = parse_jobs("Angler: 1-40\nPoems: 1-22 on gloss\n").unwrap();
+        let plans: Vec<(Job, DocumentInfo)> = jobs.into_iter().map(|job| {
+            let document = job.plan().unwrap();
+            (job, document)
+        }).collect();
+        let stocks = Stocks::parse("default: cream\nB: gloss\n").unwrap();
+        let summary = summarise(&plans, &stocks);
+        assert_eq!(summary.num_jobs, 2);
+        assert_eq!(summary.num_sheets, 16);
+        assert_eq!(summary.num_signatures, 5);
+        assert_eq!(summary.stock_sheets, vec![("cream".to_string(), 6), ("gloss".to_string(), 10)]);
+    }
+}
@@ -0,0 +1,9 @@
+use std::process;
+
+
+fn main() {
+    rust_signatures::gui::run().unwrap_or_else(|err| {
+        eprintln!("Problem starting the planner window: {}", err);
+        process::exit(1);
+    });
+}
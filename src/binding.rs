@@ -0,0 +1,66 @@
+use crate::{DocumentInfo, Signature};
+
+
+// Data structs
+// which end of the book the binder starts from when working through the
+// signatures, which varies from bench to bench
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingOrder {
+    FirstToLast,
+    LastToFirst,
+}
+
+impl BindingOrder {
+    fn describe(&self) -> &'static str {
+        match self {
+            BindingOrder::FirstToLast => "first to last",
+            BindingOrder::LastToFirst => "last to first",
+        }
+    }
+}
+
+
+// Work
+pub fn binding_order(document: &DocumentInfo, order: BindingOrder) -> Vec<String> {
+    // the signature keys in the order the binder picks them up
+    ordered_signatures(document, order)
+        .iter()
+        .map(|signature| signature.key().to_string())
+        .collect()
+}
+
+pub fn display_binding_order(document: &DocumentInfo, step: &str, order: BindingOrder) {
+    // e.g. "Sewing order (last to first):" then one numbered line per signature
+    println!("{} order ({}):", step, order.describe());
+    for (i, signature) in ordered_signatures(document, order).iter().enumerate() {
+        println!(
+            "{}. Signature {} (pages {} to {})",
+            i + 1,
+            document.label_scheme().display_key(signature.key()),
+            document.shown_page(signature.first_page()),
+            document.shown_page(signature.last_page()),
+        );
+    }
+    println!("#####################################");
+}
+
+fn ordered_signatures(document: &DocumentInfo, order: BindingOrder) -> Vec<&Signature> {
+    let mut signatures: Vec<&Signature> = document.signatures().iter().collect();
+    if order == BindingOrder::LastToFirst {
+        signatures.reverse();
+    }
+    signatures
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_order() {
+        let document = DocumentInfo::new(1, 60).unwrap();
+        assert_eq!(binding_order(&document, BindingOrder::FirstToLast), vec!["A", "B", "C", "D"]);
+        assert_eq!(binding_order(&document, BindingOrder::LastToFirst), vec!["D", "C", "B", "A"]);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::stamp::{is_recto, Placement};
+use crate::DocumentInfo;
+
+
+// Data structs
+// which pages carry a catchword, the first word of the following page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CatchwordConvention {
+    EveryPage,
+    Versos,
+    // the last page of each gathering, to keep the gatherings in order
+    GatheringEnds,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catchword {
+    pub page: u32,
+    // the page whose first word is repeated
+    pub next_page: u32,
+    pub placement: Placement,
+}
+
+
+// Work
+pub fn catchwords(document: &DocumentInfo, convention: CatchwordConvention) -> Vec<Catchword> {
+    // Catchwords always sit at the foot of the page, on the right. The last
+    // page of the document has nothing to lead on to.
+    let needs_catchword = |page: u32| match convention {
+        CatchwordConvention::EveryPage => true,
+        CatchwordConvention::Versos => !is_recto(page),
+        CatchwordConvention::GatheringEnds => {
+            document.signatures().iter().any(|signature| signature.last_page() == page)
+        },
+    };
+    (document.first_page()..document.last_page())
+        .filter(|page| needs_catchword(*page))
+        .map(|page| Catchword {page, next_page: page + 1, placement: Placement::FootRight})
+        .collect()
+}
+
+pub fn display_catchwords(document: &DocumentInfo, catchwords: &[Catchword]) {
+    println!("Catchwords:");
+    for catchword in catchwords {
+        println!(
+            "Page {}: first word of page {}, at {}",
+            document.shown_page(catchword.page),
+            document.shown_page(catchword.next_page),
+            catchword.placement.describe(),
+        );
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(catchwords: &[Catchword]) -> Vec<u32> {
+        catchwords.iter().map(|catchword| catchword.page).collect()
+    }
+
+    #[test]
+    fn test_catchwords() {
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let every_page = catchwords(&document, CatchwordConvention::EveryPage);
+        assert_eq!(every_page.len(), 39);
+        assert_eq!(every_page[0], Catchword {page: 1, next_page: 2, placement: Placement::FootRight});
+        assert_eq!(pages(&catchwords(&document, CatchwordConvention::Versos))[..3], [2, 4, 6]);
+        assert_eq!(catchwords(&document, CatchwordConvention::Versos).len(), 19);
+        assert_eq!(pages(&catchwords(&document, CatchwordConvention::GatheringEnds)), vec![16, 32]);
+    }
+}
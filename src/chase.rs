@@ -0,0 +1,182 @@
+// Diagrams of the traditional hand-press impositions, showing where each
+// page sits in the chase and which way its head points. These describe a
+// single sheet folded into a gathering, the way the bibliography books lay
+// them out, rather than the nested 2-up sheets of the plan.
+
+use std::error::Error;
+use std::fmt;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct ChaseFormatError {
+    value: String,
+}
+
+impl Error for ChaseFormatError {}
+
+impl fmt::Display for ChaseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown format: {}. Expected one of: folio, quarto, octavo", self.value)
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChaseFormat {
+    Folio,
+    Quarto,
+    Octavo,
+}
+
+impl ChaseFormat {
+    pub fn parse(value: &str) -> Result<ChaseFormat, Box<dyn Error>> {
+        match value {
+            "folio" => Ok(ChaseFormat::Folio),
+            "quarto" => Ok(ChaseFormat::Quarto),
+            "octavo" => Ok(ChaseFormat::Octavo),
+            _ => Err((ChaseFormatError {value: value.to_string()}).into()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ChaseFormat::Folio => "Folio",
+            ChaseFormat::Quarto => "Quarto",
+            ChaseFormat::Octavo => "Octavo",
+        }
+    }
+
+    pub fn outer_forme(&self) -> Forme {
+        // The standard layouts, top row first, as the pages stand in the
+        // chase. Pages in the top row of the quarto and octavo are printed
+        // head down, so the heads meet at the first fold.
+        let (top, bottom): (&[u32], &[u32]) = match self {
+            ChaseFormat::Folio => (&[], &[4, 1]),
+            ChaseFormat::Quarto => (&[5, 4], &[8, 1]),
+            ChaseFormat::Octavo => (&[5, 12, 9, 8], &[4, 13, 16, 1]),
+        };
+        let row = |pages: &[u32], head_down: bool| pages.iter().map(|&page| ChasePage {page, head_down}).collect();
+        let mut rows: Vec<Vec<ChasePage>> = Vec::new();
+        if !top.is_empty() {
+            rows.push(row(top, true));
+        }
+        rows.push(row(bottom, false));
+        Forme {rows}
+    }
+
+    pub fn inner_forme(&self) -> Forme {
+        // Turning the sheet over swaps left and right, and puts each page's
+        // other side where it was.
+        let rows = self
+            .outer_forme()
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .rev()
+                    .map(|cell| ChasePage {page: backing_page(cell.page), head_down: cell.head_down})
+                    .collect()
+            })
+            .collect();
+        Forme {rows}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChasePage {
+    pub page: u32,
+    pub head_down: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forme {
+    pub rows: Vec<Vec<ChasePage>>,
+}
+
+impl Forme {
+    pub(crate) fn draw(&self) -> String {
+        // a box per page, with ^ or v pointing to its head
+        let border = format!("+{}\n", "------+".repeat(self.rows[0].len()));
+        let mut diagram = border.clone();
+        for row in &self.rows {
+            diagram.push('|');
+            for cell in row {
+                diagram.push_str(if cell.head_down { "  v   |" } else { "  ^   |" });
+            }
+            diagram.push_str("\n|");
+            for cell in row {
+                diagram.push_str(&format!(" {:^4} |", cell.page));
+            }
+            diagram.push('\n');
+            diagram.push_str(&border);
+        }
+        diagram
+    }
+}
+
+
+// Work
+pub fn chase_diagram(format: ChaseFormat) -> String {
+    let mut diagram = String::new();
+    diagram.push_str(&format!("{}, outer forme:\n", format.name()));
+    diagram.push_str(&format.outer_forme().draw());
+    diagram.push_str(&format!("{}, inner forme:\n", format.name()));
+    diagram.push_str(&format.inner_forme().draw());
+    diagram.push_str("^ and v point to the head of each page.\n");
+    diagram
+}
+
+fn backing_page(page: u32) -> u32 {
+    // the other side of the leaf: 1 backs 2, 3 backs 4, ...
+    if page % 2 == 1 {
+        page + 1
+    } else {
+        page - 1
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(forme: &Forme) -> Vec<Vec<u32>> {
+        forme.rows.iter().map(|row| row.iter().map(|cell| cell.page).collect()).collect()
+    }
+
+    #[test]
+    fn test_formes() {
+        assert_eq!(pages(&ChaseFormat::Folio.inner_forme()), vec![vec![2, 3]]);
+        assert_eq!(pages(&ChaseFormat::Quarto.inner_forme()), vec![vec![3, 6], vec![2, 7]]);
+        assert_eq!(pages(&ChaseFormat::Octavo.inner_forme()), vec![vec![7, 10, 11, 6], vec![2, 15, 14, 3]]);
+        assert!(ChaseFormat::Octavo.inner_forme().rows[0][0].head_down);
+        assert!(!ChaseFormat::Octavo.inner_forme().rows[1][0].head_down);
+
+        // every page appears once across the two formes
+        let mut all: Vec<u32> = pages(&ChaseFormat::Octavo.outer_forme())
+            .into_iter()
+            .chain(pages(&ChaseFormat::Octavo.inner_forme()))
+            .flatten()
+            .collect();
+        all.sort();
+        assert_eq!(all, (1..=16).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_chase_diagram() {
+        let diagram = chase_diagram(ChaseFormat::Quarto);
+        assert!(diagram.starts_with(
+            "Quarto, outer forme:\n+------+------+\n|  v   |  v   |\n|  5   |  4   |\n+------+------+\n|  ^   |  ^   |\n|  8   |  1   |\n"
+        ));
+        assert!(diagram.contains("Quarto, inner forme:\n"));
+        match ChaseFormat::parse("duodecimo") {
+            Ok(_) => panic!("ChaseFormat::parse should have rejected duodecimo"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Unknown format: duodecimo. Expected one of: folio, quarto, octavo",
+            ),
+        }
+    }
+}
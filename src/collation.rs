@@ -0,0 +1,101 @@
+// Reading a bibliographer's collation formula, e.g. A–K⁸ L⁴, back into the
+// gatherings it describes, so a book known only from its description can
+// be planned for re-sewing.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::labels::LabelScheme;
+use crate::SUPERSCRIPT_DIGITS;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct CollationFormulaError {
+    gathering: String,
+    problem: String,
+}
+
+impl Error for CollationFormulaError {}
+
+impl fmt::Display for CollationFormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Couldn't read {} in the collation formula: {}.", self.gathering, self.problem)
+    }
+}
+
+
+// Work
+pub fn parse_collation_formula(formula: &str, scheme: &LabelScheme) -> Result<Vec<u32>, Box<dyn Error>> {
+    // The pages in each gathering, in order. Runs of gatherings are
+    // separated by spaces, each its first and last key, then the leaves in
+    // each gathering as a superscript or plain digits: "A–K⁸ L⁴" or
+    // "A-K8 L4". The runs have to carry on from one another, from the
+    // scheme's first key.
+    let mut gatherings = Vec::new();
+    for run in formula.split_whitespace() {
+        let problem = |problem: String| -> Box<dyn Error> {
+            (CollationFormulaError {gathering: run.to_string(), problem}).into()
+        };
+        let digits_at = run.find(|c: char| c.is_ascii_digit() || SUPERSCRIPT_DIGITS.contains(&c));
+        let (keys, leaves) = run.split_at(digits_at.unwrap_or(run.len()));
+        let leaves: Option<u32> =
+            leaves.chars().map(plain_digit).collect::<Option<String>>().and_then(|leaves| leaves.parse().ok());
+        let (first_key, last_key) = keys.split_once(['-', '\u{2013}']).unwrap_or((keys, keys));
+        let (leaves, first, last) = match (leaves, scheme.parse_key(first_key), scheme.parse_key(last_key)) {
+            (Some(leaves), Ok(first), Ok(last)) if !first_key.is_empty() && first <= last => (leaves, first, last),
+            _ => return Err(problem("expected signature keys then the number of leaves, e.g. A\u{2013}K\u{2078}".to_string())),
+        };
+        if first != gatherings.len() as u32 {
+            return Err(problem(format!("the gatherings should carry on from {}", scheme.key(gatherings.len() as u32))));
+        }
+        if leaves == 0 || !leaves.is_multiple_of(2) {
+            return Err(problem("each gathering needs an even number of leaves, folded from whole sheets".to_string()));
+        }
+        gatherings.extend((first..=last).map(|_| leaves * 2));
+    }
+    Ok(gatherings)
+}
+
+fn plain_digit(c: char) -> Option<char> {
+    // 0 to 9, or their superscripts
+    match SUPERSCRIPT_DIGITS.iter().position(|digit| *digit == c) {
+        Some(position) => char::from_digit(position as u32, 10),
+        None => Some(c).filter(|c| c.is_ascii_digit()),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DocumentInfo;
+
+    #[test]
+    fn test_parse_collation_formula() {
+        let scheme = LabelScheme::default();
+        assert_eq!(parse_collation_formula("A\u{2013}C\u{2078} D\u{2074}", &scheme).unwrap(), vec![16, 16, 16, 8]);
+        assert_eq!(parse_collation_formula("A-B12 C4", &scheme).unwrap(), vec![24, 24, 8]);
+        // the formula DocumentInfo gives reads back as the same gatherings
+        let document = DocumentInfo::new(1, 40).unwrap();
+        assert_eq!(parse_collation_formula(&document.collation_formula(), &scheme).unwrap(), vec![16, 16, 8]);
+
+        match parse_collation_formula("A-C8 E4", &scheme) {
+            Ok(_) => panic!("Should have errored because D is missing!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Couldn't read E4 in the collation formula: the gatherings should carry on from D.",
+            ),
+        }
+        match parse_collation_formula("A-C8 D3", &scheme) {
+            Ok(_) => panic!("Should have errored because 3 leaves can't be folded from whole sheets!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Couldn't read D3 in the collation formula: each gathering needs an even number of leaves, \
+                 folded from whole sheets.",
+            ),
+        }
+        assert!(parse_collation_formula("A-C", &scheme).is_err());
+        assert!(parse_collation_formula("8", &scheme).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Colour pages, which cost more to print than black and white: given as
+// spans of pages, or found by having Ghostscript measure each page's ink,
+// then counted by the sheets and sheet sides that have to go through in
+// colour. They can also be kept to signatures of their own, so only those
+// signatures are printed in colour.
+
+use std::error::Error;
+
+use crate::constraints::{self, Constraint};
+use crate::imposition;
+use crate::DocumentInfo;
+
+// Constants
+// the cyan, magenta or yellow coverage Ghostscript measures, as a fraction
+// of the page, above which a page is colour rather than grey
+const COLOUR_COVERAGE: f64 = 0.0001;
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColourPages {
+    // in the page numbers given, e.g. [(5, 8), (33, 48)]
+    Spans(Vec<(u32, u32)>),
+    // measured from the input PDF
+    Detect,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourCount {
+    pub signature_key: String,
+    pub sheets: u32,
+    // sheets with colour on either side, and the sides with colour
+    pub colour_sheets: u32,
+    pub colour_sides: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourReport {
+    pub colour_pages: u32,
+    pub signatures: Vec<ColourCount>,
+}
+
+impl ColourReport {
+    pub fn sheets(&self) -> u32 {
+        self.signatures.iter().map(|count| count.sheets).sum()
+    }
+
+    pub fn colour_sheets(&self) -> u32 {
+        self.signatures.iter().map(|count| count.colour_sheets).sum()
+    }
+
+    pub fn colour_sides(&self) -> u32 {
+        self.signatures.iter().map(|count| count.colour_sides).sum()
+    }
+}
+
+
+// Work
+pub fn parse_colour_pages(value: &str) -> Option<ColourPages> {
+    // "auto", or pages and spans of pages, e.g. "5,33-48"
+    if value == "auto" {
+        return Some(ColourPages::Detect);
+    }
+    let mut spans = Vec::new();
+    for part in value.split(',') {
+        match part.trim().parse::<u32>() {
+            Ok(page) => spans.push((page, page)),
+            Err(_) => spans.push(constraints::parse_page_span(part)?),
+        }
+    }
+    Some(ColourPages::Spans(spans))
+}
+
+pub fn ink_coverage_command(input: &str) -> Vec<String> {
+    vec![
+        "gs".to_string(),
+        "-q".to_string(),
+        "-o".to_string(),
+        "-".to_string(),
+        "-sDEVICE=inkcov".to_string(),
+        input.to_string(),
+    ]
+}
+
+pub fn colour_spans(inkcov: &str, first_page: u32) -> Vec<(u32, u32)> {
+    // Ghostscript prints a line per page, e.g.
+    //  0.01234  0.00000  0.00000  0.04567 CMYK OK
+    // and consecutive colour pages are joined into spans.
+    let mut spans: Vec<(u32, u32)> = Vec::new();
+    let pages = inkcov.lines().filter(|line| line.trim_end().ends_with("CMYK OK"));
+    for (i, line) in pages.enumerate() {
+        let coverage: Vec<f64> = line.split_whitespace().take(3).filter_map(|value| value.parse().ok()).collect();
+        if !coverage.iter().any(|&ink| ink > COLOUR_COVERAGE) {
+            continue;
+        }
+        let page = first_page + i as u32;
+        match spans.last_mut() {
+            Some(span) if span.1 + 1 == page => span.1 = page,
+            _ => spans.push((page, page)),
+        }
+    }
+    spans
+}
+
+pub fn detect_colour_pages(input: &str, first_page: u32) -> Result<Vec<(u32, u32)>, Box<dyn Error>> {
+    let inkcov = crate::deimpose::read_output(&ink_coverage_command(input))?;
+    Ok(colour_spans(&inkcov, first_page))
+}
+
+pub fn is_colour(spans: &[(u32, u32)], page: u32) -> bool {
+    spans.iter().any(|&(from, to)| from <= page && page <= to)
+}
+
+pub fn segregating_constraints(spans: &[(u32, u32)], first_page: u32, last_page: u32) -> Vec<Constraint> {
+    // each span starts a signature, and so does the page after it
+    let mut constraints = Vec::new();
+    for &(from, to) in spans {
+        if from > first_page {
+            constraints.push(Constraint::StartsSignature(from));
+        }
+        if to < last_page {
+            constraints.push(Constraint::StartsSignature(to + 1));
+        }
+    }
+    constraints
+}
+
+pub fn colour_report(document: &DocumentInfo, spans: &[(u32, u32)]) -> ColourReport {
+    // spans counted as the plan counts pages, from 1 even when zero indexed
+    let colour = |page: Option<u32>| page.is_some_and(|page| is_colour(spans, page));
+    let pages = document.first_page()..=document.last_page();
+    let colour_pages = pages.filter(|&page| colour(Some(page)));
+    let mut signatures = Vec::new();
+    for signature in document.signatures() {
+        let formes = imposition::signature_formes(document, signature);
+        let mut count = ColourCount {
+            signature_key: signature.key().to_string(),
+            sheets: (formes.len() / 2) as u32,
+            colour_sheets: 0,
+            colour_sides: 0,
+        };
+        for sheet in formes.chunks(2) {
+            let colour_sides = sheet
+                .iter()
+                .filter(|forme| forme.rows.iter().flatten().any(|cell| colour(cell.page)))
+                .count() as u32;
+            count.colour_sides += colour_sides;
+            count.colour_sheets += u32::from(colour_sides > 0);
+        }
+        signatures.push(count);
+    }
+    ColourReport {colour_pages: colour_pages.count() as u32, signatures}
+}
+
+pub fn display_colour_report(document: &DocumentInfo, report: &ColourReport, copies: u32) {
+    println!("Colour pages: {} of {}", report.colour_pages, document.num_pages());
+    for count in report.signatures.iter().filter(|count| count.colour_sheets > 0) {
+        println!(
+            "Signature {}: {} of {} sheets in colour, {} sheet sides",
+            count.signature_key, count.colour_sheets, count.sheets, count.colour_sides,
+        );
+    }
+    println!(
+        "Colour sheets: {} of {}, {} sheet sides, per copy",
+        report.colour_sheets(),
+        report.sheets(),
+        report.colour_sides(),
+    );
+    if copies > 1 {
+        let copies = u64::from(copies);
+        println!(
+            "Colour sheets for {} copies: {}, {} sheet sides",
+            copies,
+            u64::from(report.colour_sheets()) * copies,
+            u64::from(report.colour_sides()) * copies,
+        );
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colour_pages() {
+        assert_eq!(parse_colour_pages("5, 33-48"), Some(ColourPages::Spans(vec![(5, 5), (33, 48)])));
+        assert_eq!(parse_colour_pages("auto"), Some(ColourPages::Detect));
+        assert_eq!(parse_colour_pages("48-33"), None);
+        assert_eq!(parse_colour_pages("plates"), None);
+    }
+
+    #[test]
+    fn test_colour_spans() {
+        let inkcov = [
+            " 0.00000  0.00000  0.00000  0.04567 CMYK OK",
+            " 0.01234  0.00210  0.00000  0.04567 CMYK OK",
+            " 0.00000  0.00000  0.03100  0.00000 CMYK OK",
+            " 0.00000  0.00000  0.00000  0.02000 CMYK OK",
+            " 0.20000  0.10000  0.30000  0.00000 CMYK OK",
+        ]
+        .join("\n");
+        assert_eq!(colour_spans(&inkcov, 1), vec![(2, 3), (5, 5)]);
+        assert!(colour_spans("", 1).is_empty());
+    }
+
+    #[test]
+    fn test_colour_report() {
+        // a plate section of 4 pages, 17-20, opening the second signature
+        // of 16, falls on the outer two of its four sheets
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let report = colour_report(&document, &[(17, 20)]);
+        assert_eq!(report.colour_pages, 4);
+        assert_eq!(
+            report.signatures[1],
+            ColourCount {signature_key: "B".to_string(), sheets: 4, colour_sheets: 2, colour_sides: 4},
+        );
+        assert_eq!((report.sheets(), report.colour_sheets(), report.colour_sides()), (10, 2, 4));
+    }
+
+    #[test]
+    fn test_segregating_constraints() {
+        let constraints = segregating_constraints(&[(1, 8), (33, 40)], 1, 64);
+        assert_eq!(constraints, vec![Constraint::StartsSignature(9), Constraint::StartsSignature(33), Constraint::StartsSignature(41)]);
+        assert_eq!(constraints::choose_size(1, 64, &constraints).unwrap(), 8);
+    }
+}
@@ -0,0 +1,163 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::imposition::{FoldScheme, ReadingDirection};
+use crate::DocumentInfo;
+
+// Constants
+const INPUT_FILE: &str = "input.pdf";
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct ToolCommandsError {
+    reasons: Vec<String>,
+}
+
+impl Error for ToolCommandsError {}
+
+impl fmt::Display for ToolCommandsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pdfjam, pdfbook2 and psbook can't impose this plan, as {}. Use the impose subcommand instead.",
+            self.reasons.join(", and "),
+        )
+    }
+}
+
+
+// Work
+pub fn equivalent_commands(document: &DocumentInfo) -> Result<String, Box<dyn Error>> {
+    // Build the pdfjam/pdfbook2/psbook command lines that impose the same
+    // signatures as this plan, for people who want those tools to do the
+    // PDF work. They only fold sheets once, print both sides, read left to
+    // right and make every signature but the last the same size, so a plan
+    // that doesn't is refused rather than given commands making another book.
+    let reasons = unsupported(document);
+    if !reasons.is_empty() {
+        return Err((ToolCommandsError {reasons}).into());
+    }
+    let range = format!("{}-{}", document.first_page(), document.last_page());
+    let size = document.pages_per_signature();
+    let mut commands = document.job().header("# ");
+    commands.push_str(&format!(
+        "# Pages {} to {} in {}-page signatures. Replace {} with your file.\n",
+        document.first_page(),
+        document.last_page(),
+        size,
+        INPUT_FILE,
+    ));
+    commands.push_str("# Pull out the pages to print:\n");
+    commands.push_str(&format!("mutool merge -o pages.pdf {} {}\n", INPUT_FILE, range));
+    commands.push_str("# Impose them into signatures with pdfjam:\n");
+    commands.push_str(&format!("pdfjam --landscape --signature {} --outfile signatures.pdf pages.pdf\n", size));
+    commands.push_str("# ...or with pdfbook2:\n");
+    commands.push_str(&format!("pdfbook2 --signature={} --no-crop pages.pdf\n", size));
+    commands.push_str("# ...or, for a PostScript workflow, with psbook:\n");
+    commands.push_str(&format!("psbook -s{} pages.ps signatures.ps\n", size));
+    commands.push_str("# Split the imposed sheet sides back into single pages to proof them:\n");
+    commands.push_str("mutool poster -x 2 signatures.pdf proof.pdf\n");
+    Ok(commands)
+}
+
+pub fn shell_line(command: &[String]) -> String {
+    // a command as a line to paste into a shell, each argument quoted
+    // unless it's made only of characters the shell leaves alone
+    command.iter().map(|argument| shell_quote(argument)).collect::<Vec<String>>().join(" ")
+}
+
+fn shell_quote(argument: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c);
+    if !argument.is_empty() && argument.chars().all(plain) {
+        return argument.to_string();
+    }
+    format!("'{}'", argument.replace('\'', "'\\''"))
+}
+
+fn unsupported(document: &DocumentInfo) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if document.is_one_sided() {
+        reasons.push("it's printed on one side only".to_string());
+    } else if document.fold_scheme() != FoldScheme::Folio {
+        reasons.push(format!("its sheets are folded in {}", document.fold_scheme().name()));
+    }
+    if document.reading_direction() == ReadingDirection::RightToLeft {
+        reasons.push("it reads right to left".to_string());
+    }
+    // balanced and collated plans have signatures of their own sizes
+    let size = document.pages_per_signature();
+    if let Some((last, others)) = document.signatures().split_last() {
+        if others.iter().any(|signature| signature.num_pages() != size) || last.num_pages() > size {
+            reasons.push(format!("its signatures aren't all {} pages but the last", size));
+        }
+    }
+    reasons
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_line() {
+        let command = |arguments: &[&str]| arguments.iter().map(|argument| argument.to_string()).collect::<Vec<String>>();
+        assert_eq!(shell_line(&command(&["lp", "-o", "page-ranges=1-16", "imposed.pdf"])), "lp -o page-ranges=1-16 imposed.pdf");
+        assert_eq!(
+            shell_line(&command(&["lp", "-o", "media-source=Tray 2", "my book.pdf"])),
+            "lp -o 'media-source=Tray 2' 'my book.pdf'",
+        );
+        assert_eq!(shell_line(&command(&["cp", "$HOME; rm -rf x", ""])), "cp '$HOME; rm -rf x' ''");
+        assert_eq!(shell_line(&command(&["cp", "it's.pdf"])), "cp 'it'\\''s.pdf'");
+    }
+
+    #[test]
+    fn test_equivalent_commands() {
+        let document = DocumentInfo::new(5, 60).unwrap();
+        let commands = equivalent_commands(&document).unwrap();
+        assert!(commands.contains("mutool merge -o pages.pdf input.pdf 5-60\n"));
+        assert!(commands.contains("pdfjam --landscape --signature 16 --outfile signatures.pdf pages.pdf\n"));
+        assert!(commands.contains("pdfbook2 --signature=16 --no-crop pages.pdf\n"));
+        assert!(commands.contains("psbook -s16 pages.ps signatures.ps\n"));
+
+        let document = DocumentInfo::with_signature_size(1, 60, 24).unwrap();
+        let commands = equivalent_commands(&document).unwrap();
+        assert!(commands.contains("pdfjam --landscape --signature 24 "));
+    }
+
+    fn refusal(document: &DocumentInfo) -> String {
+        match equivalent_commands(document) {
+            Ok(_) => panic!("Should have refused a plan the tools can't impose!"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_equivalent_commands_refused() {
+        let mut document = DocumentInfo::with_signature_size(1, 32, 16).unwrap();
+        document.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        assert!(refusal(&document).contains("as its sheets are folded in octavo."));
+
+        let mut document = DocumentInfo::new(1, 32).unwrap();
+        document.set_one_sided(true);
+        document.set_reading_direction(ReadingDirection::RightToLeft);
+        assert!(refusal(&document).contains("as it's printed on one side only, and it reads right to left."));
+
+        // 18 pages balanced as 12 and 6 rather than 16 and 2
+        let options = crate::DocumentOptions {
+            pages_per_signature: 16,
+            balance: crate::BalanceStrategy::Even,
+            fold_scheme: FoldScheme::Folio,
+        };
+        let document = DocumentInfo::new_with_options(1, 18, &options).unwrap();
+        assert!(refusal(&document).contains("its signatures aren't all 16 pages but the last"));
+
+        // a collation of A8 B4 C8
+        let document = DocumentInfo::with_gatherings(1, &[8, 4, 8]).unwrap();
+        assert!(refusal(&document).contains("its signatures aren't all 8 pages but the last"));
+        // but A8 B8 C4 is just signatures of 8
+        let document = DocumentInfo::with_gatherings(1, &[8, 8, 4]).unwrap();
+        assert!(equivalent_commands(&document).is_ok());
+    }
+}
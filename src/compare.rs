@@ -0,0 +1,116 @@
+use std::error::Error;
+
+use crate::{DocumentInfo, DOC_PAGES_PER_SHEET};
+
+
+// Data structs
+#[derive(Debug)]
+pub struct Comparison {
+    first_page: u32,
+    last_page: u32,
+    candidates: Vec<DocumentInfo>,
+    zero_indexed: bool,
+}
+
+impl Comparison {
+    pub fn new(first_number: u32, second_number: u32, sizes: &[u32]) -> Result<Comparison, Box<dyn Error>> {
+        // Work out the document for each candidate signature size.
+        let candidates = sizes
+            .iter()
+            .map(|&size| DocumentInfo::with_signature_size(first_number, second_number, size))
+            .collect::<Result<_, _>>()?;
+        Ok(Comparison {
+            first_page: first_number,
+            last_page: second_number,
+            candidates,
+            zero_indexed: false,
+        })
+    }
+
+    pub fn set_zero_indexed(&mut self, zero_indexed: bool) {
+        // report the page range counting from 0
+        self.zero_indexed = zero_indexed;
+    }
+
+    pub fn display(&self) {
+        let offset = if self.zero_indexed { 1 } else { 0 };
+        println!(
+            "Comparing signature sizes for pages {} to {}",
+            self.first_page - offset,
+            self.last_page - offset,
+        );
+        println!("#####################################");
+        self.display_row("Pages per signature", |document| document.pages_per_signature());
+        self.display_row("Sheets per signature", |document| {
+            document.pages_per_signature() / DOC_PAGES_PER_SHEET
+        });
+        self.display_row("Signatures to bind", |document| document.num_signatures());
+        self.display_row("Pages in last signature", last_signature_pages);
+        self.display_row("Sheets to print", |document| document.num_sheets());
+        self.display_row("Blank pages", |document| document.num_blank_pages());
+        self.display_row("Sheets if last signature full", |document| {
+            document.num_signatures() * document.pages_per_signature() / DOC_PAGES_PER_SHEET
+        });
+        self.display_row("Blank pages if last signature full", |document| {
+            document.num_signatures() * document.pages_per_signature() - document.num_pages()
+        });
+        println!("#####################################");
+    }
+
+    fn display_row<F: Fn(&DocumentInfo) -> u32>(&self, label: &str, value: F) {
+        // one line of the report, with a column per candidate size
+        let mut row = format!("{:<36}", label);
+        for document in &self.candidates {
+            row.push_str(&format!("{:>6}", value(document)));
+        }
+        println!("{}", row);
+    }
+}
+
+fn last_signature_pages(document: &DocumentInfo) -> u32 {
+    match document.signatures().last() {
+        Some(signature) => signature.last_page() - signature.first_page() + 1,
+        None => 0,
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comparison_new() {
+        let comparison = Comparison::new(1, 60, &[16, 20, 24]).unwrap();
+        assert_eq!(comparison.candidates.len(), 3);
+
+        let document = &comparison.candidates[0];
+        assert_eq!(document.num_signatures(), 4);
+        assert_eq!(document.num_sheets(), 15);
+        assert_eq!(last_signature_pages(document), 12);
+
+        let document = &comparison.candidates[1];
+        assert_eq!(document.num_signatures(), 3);
+        assert_eq!(document.num_sheets(), 15);
+        assert_eq!(last_signature_pages(document), 20);
+
+        let document = &comparison.candidates[2];
+        assert_eq!(document.num_signatures(), 3);
+        assert_eq!(document.num_sheets(), 15);
+        assert_eq!(last_signature_pages(document), 12);
+    }
+
+    #[test]
+    fn test_comparison_blank_pages() {
+        let comparison = Comparison::new(1, 18, &[8, 12]).unwrap();
+        let document = &comparison.candidates[0];
+        assert_eq!(document.num_signatures(), 3);
+        assert_eq!(document.num_blank_pages(), 2);
+        assert_eq!(last_signature_pages(document), 2);
+
+        let document = &comparison.candidates[1];
+        assert_eq!(document.num_signatures(), 2);
+        assert_eq!(document.num_blank_pages(), 2);
+        assert_eq!(last_signature_pages(document), 6);
+    }
+}
@@ -0,0 +1,132 @@
+// A record of the gatherings for conservation documentation: the leaves
+// in each and their folio references, with empty columns for the sewing
+// structure and the condition and treatment of each, to be filled in by
+// hand, as CSV for a spreadsheet or HTML to print for the treatment file.
+
+use crate::{csv_line, DocumentInfo, Signature, DOC_PAGES_PER_SHEET};
+
+// Constants
+const COLUMNS: [&str; 8] = [
+    "Gathering",
+    "Leaves",
+    "Folios",
+    "Pages",
+    "Sewing stations",
+    "Sewing structure",
+    "Condition",
+    "Treatment",
+];
+// the columns filled in, after which the rest are left empty
+const FILLED_COLUMNS: usize = 4;
+
+
+// Work
+pub fn conservation_rows(document: &DocumentInfo) -> Vec<Vec<String>> {
+    // the filled in columns for each gathering, e.g.
+    // ["A", "8", "1r–8v", "1–16"]
+    document
+        .signatures()
+        .iter()
+        .map(|signature| {
+            let num_pages = signature.last_page() - signature.first_page() + 1;
+            vec![
+                document.label_scheme().display_key(signature.key()),
+                (num_pages.div_ceil(DOC_PAGES_PER_SHEET) * 2).to_string(),
+                folios(document, signature),
+                format!(
+                    "{}\u{2013}{}",
+                    document.shown_page(signature.first_page()),
+                    document.shown_page(signature.last_page()),
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn folios(document: &DocumentInfo, signature: &Signature) -> String {
+    // leaves counted from the first page of the document, each a recto then
+    // a verso
+    let folio = |page: u32| {
+        let side = if (page - document.first_page()).is_multiple_of(2) { "r" } else { "v" };
+        format!("{}{}", (page - document.first_page()) / 2 + 1, side)
+    };
+    format!("{}\u{2013}{}", folio(signature.first_page()), folio(signature.last_page()))
+}
+
+pub fn conservation_csv(document: &DocumentInfo) -> String {
+    let mut csv = csv_line(&COLUMNS);
+    for row in conservation_rows(document) {
+        let mut row: Vec<&str> = row.iter().map(|field| field.as_str()).collect();
+        row.resize(COLUMNS.len(), "");
+        csv.push_str(&csv_line(&row));
+    }
+    csv
+}
+
+pub fn conservation_html(document: &DocumentInfo) -> String {
+    let title = document.job().title.as_deref().unwrap_or("Gatherings");
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(title)));
+    html.push_str("<style>table { border-collapse: collapse; } th, td { border: 1px solid black; padding: 4px; }\n");
+    html.push_str("td.notes { min-width: 12em; }</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+    for (name, value) in document.job().fields() {
+        html.push_str(&format!("<p>{}: {}</p>\n", name, escape(value)));
+    }
+    html.push_str(&format!("<p>Collation: {}</p>\n", escape(&document.collation_formula())));
+    html.push_str("<table>\n<tr>");
+    for column in COLUMNS.iter() {
+        html.push_str(&format!("<th>{}</th>", column));
+    }
+    html.push_str("</tr>\n");
+    for row in conservation_rows(document) {
+        html.push_str("<tr>");
+        for field in &row {
+            html.push_str(&format!("<td>{}</td>", escape(field)));
+        }
+        for _ in FILLED_COLUMNS..COLUMNS.len() {
+            html.push_str("<td class=\"notes\"></td>");
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobInfo;
+
+    #[test]
+    fn test_conservation_csv() {
+        let document = DocumentInfo::new(1, 40).unwrap();
+        assert_eq!(
+            conservation_csv(&document),
+            "Gathering,Leaves,Folios,Pages,Sewing stations,Sewing structure,Condition,Treatment\r\n\
+             A,8,1r\u{2013}8v,1\u{2013}16,,,,\r\n\
+             B,8,9r\u{2013}16v,17\u{2013}32,,,,\r\n\
+             C,4,17r\u{2013}20v,33\u{2013}40,,,,\r\n",
+        );
+        assert_eq!(csv_line(&["a, b", "say \"hi\""]), "\"a, b\",\"say \"\"hi\"\"\"\r\n");
+    }
+
+    #[test]
+    fn test_conservation_html() {
+        let mut document = DocumentInfo::new(1, 20).unwrap();
+        document.set_job(JobInfo {title: Some("Psalter <MS 12>".to_string()), ..JobInfo::default()});
+        let html = conservation_html(&document);
+        assert!(html.contains("<h1>Psalter &lt;MS 12&gt;</h1>\n"));
+        assert!(html.contains("<p>Collation: A\u{2078} B\u{b2}</p>\n"));
+        assert!(html.contains(
+            "<tr><td>B</td><td>2</td><td>9r\u{2013}10v</td><td>17\u{2013}20</td>\
+             <td class=\"notes\"></td><td class=\"notes\"></td><td class=\"notes\"></td><td class=\"notes\"></td></tr>\n"
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+// Contact sheets for collating by eye: one PDF per signature with every one
+// of its pages shrunk to a thumbnail, in reading order with its page number
+// underneath, to hold against a folded gathering and check each page turns
+// up where it should. Drawing the thumbnails needs a build with the pdf
+// feature.
+
+use std::error::Error;
+#[cfg(not(feature = "pdf"))]
+use std::fmt;
+
+use crate::imposition::PageSize;
+use crate::DocumentInfo;
+
+// Constants
+const CONTACT_SUFFIX: &str = "-contact.pdf";
+// portrait A4, in millimetres
+pub const CONTACT_SHEET_SIZE: PageSize = PageSize {width: 210.0, height: 297.0};
+// a cell with no page of the input PDF on it
+pub const BLANK: u32 = 0;
+
+
+// Custom errors
+#[cfg(not(feature = "pdf"))]
+#[derive(Debug)]
+pub(crate) struct ContactSheetPdfError;
+
+#[cfg(not(feature = "pdf"))]
+impl Error for ContactSheetPdfError {}
+
+#[cfg(not(feature = "pdf"))]
+impl fmt::Display for ContactSheetPdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Drawing contact sheets needs a build with --features pdf.")
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactCell {
+    // the input PDF's page, counted from 1, or BLANK
+    pub page: u32,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactSheet {
+    pub path: String,
+    pub title: String,
+    pub cells: Vec<ContactCell>,
+}
+
+impl ContactSheet {
+    pub fn columns(&self) -> u32 {
+        // as near square a grid as holds every cell
+        let num_cells = self.cells.len() as u32;
+        (1..).find(|columns| columns * columns >= num_cells).unwrap_or(1)
+    }
+
+    pub fn rows(&self) -> u32 {
+        (self.cells.len() as u32).div_ceil(self.columns()).max(1)
+    }
+}
+
+
+// Work
+pub fn contact_path(input: &str, signature_key: &str) -> String {
+    // book.pdf gives book-A-contact.pdf and so on beside it
+    format!("{}-{}{}", crate::split::input_stem(input), signature_key, CONTACT_SUFFIX)
+}
+
+pub fn contact_sheets(document: &DocumentInfo, input: &str) -> Vec<ContactSheet> {
+    // The input PDF's pages start at 1 whatever the document's first page
+    // is. The last signature's blanks are shown too, so the gathering can
+    // be checked leaf for leaf.
+    document
+        .signatures()
+        .iter()
+        .map(|signature| {
+            let mut cells: Vec<ContactCell> = signature
+                .pages()
+                .map(|page| ContactCell {
+                    page: page - document.first_page() + 1,
+                    label: document.shown_page(page).to_string(),
+                })
+                .collect();
+            let num_blanks = document.pages_per_signature().saturating_sub(cells.len() as u32);
+            cells.extend((0..num_blanks).map(|_| ContactCell {page: BLANK, label: "blank".to_string()}));
+            ContactSheet {
+                path: contact_path(input, signature.key()),
+                title: format!(
+                    "Signature {}: pages {}-{}",
+                    signature.key(),
+                    document.shown_page(signature.first_page()),
+                    document.shown_page(signature.last_page()),
+                ),
+                cells,
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "pdf")]
+pub fn write_contact_sheets(input: &str, sheets: &[ContactSheet]) -> Result<(), Box<dyn Error>> {
+    for sheet in sheets {
+        crate::pdf::contact_sheet(input, sheet, CONTACT_SHEET_SIZE)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn write_contact_sheets(_input: &str, _sheets: &[ContactSheet]) -> Result<(), Box<dyn Error>> {
+    Err(ContactSheetPdfError.into())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_sheets() {
+        // pages 5 to 26: a full signature, then 6 pages and 10 blanks
+        let document = DocumentInfo::new(5, 26).unwrap();
+        let sheets = contact_sheets(&document, "books/angler.pdf");
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].path, "books/angler-A-contact.pdf");
+        assert_eq!(sheets[0].title, "Signature A: pages 5-20");
+        assert_eq!(sheets[0].cells[0], ContactCell {page: 1, label: "5".to_string()});
+        assert_eq!((sheets[0].columns(), sheets[0].rows()), (4, 4));
+        assert_eq!(sheets[1].cells.len(), 16);
+        assert_eq!(sheets[1].cells[5], ContactCell {page: 22, label: "26".to_string()});
+        assert_eq!(sheets[1].cells[6], ContactCell {page: BLANK, label: "blank".to_string()});
+    }
+
+    #[test]
+    fn test_contact_sheet_grid() {
+        let sheet = |num_cells: u32| ContactSheet {
+            path: String::new(),
+            title: String::new(),
+            cells: (1..=num_cells).map(|page| ContactCell {page, label: page.to_string()}).collect(),
+        };
+        assert_eq!((sheet(8).columns(), sheet(8).rows()), (3, 3));
+        assert_eq!((sheet(24).columns(), sheet(24).rows()), (5, 5));
+        assert_eq!((sheet(32).columns(), sheet(32).rows()), (6, 6));
+        assert_eq!((sheet(1).columns(), sheet(1).rows()), (1, 1));
+    }
+
+    #[cfg(not(feature = "pdf"))]
+    #[test]
+    fn test_write_contact_sheets_not_built() {
+        let document = DocumentInfo::new(1, 16).unwrap();
+        match write_contact_sheets("book.pdf", &contact_sheets(&document, "book.pdf")) {
+            Ok(_) => panic!("Should have errored because pdf isn't built in!"),
+            Err(result_error) => {
+                assert_eq!(format!("{}", result_error), "Drawing contact sheets needs a build with --features pdf.")
+            },
+        }
+    }
+}
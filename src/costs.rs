@@ -0,0 +1,320 @@
+// Costing a run from a price list: the paper for each stock, and the
+// materials each bound copy needs, like covers, thread, glue and boards.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::press::{StockPull, StockRun};
+
+// Constants
+// prices are kept in ten-thousandths of the currency unit, so sheet prices
+// like 0.0125 add up exactly
+const PRICE_SCALE: u64 = 10_000;
+const PRICE_DECIMALS: usize = 4;
+// code, symbol and decimal places of the currencies we know how to show
+const CURRENCIES: [(&str, &str, u32); 6] = [
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("USD", "$", 2),
+    ("CHF", "CHF ", 2),
+    ("SEK", "kr ", 2),
+    ("JPY", "¥", 0),
+];
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct CostLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for CostLineError {}
+
+impl fmt::Display for CostLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the costs file! Expected \"currency: code\", \"sheet: price\", \"sheet stock: price\" or \"material: price\", got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Currency {
+    pub code: String,
+    pub symbol: String,
+    pub decimals: u32,
+}
+
+impl Currency {
+    pub fn from_code(code: &str) -> Currency {
+        // a currency we don't know is shown by its code, to 2 places
+        let code = code.to_uppercase();
+        match CURRENCIES.iter().find(|(known, _, _)| *known == code) {
+            Some((_, symbol, decimals)) => Currency {code, symbol: symbol.to_string(), decimals: *decimals},
+            None => Currency {symbol: format!("{} ", code), code, decimals: 2},
+        }
+    }
+
+    pub fn format(&self, price: u64) -> String {
+        format!("{}{}", self.symbol, round_price(price, self.decimals))
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Costs {
+    pub currency: Option<Currency>,
+    // the price of a sheet of any stock without its own price
+    sheet: Option<u64>,
+    sheets: Vec<(String, u64)>,
+    // what printing a sheet side in colour costs on top of its paper
+    colour_side: Option<u64>,
+    // the materials for one bound copy, e.g. ("thread", 500)
+    per_copy: Vec<(String, u64)>,
+}
+
+impl Costs {
+    pub fn from_file(path: &str) -> Result<Costs, Box<dyn Error>> {
+        Costs::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Costs, Box<dyn Error>> {
+        // Read the price list, one entry per line:
+        //   currency: GBP
+        //   sheet: 0.04
+        //   sheet 150gsm Gloss Art, SRA3: 0.11
+        //   colour side: 0.08
+        //   cover: 1.20
+        //   thread: 0.05
+        // Stocks are named as in the stocks file. Blank lines and lines
+        // starting with # are ignored.
+        let mut costs = Costs::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_error = || CostLineError {line_number: i + 1, line: line.to_string()};
+            if let Some(code) = line.strip_prefix("currency:") {
+                match code.trim() {
+                    "" => return Err(line_error().into()),
+                    code => costs.currency = Some(Currency::from_code(code)),
+                }
+                continue;
+            }
+            // stock names can have colons in, so split at the last one
+            let (item, price) = match line.rfind(':') {
+                Some(colon) => (line[..colon].trim(), parse_price(line[colon + 1..].trim())),
+                None => return Err(line_error().into()),
+            };
+            let price = match price {
+                Some(price) if !item.is_empty() => price,
+                _ => return Err(line_error().into()),
+            };
+            if item == "sheet" {
+                costs.sheet = Some(price);
+            } else if item == "colour side" {
+                costs.colour_side = Some(price);
+            } else if let Some(stock) = item.strip_prefix("sheet ") {
+                costs.sheets.push((stock.trim().to_string(), price));
+            } else {
+                costs.per_copy.push((item.to_string(), price));
+            }
+        }
+        Ok(costs)
+    }
+
+    fn sheet_price(&self, stock: &str) -> Option<u64> {
+        self.sheets
+            .iter()
+            .rev() // later entries override earlier ones
+            .find(|(known, _)| known == stock)
+            .map(|(_, price)| *price)
+            .or(self.sheet)
+    }
+
+    pub fn format_price(&self, price: u64) -> String {
+        match &self.currency {
+            Some(currency) => currency.format(price),
+            None => format_price(price),
+        }
+    }
+}
+
+// one line of the cost report, with None for a stock that has no price
+#[derive(Debug, PartialEq)]
+pub struct CostLine {
+    pub item: String,
+    pub per_copy: Option<u64>,
+    pub per_run: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CostReport {
+    pub copies: u32,
+    pub lines: Vec<CostLine>,
+}
+
+impl CostReport {
+    pub fn per_copy(&self) -> u64 {
+        self.lines.iter().filter_map(|line| line.per_copy).fold(0, u64::saturating_add)
+    }
+
+    pub fn per_run(&self) -> u64 {
+        self.lines.iter().filter_map(|line| line.per_run).fold(0, u64::saturating_add)
+    }
+}
+
+
+// Work
+pub fn cost_report(runs: &[StockRun], pulls: &[StockPull], costs: &Costs, copies: u32) -> CostReport {
+    // The paper for one copy is just its sheets, but the run pays for the
+    // spoilage allowance too, so the per-run paper comes from the sheets
+    // pulled from inventory.
+    let mut lines = Vec::new();
+    for (run, pull) in runs.iter().zip(pulls) {
+        let price = costs.sheet_price(&run.stock);
+        lines.push(CostLine {
+            item: format!("Paper, {}", run.stock),
+            per_copy: price.map(|price| price.saturating_mul(u64::from(run.num_sheets()))),
+            per_run: price.map(|price| price.saturating_mul(pull.sheets)),
+        });
+    }
+    for (item, price) in &costs.per_copy {
+        lines.push(CostLine {
+            item: item.clone(),
+            per_copy: Some(*price),
+            per_run: Some(price.saturating_mul(u64::from(copies))),
+        });
+    }
+    CostReport {copies, lines}
+}
+
+pub fn colour_cost_line(costs: &Costs, colour_sides: u32, copies: u32) -> Option<CostLine> {
+    // the colour sheet sides of one copy, if colour has a price
+    let price = costs.colour_side?.saturating_mul(u64::from(colour_sides));
+    Some(CostLine {
+        item: "Colour printing".to_string(),
+        per_copy: Some(price),
+        per_run: Some(price.saturating_mul(u64::from(copies))),
+    })
+}
+
+pub fn display_cost_report(report: &CostReport, costs: &Costs) {
+    let amount = |price: Option<u64>| price.map_or("no price".to_string(), |price| costs.format_price(price));
+    println!("Costs, per copy and for {} {}:", report.copies, if report.copies == 1 { "copy" } else { "copies" });
+    for line in &report.lines {
+        println!("{}: {} per copy, {} per run", line.item, amount(line.per_copy), amount(line.per_run));
+    }
+    println!("Total: {} per copy, {} per run", amount(Some(report.per_copy())), amount(Some(report.per_run())));
+    println!("#####################################");
+}
+
+pub fn format_price(price: u64) -> String {
+    // to the nearest hundredth, e.g. 12345 is 1.23
+    round_price(price, 2)
+}
+
+fn round_price(price: u64, decimals: u32) -> String {
+    let unit = PRICE_SCALE / 10u64.pow(decimals);
+    let rounded = price.saturating_add(unit / 2) / unit;
+    match decimals {
+        0 => rounded.to_string(),
+        _ => {
+            let places = 10u64.pow(decimals);
+            format!("{}.{:0width$}", rounded / places, rounded % places, width = decimals as usize)
+        },
+    }
+}
+
+fn parse_price(value: &str) -> Option<u64> {
+    // "1.20" or "0.0125", in ten-thousandths
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > PRICE_DECIMALS || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let whole: u64 = whole.parse().ok()?;
+    let fraction: u64 = format!("{:0<width$}", fraction, width = PRICE_DECIMALS).parse().ok()?;
+    whole.checked_mul(PRICE_SCALE)?.checked_add(fraction)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::press::{self, Stocks};
+    use crate::DocumentInfo;
+
+    #[test]
+    fn test_costs_parse() {
+        let costs = Costs::parse("# shop prices\nsheet: 0.04\nsheet gloss, SRA3: 0.11\n\ncover: 1.20\nthread: 0.05\n").unwrap();
+        assert_eq!(costs.sheet_price("cream"), Some(400));
+        assert_eq!(costs.sheet_price("gloss, SRA3"), Some(1100));
+        assert_eq!(costs.per_copy, vec![("cover".to_string(), 12000), ("thread".to_string(), 500)]);
+        assert_eq!(Costs::default().sheet_price("cream"), None);
+
+        match Costs::parse("cover 1.20\n") {
+            Ok(_) => panic!("Costs::parse should have rejected a line without a colon"),
+            Err(result_error) => assert!(
+                format!("{}", result_error).starts_with("Couldn't read line 1 of the costs file!"),
+            ),
+        }
+        assert!(Costs::parse("cover: £1.20\n").is_err());
+        assert!(Costs::parse("cover: 1.00001\n").is_err());
+        assert!(Costs::parse(": 1.20\n").is_err());
+    }
+
+    #[test]
+    fn test_cost_report() {
+        // 10 sheets of cream and 8 of gloss a copy
+        let document = DocumentInfo::new(1, 72).unwrap();
+        let runs = press::run_order(&document, &Stocks::parse("default: cream\nB, D: gloss\n").unwrap());
+        let pulls = press::inventory(&runs, 100, 500);
+        let costs = Costs::parse("sheet cream: 0.04\ncover: 1.20\n").unwrap();
+        let report = cost_report(&runs, &pulls, &costs, 100);
+        assert_eq!(
+            report.lines,
+            vec![
+                CostLine {item: "Paper, cream".to_string(), per_copy: Some(4000), per_run: Some(420000)},
+                CostLine {item: "Paper, gloss".to_string(), per_copy: None, per_run: None},
+                CostLine {item: "cover".to_string(), per_copy: Some(12000), per_run: Some(1200000)},
+            ],
+        );
+        assert_eq!(report.per_copy(), 16000);
+        assert_eq!(report.per_run(), 1620000);
+        assert_eq!(colour_cost_line(&costs, 4, 100), None);
+        let costs = Costs::parse("colour side: 0.08\n").unwrap();
+        assert_eq!(
+            colour_cost_line(&costs, 4, 100),
+            Some(CostLine {item: "Colour printing".to_string(), per_copy: Some(3200), per_run: Some(320000)}),
+        );
+    }
+
+    #[test]
+    fn test_format_price() {
+        assert_eq!(format_price(12000), "1.20");
+        assert_eq!(format_price(12345), "1.23");
+        assert_eq!(format_price(12350), "1.24");
+        assert_eq!(format_price(0), "0.00");
+    }
+
+    #[test]
+    fn test_currency() {
+        let costs = Costs::parse("currency: gbp
+cover: 1.20
+").unwrap();
+        assert_eq!(costs.format_price(12345), "£1.23");
+        assert_eq!(Currency::from_code("EUR").format(5), "€0.00");
+        assert_eq!(Currency::from_code("JPY").format(12_345_678), "¥1235");
+        assert_eq!(Currency::from_code("NOK").format(12000), "NOK 1.20");
+        assert_eq!(Costs::default().format_price(12000), "1.20");
+        assert!(Costs::parse("currency:
+").is_err());
+    }
+}
@@ -0,0 +1,344 @@
+// Cutting the press sheets for a job out of bigger parent sheets, as the
+// paper comes from the merchant, fitting in as many as possible.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::imposition::PageSize;
+use crate::press::StockPull;
+
+// Constants
+// the ISO sizes paper usually comes in, in millimetres
+const NAMED_SIZES: [(&str, f64, f64); 9] = [
+    ("A0", 841.0, 1189.0),
+    ("A1", 594.0, 841.0),
+    ("A2", 420.0, 594.0),
+    ("A3", 297.0, 420.0),
+    ("SRA1", 640.0, 900.0),
+    ("SRA2", 450.0, 640.0),
+    ("SRA3", 320.0, 450.0),
+    ("B1", 707.0, 1000.0),
+    ("B2", 500.0, 707.0),
+];
+// room around the parent sheet in the diagram for its dimensions, in
+// millimetres
+const DIAGRAM_MARGIN: f64 = 30.0;
+const DIAGRAM_FONT_SIZE: f64 = 12.0;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct SheetTooBigError {
+    parent: PageSize,
+    sheet: PageSize,
+}
+
+impl Error for SheetTooBigError {}
+
+impl fmt::Display for SheetTooBigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A {}x{}mm sheet can't be cut from a {}x{}mm parent sheet, either way round.",
+            self.sheet.width,
+            self.sheet.height,
+            self.parent.width,
+            self.parent.height,
+        )
+    }
+}
+
+
+// Data structs
+// which way the fibres run in the parent sheet
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Grain {
+    // along the longer side, as most sheets are made
+    #[default]
+    Long,
+    Short,
+}
+
+impl Grain {
+    fn runs_across(&self, parent: PageSize) -> bool {
+        (parent.width >= parent.height) == (*self == Grain::Long)
+    }
+}
+
+// one sheet cut from the parent, from its top left corner in millimetres;
+// rotated sheets lie the other way round to the parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Piece {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub rotated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuttingPlan {
+    pub parent: PageSize,
+    pub sheet: PageSize,
+    pub pieces: Vec<Piece>,
+}
+
+impl CuttingPlan {
+    pub fn sheets_per_parent(&self) -> u32 {
+        self.pieces.len() as u32
+    }
+
+    pub fn waste(&self) -> f64 {
+        // the share of the parent sheet's area that's cut away
+        let used = self.pieces.len() as f64 * self.sheet.width * self.sheet.height;
+        1.0 - used / (self.parent.width * self.parent.height)
+    }
+
+    pub fn parent_sheets(&self, sheets: u64) -> u64 {
+        sheets.div_ceil(u64::from(self.sheets_per_parent()))
+    }
+}
+
+
+// Work
+pub fn parse_parent_size(value: &str) -> Result<PageSize, Box<dyn Error>> {
+    // a name like A1 or SRA2, or WIDTHxHEIGHT in millimetres
+    match NAMED_SIZES.iter().find(|(name, _, _)| name.eq_ignore_ascii_case(value)) {
+        Some((_, width, height)) => Ok(PageSize {width: *width, height: *height}),
+        None => PageSize::parse(value),
+    }
+}
+
+pub fn cutting_plan(parent: PageSize, sheet: PageSize) -> Result<CuttingPlan, Box<dyn Error>> {
+    // Try every guillotine cut that first splits the parent into two blocks,
+    // across or down, each filled with sheets one way round, and keep the
+    // one that fits the most. Ties go to the fewest rotated sheets.
+    let mut best: Vec<Piece> = Vec::new();
+    for &rotated in [false, true].iter() {
+        let (width, height) = if rotated { (sheet.height, sheet.width) } else { (sheet.width, sheet.height) };
+        let (other_width, other_height) = (height, width);
+        let max_columns = (parent.width / width).floor() as u32;
+        for columns in 0..=max_columns {
+            // a block of columns on the left, the other way round to the right
+            let split = f64::from(columns) * width;
+            let mut pieces = grid(0.0, 0.0, split, parent.height, width, height, rotated);
+            pieces.extend(grid(split, 0.0, parent.width - split, parent.height, other_width, other_height, !rotated));
+            best = better(best, pieces);
+        }
+        let max_rows = (parent.height / height).floor() as u32;
+        for rows in 0..=max_rows {
+            // a block of rows at the top, the other way round below
+            let split = f64::from(rows) * height;
+            let mut pieces = grid(0.0, 0.0, parent.width, split, width, height, rotated);
+            pieces.extend(grid(0.0, split, parent.width, parent.height - split, other_width, other_height, !rotated));
+            best = better(best, pieces);
+        }
+    }
+    if best.is_empty() {
+        return Err((SheetTooBigError {parent, sheet}).into());
+    }
+    Ok(CuttingPlan {parent, sheet, pieces: best})
+}
+
+pub fn describe(plan: &CuttingPlan) -> String {
+    let rotated = plan.pieces.iter().filter(|piece| piece.rotated).count();
+    let mut description = format!(
+        "{} sheets of {}x{}mm from each {}x{}mm parent sheet",
+        plan.sheets_per_parent(),
+        plan.sheet.width,
+        plan.sheet.height,
+        plan.parent.width,
+        plan.parent.height,
+    );
+    if rotated > 0 {
+        description.push_str(&format!(", {} of them turned", rotated));
+    }
+    description.push_str(&format!(", wasting {:.1}%", plan.waste() * 100.0));
+    description
+}
+
+pub fn display_cutting_plan(plan: &CuttingPlan, pulls: &[StockPull], strips: u32) {
+    // the pulls count the plan's sheets, several of which may be cut from
+    // each press sheet
+    println!("Cutting: {}.", describe(plan));
+    for pull in pulls {
+        let press_sheets = pull.sheets.div_ceil(u64::from(strips.max(1)));
+        println!("{}: {} parent sheets for {} press sheets", pull.stock, plan.parent_sheets(press_sheets), press_sheets);
+    }
+    for piece in &plan.pieces {
+        println!(
+            "  {}x{}mm at {}, {}{}",
+            piece.width,
+            piece.height,
+            piece.x,
+            piece.y,
+            if piece.rotated { ", turned" } else { "" },
+        );
+    }
+    println!("#####################################");
+}
+
+pub fn cutting_svg(plan: &CuttingPlan, grain: Grain) -> String {
+    // Drawn to scale, a millimetre to a unit, on a grey parent sheet so
+    // whatever is cut away as waste stands out. Every piece is outlined along
+    // its cuts, labelled with its size, and has an arrow along the grain.
+    let parent = plan.parent;
+    let width = parent.width + 2.0 * DIAGRAM_MARGIN;
+    let height = parent.height + 3.0 * DIAGRAM_MARGIN;
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"{}\">\n",
+        DIAGRAM_FONT_SIZE,
+        w = width,
+        h = height,
+    ));
+    svg.push_str("  <defs><marker id=\"arrow\" markerWidth=\"8\" markerHeight=\"8\" refX=\"8\" refY=\"4\" orient=\"auto\" markerUnits=\"userSpaceOnUse\"><path d=\"M0,0 L8,4 L0,8 z\"/></marker></defs>\n");
+    svg.push_str(&format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"lightgrey\" stroke=\"black\"/>\n",
+        DIAGRAM_MARGIN,
+        DIAGRAM_MARGIN,
+        parent.width,
+        parent.height,
+    ));
+    // the parent's dimensions along the top and down the left
+    svg.push_str(&format!(
+        "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}mm</text>\n",
+        DIAGRAM_MARGIN + parent.width / 2.0,
+        DIAGRAM_MARGIN / 2.0,
+        parent.width,
+    ));
+    let (left_x, left_y) = (DIAGRAM_MARGIN / 2.0, DIAGRAM_MARGIN + parent.height / 2.0);
+    svg.push_str(&format!(
+        "  <text x=\"{x}\" y=\"{y}\" text-anchor=\"middle\" transform=\"rotate(-90 {x} {y})\">{}mm</text>\n",
+        parent.height,
+        x = left_x,
+        y = left_y,
+    ));
+    let across = grain.runs_across(parent);
+    for piece in &plan.pieces {
+        let (x, y) = (DIAGRAM_MARGIN + piece.x, DIAGRAM_MARGIN + piece.y);
+        let (centre_x, centre_y) = (x + piece.width / 2.0, y + piece.height / 2.0);
+        svg.push_str(&format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"black\" stroke-width=\"2\"/>\n",
+            x,
+            y,
+            piece.width,
+            piece.height,
+        ));
+        svg.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{} x {}mm</text>\n",
+            centre_x,
+            y + 2.0 * DIAGRAM_FONT_SIZE,
+            piece.width,
+            piece.height,
+        ));
+        let (x1, y1, x2, y2) = if across {
+            (centre_x - piece.width / 4.0, centre_y, centre_x + piece.width / 4.0, centre_y)
+        } else {
+            (centre_x, centre_y - piece.height / 4.0, centre_x, centre_y + piece.height / 4.0)
+        };
+        svg.push_str(&format!(
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"blue\" marker-end=\"url(#arrow)\"/>\n",
+            x1,
+            y1,
+            x2,
+            y2,
+        ));
+    }
+    svg.push_str(&format!(
+        "  <text x=\"{}\" y=\"{}\">{}. Arrows show the grain.</text>\n",
+        DIAGRAM_MARGIN,
+        height - DIAGRAM_MARGIN,
+        describe(plan),
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn grid(x: f64, y: f64, width: f64, height: f64, piece_width: f64, piece_height: f64, rotated: bool) -> Vec<Piece> {
+    let columns = (width / piece_width).floor() as u32;
+    let rows = (height / piece_height).floor() as u32;
+    let mut pieces = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            pieces.push(Piece {
+                x: x + f64::from(column) * piece_width,
+                y: y + f64::from(row) * piece_height,
+                width: piece_width,
+                height: piece_height,
+                rotated,
+            });
+        }
+    }
+    pieces
+}
+
+fn better(best: Vec<Piece>, pieces: Vec<Piece>) -> Vec<Piece> {
+    let turned = |pieces: &[Piece]| pieces.iter().filter(|piece| piece.rotated).count();
+    if pieces.len() > best.len() || (pieces.len() == best.len() && turned(&pieces) < turned(&best)) {
+        pieces
+    } else {
+        best
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_parent_size() {
+        assert_eq!(parse_parent_size("A1").unwrap(), PageSize {width: 594.0, height: 841.0});
+        assert_eq!(parse_parent_size("sra2").unwrap(), PageSize {width: 450.0, height: 640.0});
+        assert_eq!(parse_parent_size("700x1000").unwrap(), PageSize {width: 700.0, height: 1000.0});
+        assert!(parse_parent_size("A9").is_err());
+    }
+
+    #[test]
+    fn test_cutting_svg() {
+        let plan = cutting_plan(parse_parent_size("A1").unwrap(), PageSize {width: 297.0, height: 420.0}).unwrap();
+        let svg = cutting_svg(&plan, Grain::Long);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"654mm\" height=\"931mm\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">594mm</text>"));
+        assert!(svg.contains("transform=\"rotate(-90 15 450.5)\">841mm</text>"));
+        assert_eq!(svg.matches(">297 x 420mm</text>").count(), 4);
+        // long grain runs down a portrait parent sheet
+        assert_eq!(svg.matches("marker-end=\"url(#arrow)\"").count(), 4);
+        assert!(svg.contains("<line x1=\"178.5\" y1=\"135\" x2=\"178.5\" y2=\"345\""));
+        assert!(svg.contains(">4 sheets of 297x420mm from each 594x841mm parent sheet, wasting 0.1%. Arrows"));
+    }
+
+    #[test]
+    fn test_cutting_plan() {
+        // A3 sheets fit an A1 parent 2 by 2, wasting only a 1mm strip
+        let plan = cutting_plan(parse_parent_size("A1").unwrap(), PageSize {width: 297.0, height: 420.0}).unwrap();
+        assert_eq!(plan.sheets_per_parent(), 4);
+        assert!(plan.waste() < 0.002);
+        assert!(plan.pieces.iter().all(|piece| !piece.rotated));
+        assert_eq!(plan.parent_sheets(10), 3);
+
+        // 200x300 from 700x1000: 3 by 3 one way or 2 by 5 the other, but 2
+        // columns one way leave room for a column of 5 the other way
+        let plan = cutting_plan(PageSize {width: 700.0, height: 1000.0}, PageSize {width: 200.0, height: 300.0}).unwrap();
+        assert_eq!(plan.sheets_per_parent(), 11);
+        assert_eq!(plan.pieces.iter().filter(|piece| piece.rotated).count(), 5);
+        assert_eq!(
+            describe(&plan),
+            "11 sheets of 200x300mm from each 700x1000mm parent sheet, 5 of them turned, wasting 5.7%",
+        );
+
+        assert!(Grain::Long.runs_across(PageSize {width: 1000.0, height: 700.0}));
+        assert!(!Grain::Long.runs_across(plan.parent));
+        assert!(Grain::Short.runs_across(plan.parent));
+
+        match cutting_plan(PageSize {width: 320.0, height: 450.0}, PageSize {width: 500.0, height: 300.0}) {
+            Ok(_) => panic!("Should have errored because the sheet is bigger than the parent!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "A 500x300mm sheet can't be cut from a 320x450mm parent sheet, either way round.",
+            ),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::process::Command;
+
+use crate::imposition::{self, TwoUpError};
+use crate::job::JobInfo;
+use crate::stamp::NumeralStyle;
+use crate::DocumentInfo;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct ExternalToolError {
+    pub(crate) command: String,
+    pub(crate) reason: String,
+}
+
+impl Error for ExternalToolError {}
+
+impl fmt::Display for ExternalToolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Running `{}` failed: {}", self.command, self.reason)
+    }
+}
+
+
+// Data structs
+// what a written PDF records about the plan it came from
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PdfMetadata {
+    pub job: JobInfo,
+    pub plan_checksum: String,
+    // how viewers number the pages, to match the book
+    pub page_labels: Vec<PageLabelRange>,
+}
+
+impl PdfMetadata {
+    pub fn for_plan(document: &DocumentInfo) -> PdfMetadata {
+        PdfMetadata {
+            job: document.job().clone(),
+            plan_checksum: document.checksum(),
+            page_labels: page_labels(document, 0),
+        }
+    }
+
+    pub fn creator(&self) -> String {
+        format!("rust-signatures {}", env!("CARGO_PKG_VERSION"))
+    }
+}
+
+// a run of the reading-order PDF's pages numbered the same way, from the
+// index of the PDF page it starts on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLabelRange {
+    pub start: u32,
+    pub numerals: NumeralStyle,
+    pub first_number: u32,
+}
+
+
+// Work
+pub fn page_labels(document: &DocumentInfo, front_matter: u32) -> Vec<PageLabelRange> {
+    // The first front_matter pages of the book are numbered in lower case
+    // roman numerals, and the numbering starts again at 1 after them. The
+    // PDF holds the document's pages, which may start partway through.
+    let mut ranges = Vec::new();
+    if document.first_page() <= front_matter {
+        ranges.push(PageLabelRange {start: 0, numerals: NumeralStyle::LowerRoman, first_number: document.first_page()});
+    }
+    if document.last_page() > front_matter {
+        let first_body_page = document.first_page().max(front_matter + 1);
+        ranges.push(PageLabelRange {
+            start: first_body_page - document.first_page(),
+            numerals: NumeralStyle::Arabic,
+            first_number: first_body_page - front_matter,
+        });
+    }
+    ranges
+}
+
+pub fn reading_order(document: &DocumentInfo) -> Result<Vec<u32>, TwoUpError> {
+    // Once every imposed sheet side has been cut into its left and right
+    // halves, find which half holds each page of the document, in reading
+    // order. The halves are numbered from 1, like PDF page selections.
+    let mut halves: Vec<(u32, u32)> = Vec::new();
+    for (i, side) in imposition::impose(document)?.iter().enumerate() {
+        let left_half = 2 * i as u32 + 1;
+        if let Some(page) = side.left {
+            halves.push((page, left_half));
+        }
+        if let Some(page) = side.right {
+            halves.push((page, left_half + 1));
+        }
+    }
+    halves.sort();
+    Ok(halves.into_iter().map(|(_, half)| half).collect())
+}
+
+pub fn deimpose_commands(document: &DocumentInfo, imposed: &str, output: &str) -> Result<Vec<Vec<String>>, TwoUpError> {
+    // the mutool invocations that turn the imposed PDF back into reading order
+    let halves = format!("{}.halves.pdf", output);
+    let order: Vec<String> = reading_order(document)?.iter().map(|half| half.to_string()).collect();
+    Ok(vec![
+        vec!["mutool".to_string(), "poster".to_string(), "-x".to_string(), "2".to_string(), imposed.to_string(), halves.clone()],
+        vec!["mutool".to_string(), "merge".to_string(), "-o".to_string(), output.to_string(), halves, order.join(",")],
+    ])
+}
+
+#[cfg(feature = "pdf")]
+pub fn deimpose(document: &DocumentInfo, imposed: &str, output: &str, metadata: &PdfMetadata) -> Result<(), Box<dyn Error>> {
+    crate::pdf::deimpose(document, imposed, output, metadata)
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn deimpose(document: &DocumentInfo, imposed: &str, output: &str, _metadata: &PdfMetadata) -> Result<(), Box<dyn Error>> {
+    // mutool has no way to set the metadata, so only native builds do
+    deimpose_with_tools(document, imposed, output)
+}
+
+pub fn deimpose_with_tools(document: &DocumentInfo, imposed: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    // Run the mutool commands, tidying up the intermediate file afterwards.
+    let commands = deimpose_commands(document, imposed, output)?;
+    for command in &commands {
+        run(command)?;
+    }
+    fs::remove_file(&commands[0][5])?;
+    Ok(())
+}
+
+pub(crate) fn run(command: &[String]) -> Result<(), Box<dyn Error>> {
+    let tool_error = |reason: String| ExternalToolError {command: command.join(" "), reason};
+    let status = match Command::new(&command[0]).args(&command[1..]).status() {
+        Ok(status) => status,
+        Err(err) => {
+            let reason = format!("{}. Is {} installed? Building with --features pdf removes the need for it.", err, command[0]);
+            return Err(tool_error(reason).into());
+        },
+    };
+    if !status.success() {
+        return Err(tool_error(status.to_string()).into());
+    }
+    Ok(())
+}
+
+pub(crate) fn read_output(command: &[String]) -> Result<String, Box<dyn Error>> {
+    // run a tool for what it prints, rather than a file it writes
+    let tool_error = |reason: String| ExternalToolError {command: command.join(" "), reason};
+    let output = match Command::new(&command[0]).args(&command[1..]).output() {
+        Ok(output) => output,
+        Err(err) => return Err(tool_error(format!("{}. Is {} installed?", err, command[0])).into()),
+    };
+    if !output.status.success() {
+        return Err(tool_error(output.status.to_string()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::shell_line;
+
+    #[test]
+    fn test_reading_order() {
+        // halves: 16 1 | 2 15 | 14 3 | 4 13 | 12 5 | 6 11 | 10 7 | 8 9
+        let document = DocumentInfo::new(1, 16).unwrap();
+        assert_eq!(
+            reading_order(&document).unwrap(),
+            vec![2, 3, 6, 7, 10, 11, 14, 15, 16, 13, 12, 9, 8, 5, 4, 1],
+        );
+
+        // blanks are skipped: - 1 | 2 - | 6 3 | 4 5
+        let document = DocumentInfo::new(1, 6).unwrap();
+        assert_eq!(reading_order(&document).unwrap(), vec![2, 3, 6, 7, 8, 5]);
+    }
+
+    #[test]
+    fn test_page_labels() {
+        // i-xii then 1-240
+        let document = DocumentInfo::new(1, 252).unwrap();
+        assert_eq!(
+            page_labels(&document, 12),
+            vec![
+                PageLabelRange {start: 0, numerals: NumeralStyle::LowerRoman, first_number: 1},
+                PageLabelRange {start: 12, numerals: NumeralStyle::Arabic, first_number: 1},
+            ],
+        );
+        // starting partway through the front matter, or after it
+        let document = DocumentInfo::new(5, 40).unwrap();
+        assert_eq!(page_labels(&document, 12)[0].first_number, 5);
+        assert_eq!(page_labels(&document, 12)[1], PageLabelRange {start: 8, numerals: NumeralStyle::Arabic, first_number: 1});
+        assert_eq!(page_labels(&document, 0), vec![PageLabelRange {start: 0, numerals: NumeralStyle::Arabic, first_number: 5}]);
+        assert_eq!(
+            page_labels(&DocumentInfo::new(13, 40).unwrap(), 12),
+            vec![PageLabelRange {start: 0, numerals: NumeralStyle::Arabic, first_number: 1}],
+        );
+        assert_eq!(page_labels(&DocumentInfo::new(1, 8).unwrap(), 12).len(), 1);
+    }
+
+    #[test]
+    fn test_deimpose_commands() {
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let commands = deimpose_commands(&document, "booklet.pdf", "reading.pdf").unwrap();
+        assert_eq!(commands[0].join(" "), "mutool poster -x 2 booklet.pdf reading.pdf.halves.pdf");
+        assert_eq!(commands[1].join(" "), "mutool merge -o reading.pdf reading.pdf.halves.pdf 2,3,6,7,8,5");
+        // paths the shell would split are quoted
+        let commands = deimpose_commands(&document, "my booklet.pdf", "reading.pdf").unwrap();
+        assert_eq!(shell_line(&commands[0]), "mutool poster -x 2 'my booklet.pdf' reading.pdf.halves.pdf");
+    }
+}
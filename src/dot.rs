@@ -0,0 +1,107 @@
+// A Graphviz graph of the plan, from the document down through its
+// signatures and sheets to the pages printed on each, e.g. for
+//   rust-signatures 1 40 --format dot | dot -Tsvg > plan.svg
+
+use crate::imposition;
+use crate::DocumentInfo;
+
+
+// Work
+pub fn dot_graph(document: &DocumentInfo) -> String {
+    // Node names are built from positions so they're always valid; the
+    // labels carry the keys and page numbers people read.
+    let mut dot = String::new();
+    dot.push_str(&document.job().header("// "));
+    dot.push_str("digraph plan {\n");
+    dot.push_str("  rankdir=LR;\n");
+    dot.push_str("  node [shape=box];\n");
+    dot.push_str(&format!(
+        "  document [label=\"Pages {} to {}\"];\n",
+        document.shown_page(document.first_page()),
+        document.shown_page(document.last_page()),
+    ));
+    let mut signature = 0;
+    for forme in imposition::impose_formes(document) {
+        if forme.sheet == 0 && forme.side == imposition::Side::Front {
+            signature += 1;
+            dot.push_str(&format!(
+                "  signature_{} [label=\"Signature {}\"];\n",
+                signature,
+                escape(&forme.signature_key),
+            ));
+            dot.push_str(&format!("  document -> signature_{};\n", signature));
+        }
+        let sheet = format!("sheet_{}_{}", signature, forme.sheet + 1);
+        if forme.side == imposition::Side::Front {
+            dot.push_str(&format!("  {} [label=\"Sheet {}\"];\n", sheet, forme.sheet + 1));
+            dot.push_str(&format!("  signature_{} -> {};\n", signature, sheet));
+        }
+        let two_up = forme.rows.len() == 1 && forme.rows[0].len() == 2;
+        for (row, cells) in forme.rows.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                // left and right for a folio, rows and columns for the rest
+                let position = match two_up {
+                    true => ["left", "right"][column].to_string(),
+                    false => format!("row {} column {}", row + 1, column + 1),
+                };
+                let node = match cell.page {
+                    Some(page) => {
+                        let node = format!("page_{}", page);
+                        dot.push_str(&format!("  {} [shape=ellipse, label=\"{}\"];\n", node, document.shown_page(page)));
+                        node
+                    },
+                    None => {
+                        let node = format!(
+                            "blank_{}_{}_{}_{}",
+                            signature,
+                            forme.sheet + 1,
+                            forme.side.forme(),
+                            position.replace(' ', "_"),
+                        );
+                        dot.push_str(&format!("  {} [shape=ellipse, style=dashed, label=\"blank\"];\n", node));
+                        node
+                    },
+                };
+                dot.push_str(&format!("  {} -> {} [label=\"{} {}\"];\n", sheet, node, forme.side.forme(), position));
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_graph() {
+        // one sheet, then a second signature of one sheet with two blanks
+        let document = DocumentInfo::with_signature_size(1, 6, 4).unwrap();
+        let dot = dot_graph(&document);
+        assert!(dot.starts_with("digraph plan {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("  document [label=\"Pages 1 to 6\"];\n"));
+        assert!(dot.contains("  document -> signature_2;\n"));
+        assert!(dot.contains("  signature_2 [label=\"Signature B\"];\n"));
+        assert!(dot.contains("  signature_1 -> sheet_1_1;\n"));
+        assert!(dot.contains("  sheet_1_1 -> page_4 [label=\"outer left\"];\n"));
+        assert!(dot.contains("  sheet_2_1 -> blank_2_1_outer_left [label=\"outer left\"];\n"));
+        assert_eq!(dot.matches("-> page_").count(), 6);
+        assert_eq!(dot.matches("-> blank_").count(), 2);
+        assert_eq!(escape("a\"b"), "a\\\"b");
+
+        // an octavo sheet has all sixteen pages under it, by row and column
+        let mut document = DocumentInfo::with_signature_size(1, 16, 16).unwrap();
+        document.set_fold_scheme(imposition::FoldScheme::Octavo).unwrap();
+        let dot = dot_graph(&document);
+        assert_eq!(dot.matches("-> sheet_").count(), 1);
+        assert_eq!(dot.matches("sheet_1_1 -> page_").count(), 16);
+        assert!(dot.contains("  sheet_1_1 -> page_1 [label=\"outer row 2 column 4\"];\n"));
+    }
+}
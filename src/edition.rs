@@ -0,0 +1,158 @@
+// Numbered slips for a limited edition, "Copy 12 of 150", laid out several
+// to a sheet in a PDF with lines to cut them apart along, ready to print and
+// tip in at the colophon. The PDF is written out by hand, so any build can
+// make one.
+
+use crate::imposition::PageSize;
+
+// Constants
+const POINTS_PER_MILLIMETRE: f64 = 72.0 / 25.4;
+// the largest the number is set, in points, and roughly how wide Helvetica
+// is on average for a given size, to centre each slip's line with
+const MAX_FONT_SIZE: f64 = 14.0;
+const HELVETICA_WIDTH: f64 = 0.5;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlipLayout {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl SlipLayout {
+    pub fn for_slips_per_sheet(slips_per_sheet: u32) -> SlipLayout {
+        // two columns when the slips pair up, otherwise one
+        let columns = if slips_per_sheet > 1 && slips_per_sheet.is_multiple_of(2) { 2 } else { 1 };
+        SlipLayout {columns, rows: slips_per_sheet.max(1) / columns}
+    }
+
+    pub fn slips_per_sheet(&self) -> u32 {
+        self.columns * self.rows
+    }
+}
+
+
+// Work
+pub fn slip_text(copy: u32, copies: u32) -> String {
+    format!("Copy {} of {}", copy, copies)
+}
+
+pub fn num_slip_sheets(copies: u32, layout: SlipLayout) -> u32 {
+    copies.div_ceil(layout.slips_per_sheet())
+}
+
+pub fn slips_pdf(copies: u32, layout: SlipLayout, sheet: PageSize) -> Vec<u8> {
+    // One page per sheet of slips, numbered across then down, each with
+    // its cutting lines and its line of text centred in its cell.
+    let width = sheet.width * POINTS_PER_MILLIMETRE;
+    let height = sheet.height * POINTS_PER_MILLIMETRE;
+    let cell_width = width / f64::from(layout.columns);
+    let cell_height = height / f64::from(layout.rows);
+    let font_size = (cell_height / 3.0).min(MAX_FONT_SIZE);
+    let mut contents = Vec::new();
+    for sheet_i in 0..num_slip_sheets(copies, layout) {
+        let mut content = String::from("0.25 w\n");
+        for column in 1..layout.columns {
+            let x = cell_width * f64::from(column);
+            content.push_str(&format!("{:.2} 0 m {:.2} {:.2} l S\n", x, x, height));
+        }
+        for row in 1..layout.rows {
+            let y = cell_height * f64::from(row);
+            content.push_str(&format!("0 {:.2} m {:.2} {:.2} l S\n", y, width, y));
+        }
+        for slip in 0..layout.slips_per_sheet() {
+            let copy = sheet_i * layout.slips_per_sheet() + slip + 1;
+            if copy > copies {
+                break;
+            }
+            let text = slip_text(copy, copies);
+            let text_width = text.chars().count() as f64 * font_size * HELVETICA_WIDTH;
+            // rows count down from the top of the sheet
+            let x = cell_width * f64::from(slip % layout.columns) + (cell_width - text_width) / 2.0;
+            let y = height - cell_height * f64::from(slip / layout.columns + 1) + (cell_height - font_size) / 2.0;
+            content.push_str(&format!("BT /F1 {:.1} Tf {:.2} {:.2} Td ({}) Tj ET\n", font_size, x, y, escape(&text)));
+        }
+        contents.push(content);
+    }
+    write_pdf(&contents, width, height)
+}
+
+fn write_pdf(contents: &[String], width: f64, height: f64) -> Vec<u8> {
+    // Objects 1 to 3 are the catalogue, the page tree and the font; each
+    // page is then a page object followed by its content stream.
+    let num_pages = contents.len();
+    let kids: Vec<String> = (0..num_pages).map(|i| format!("{} 0 R", 4 + 2 * i)).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), num_pages),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for (i, content) in contents.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            width,
+            height,
+            5 + 2 * i,
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content));
+    }
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref));
+    pdf.into_bytes()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slip_layout() {
+        assert_eq!(SlipLayout::for_slips_per_sheet(8), SlipLayout {columns: 2, rows: 4});
+        assert_eq!(SlipLayout::for_slips_per_sheet(5), SlipLayout {columns: 1, rows: 5});
+        assert_eq!(SlipLayout::for_slips_per_sheet(0).slips_per_sheet(), 1);
+        assert_eq!(num_slip_sheets(150, SlipLayout::for_slips_per_sheet(8)), 19);
+    }
+
+    #[test]
+    fn test_slips_pdf() {
+        let a4 = PageSize {width: 210.0, height: 297.0};
+        let pdf = String::from_utf8(slips_pdf(10, SlipLayout::for_slips_per_sheet(8), a4)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("/Count 2 >>"));
+        assert!(pdf.contains("(Copy 1 of 10) Tj"));
+        assert!(pdf.contains("(Copy 10 of 10) Tj"));
+        assert!(!pdf.contains("(Copy 11 of 10)"));
+        // the cross reference table is where the trailer says it is
+        let startxref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n0 8\n"));
+        // and each object where the table says
+        let table: Vec<usize> = pdf[startxref..].lines().skip(3).take(7).map(|line| line[..10].parse().unwrap()).collect();
+        for (i, offset) in table.iter().enumerate() {
+            assert!(pdf[*offset..].starts_with(&format!("{} 0 obj\n", i + 1)));
+        }
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_slips_pdf_loads() {
+        let a4 = PageSize {width: 210.0, height: 297.0};
+        let pdf = lopdf::Document::load_mem(&slips_pdf(150, SlipLayout::for_slips_per_sheet(8), a4)).unwrap();
+        assert_eq!(pdf.get_pages().len(), 19);
+    }
+}
@@ -0,0 +1,139 @@
+// The board panels of a box for the finished book, a slipcase open at the
+// spine or a clamshell of two trays nesting one in the other, sized from the
+// book's cover with a clearance all round so it slides in without rubbing.
+
+use crate::imposition::PageSize;
+use crate::materials::{Piece, SQUARE};
+use crate::spine::{Spine, SpineConfig};
+
+// Constants
+// in millimetres, the box board when the book's isn't given, and the room
+// left round the book when none is asked for
+const DEFAULT_BOARD_THICKNESS: f64 = 2.0;
+const DEFAULT_CLEARANCE: f64 = 1.0;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Enclosure {
+    Slipcase,
+    Clamshell,
+}
+
+impl Enclosure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Enclosure::Slipcase => "Slipcase",
+            Enclosure::Clamshell => "Clamshell box",
+        }
+    }
+}
+
+// the finished book, in millimetres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookSize {
+    pub width: f64,
+    pub height: f64,
+    pub thickness: f64,
+}
+
+
+// Work
+pub fn book_size(spine: &Spine, config: &SpineConfig, page_size: PageSize) -> BookSize {
+    // the boards stand out by the square at head, tail and fore-edge, and
+    // add their thickness each side of the text block
+    let board_thickness = config.board_thickness.unwrap_or(0.0);
+    BookSize {
+        width: page_size.width + SQUARE,
+        height: page_size.height + 2.0 * SQUARE,
+        thickness: spine.width + 2.0 * board_thickness,
+    }
+}
+
+pub fn enclosure_panels(
+    enclosure: Enclosure,
+    book: BookSize,
+    clearance: Option<f64>,
+    board_thickness: Option<f64>,
+) -> Vec<Piece> {
+    // The inside is the book plus the clearance each way. The sides are
+    // the full size, with the walls fitted between them.
+    let clearance = clearance.unwrap_or(DEFAULT_CLEARANCE);
+    let board = board_thickness.unwrap_or(DEFAULT_BOARD_THICKNESS);
+    let inside = BookSize {
+        width: book.width + clearance,
+        height: book.height + clearance,
+        thickness: book.thickness + clearance,
+    };
+    match enclosure {
+        Enclosure::Slipcase => {
+            let mut panels = vec![piece("Side", 2, inside.width + board, inside.height + 2.0 * board)];
+            panels.extend(tray_walls(["Head and tail", "Fore-edge"], inside, board));
+            panels
+        },
+        Enclosure::Clamshell => {
+            // the outer tray fits over the inner one, walls and all
+            let outer = BookSize {
+                width: inside.width + board + clearance,
+                height: inside.height + 2.0 * board + clearance,
+                thickness: inside.thickness + board + clearance,
+            };
+            let mut panels = vec![piece("Inner tray base", 1, inside.width, inside.height)];
+            panels.extend(tray_walls(["Inner tray head and tail", "Inner tray fore-edge"], inside, board));
+            panels.push(piece("Outer tray base", 1, outer.width, outer.height));
+            panels.extend(tray_walls(["Outer tray head and tail", "Outer tray fore-edge"], outer, board));
+            let case_height = outer.height + 2.0 * board;
+            panels.push(piece("Case board", 2, outer.width + board, case_height));
+            panels.push(piece("Case spine", 1, outer.thickness + 2.0 * board, case_height));
+            panels
+        },
+    }
+}
+
+fn tray_walls(names: [&str; 2], inside: BookSize, board: f64) -> Vec<Piece> {
+    // the head and tail walls, and the fore-edge wall across their ends
+    vec![
+        piece(names[0], 2, inside.width, inside.thickness),
+        piece(names[1], 1, inside.thickness, inside.height + 2.0 * board),
+    ]
+}
+
+fn piece(item: &str, per_copy: u32, width: f64, height: f64) -> Piece {
+    Piece {item: item.to_string(), per_copy, width, height: Some(height)}
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_book_size() {
+        let document = crate::DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let config = SpineConfig {caliper: 0.4, board_thickness: Some(2.5), ..SpineConfig::default()};
+        let spine = crate::spine::spine(&document, &config, page_size).unwrap();
+        assert_eq!(book_size(&spine, &config, page_size), BookSize {width: 151.0, height: 216.0, thickness: 17.0});
+    }
+
+    #[test]
+    fn test_enclosure_panels() {
+        let book = BookSize {width: 151.0, height: 216.0, thickness: 17.0};
+        let sizes = |panels: Vec<Piece>| -> Vec<(String, u32, f64, f64)> {
+            panels.into_iter().map(|panel| (panel.item, panel.per_copy, panel.width, panel.height.unwrap())).collect()
+        };
+        assert_eq!(
+            sizes(enclosure_panels(Enclosure::Slipcase, book, Some(1.0), Some(2.0))),
+            vec![
+                ("Side".to_string(), 2, 154.0, 221.0),
+                ("Head and tail".to_string(), 2, 152.0, 18.0),
+                ("Fore-edge".to_string(), 1, 18.0, 221.0),
+            ],
+        );
+        let clamshell = sizes(enclosure_panels(Enclosure::Clamshell, book, None, None));
+        assert_eq!(clamshell[0], ("Inner tray base".to_string(), 1, 152.0, 217.0));
+        assert_eq!(clamshell[3], ("Outer tray base".to_string(), 1, 155.0, 222.0));
+        assert_eq!(clamshell[6], ("Case board".to_string(), 2, 157.0, 226.0));
+        assert_eq!(clamshell[7], ("Case spine".to_string(), 1, 25.0, 226.0));
+    }
+}
@@ -0,0 +1,230 @@
+// Machine readable error reports, so scripts driving the tool can react to
+// the kind of failure rather than parsing the message.
+
+use std::error::Error;
+use std::io;
+use std::num;
+
+use crate::analyse::{CellsLineError, NoMatchingPlanError};
+use crate::batch::JobLineError;
+use crate::chase::ChaseFormatError;
+use crate::collation::CollationFormulaError;
+use crate::constraints::ConstraintConflictError;
+use crate::config::{FillerPageError, PagesPerSheetError, PlanConfigError};
+use crate::costs::CostLineError;
+use crate::cutting::SheetTooBigError;
+use crate::deimpose::ExternalToolError;
+use crate::imposition::PageSizeError;
+use crate::job::{DueDateError, ProjectLineError, ProjectVersionError};
+use crate::json::Json;
+use crate::labels::{NoLettersLeftError, NotEnoughLabelsError, SkipLetterError, UnknownSignatureKeyError};
+use crate::options::{InvalidOptionValueError, MissingFlagValueError, UnknownFlagError};
+use crate::pad::BlankPaddingError;
+use crate::page_count::PageCountError;
+use crate::press::StockLineError;
+use crate::spine::GuardedLeavesError;
+use crate::stamp::RunningHeadsLineError;
+use crate::volume::PartError;
+use crate::watch::NothingToWatchError;
+use crate::web::WebTooNarrowError;
+use crate::zine::ZineTooLongError;
+use crate::{FoldSchemeError, InvalidSignatureSizeError, NeedTwoArgumentsError, OverflowError, PageZeroError, SecondNumberGreaterError};
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport {
+    pub code: String,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl ErrorReport {
+    pub fn new(code: &str, message: &str, hint: Option<&str>) -> ErrorReport {
+        ErrorReport {
+            code: code.to_string(),
+            message: message.to_string(),
+            hint: hint.map(|hint| hint.to_string()),
+        }
+    }
+
+    pub fn from_error(problem: &str, err: &(dyn Error + 'static)) -> ErrorReport {
+        // e.g. ("Problem parsing arguments", PageZeroError) becomes
+        // code "page_zero", message "Problem parsing arguments: There is no..."
+        let (code, hint) = classify(err);
+        ErrorReport::new(code, &format!("{}: {}", problem, err), hint)
+    }
+
+    pub fn to_json(&self) -> String {
+        Json::object(vec![
+            ("code", Json::string(&self.code)),
+            ("message", Json::string(&self.message)),
+            ("hint", self.hint.as_deref().map_or(Json::Null, Json::string)),
+        ])
+        .to_pretty_string()
+    }
+}
+
+
+// Work
+fn classify(err: &(dyn Error + 'static)) -> (&'static str, Option<&'static str>) {
+    // the error code and a hint at how to fix it, for every error the crate
+    // can return
+    #[cfg(feature = "pdf")]
+    {
+        if err.is::<crate::pdf::PdfLayoutError>() {
+            return ("unexpected_pdf_layout", Some("Check the PDF was imposed with the same page range."));
+        } else if err.is::<lopdf::Error>() {
+            return ("invalid_pdf", None);
+        }
+    }
+    #[cfg(feature = "templates")]
+    {
+        if err.is::<tera::Error>() {
+            return ("template_error", None);
+        }
+    }
+    #[cfg(not(feature = "templates"))]
+    {
+        if err.is::<crate::templates::TemplatesNotBuiltError>() {
+            return ("templates_not_built", Some("Build with --features templates."));
+        }
+    }
+    #[cfg(not(feature = "pdf"))]
+    {
+        if err.is::<crate::zine::ZinePdfError>() {
+            return ("zine_needs_pdf", Some("Build with --features pdf."));
+        } else if err.is::<crate::impose::ImposePdfError>() {
+            return ("impose_needs_pdf", Some("Build with --features pdf."));
+        } else if err.is::<crate::pdfx::PdfxNotBuiltError>() {
+            return ("pdfx_needs_pdf", Some("Build with --features pdf."));
+        } else if err.is::<crate::contact::ContactSheetPdfError>() {
+            return ("contact_needs_pdf", Some("Build with --features pdf."));
+        }
+    }
+    #[cfg(not(feature = "ocr"))]
+    {
+        if err.is::<crate::verify::OcrNotBuiltError>() {
+            return ("ocr_not_built", Some("Build with --features ocr, with mutool and tesseract installed."));
+        }
+    }
+    if let Some(config_error) = err.downcast_ref::<PlanConfigError>() {
+        // one problem is reported as itself
+        return match config_error.problems.as_slice() {
+            [problem] => classify(problem.as_ref()),
+            _ => ("invalid_config", Some("Fix each of the problems listed.")),
+        };
+    }
+    if err.is::<NeedTwoArgumentsError>() {
+        ("need_two_arguments", Some("Give the first and last page numbers, e.g. rust-signatures 1 60."))
+    } else if err.is::<PageZeroError>() {
+        ("page_zero", Some("Page numbers start at 1."))
+    } else if err.is::<SecondNumberGreaterError>() {
+        ("page_range_reversed", Some("Give the first page before the last page."))
+    } else if err.is::<InvalidSignatureSizeError>() {
+        ("invalid_signature_size", Some("Use a multiple of 4 pages, e.g. 16 or 20."))
+    } else if err.is::<OverflowError>() {
+        ("overflow", Some("Page numbers that large can't be printed; check the page range."))
+    } else if err.is::<PagesPerSheetError>() {
+        ("invalid_pages_per_sheet", None)
+    } else if err.is::<FoldSchemeError>() {
+        ("invalid_fold_scheme", Some("Give a --signature-size that's a multiple of the pages to a folded sheet."))
+    } else if err.is::<ConstraintConflictError>() {
+        ("constraints_conflict", Some("Drop or loosen one of the constraints named."))
+    } else if err.is::<FillerPageError>() {
+        ("filler_page_out_of_range", Some("Give a page number inside the page range."))
+    } else if err.is::<BlankPaddingError>() {
+        ("blank_padding_needs_pdf", Some("Give --filler-page to repeat a page instead, or build with --features pdf."))
+    } else if err.is::<SheetTooBigError>() {
+        ("sheet_too_big", Some("Choose a bigger parent sheet, or fewer pages per side."))
+    } else if err.is::<GuardedLeavesError>() {
+        ("too_many_guarded_leaves", Some("Give at most as many single leaves as the plan has leaves."))
+    } else if err.is::<WebTooNarrowError>() {
+        ("web_too_narrow", Some("Use a wider web, or a smaller page size."))
+    } else if err.is::<UnknownFlagError>() {
+        ("unknown_option", None)
+    } else if err.is::<MissingFlagValueError>() {
+        ("missing_option_value", None)
+    } else if err.is::<InvalidOptionValueError>() {
+        ("invalid_option_value", None)
+    } else if err.is::<PageSizeError>() {
+        ("invalid_page_size", Some("Give the page size in millimetres, e.g. 148x210."))
+    } else if err.is::<SkipLetterError>() {
+        ("invalid_skip_letters", Some("Separate the letters with commas, e.g. J,V,W."))
+    } else if err.is::<NoLettersLeftError>() {
+        ("no_letters_left", Some("Skip fewer letters, or choose another alphabet."))
+    } else if err.is::<NotEnoughLabelsError>() {
+        ("not_enough_labels", Some("Add more labels to the labels file."))
+    } else if err.is::<UnknownSignatureKeyError>() {
+        ("unknown_signature_key", None)
+    } else if err.is::<RunningHeadsLineError>() {
+        ("invalid_running_heads", Some("Lines should look like \"title: TEXT\", \"5-20: TEXT\" or \"5: TEXT\"."))
+    } else if err.is::<StockLineError>() {
+        ("invalid_stocks", Some("Lines should look like \"default: STOCK\", \"A, B: STOCK\" or \"tray 2: STOCK\"."))
+    } else if err.is::<CostLineError>() {
+        ("invalid_costs", Some("Lines should look like \"sheet: 0.04\", \"sheet STOCK: 0.11\" or \"cover: 1.20\"."))
+    } else if err.is::<ProjectLineError>() {
+        ("invalid_project", Some("Lines should look like \"title: TEXT\", \"client: TEXT\", \"job: TEXT\" or \"due: 2026-11-01\"."))
+    } else if err.is::<ProjectVersionError>() {
+        ("project_version_unsupported", Some("Read it with the release that wrote it, or a newer one."))
+    } else if err.is::<DueDateError>() {
+        ("invalid_due_date", Some("Give the date as YYYY-MM-DD."))
+    } else if err.is::<JobLineError>() {
+        ("invalid_jobs", Some("Lines should look like \"NAME: 1-212\" or \"NAME: book.pdf on STOCK\"."))
+    } else if err.is::<CollationFormulaError>() {
+        ("invalid_collation_formula", Some("Give runs of gatherings with their leaves, e.g. \"A-K8 L4\"."))
+    } else if err.is::<ZineTooLongError>() {
+        ("zine_too_long", Some("Split it into zines of 8 pages, or plan it as a book."))
+    } else if err.is::<ChaseFormatError>() {
+        ("unknown_chase_format", None)
+    } else if err.is::<NothingToWatchError>() {
+        ("nothing_to_watch", None)
+    } else if err.is::<PartError>() {
+        ("invalid_part", Some("Give each --part as a PDF or a span of pages, e.g. 1-48."))
+    } else if err.is::<PageCountError>() {
+        ("page_count_failed", Some("Install pdfinfo, qpdf or mutool, or give the page range as numbers."))
+    } else if err.is::<CellsLineError>() {
+        ("invalid_cells", Some("Give the left and right page of each sheet side, e.g. \"16 1\", with - for a blank."))
+    } else if err.is::<NoMatchingPlanError>() {
+        ("no_matching_plan", None)
+    } else if err.is::<ExternalToolError>() {
+        ("external_tool_failed", Some("Check the tool is installed and on the PATH."))
+    } else if err.is::<num::ParseIntError>() || err.is::<num::ParseFloatError>() {
+        ("invalid_number", None)
+    } else if err.is::<io::Error>() {
+        ("io", None)
+    } else {
+        ("unknown", None)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PlanConfig;
+
+    #[test]
+    fn test_error_report() {
+        let err: Box<dyn Error> = PageZeroError.into();
+        let report = ErrorReport::from_error("Problem parsing arguments", err.as_ref());
+        assert_eq!(report.code, "page_zero");
+        assert_eq!(
+            report.to_json(),
+            "{\n  \"code\": \"page_zero\",\n  \"message\": \"Problem parsing arguments: There is no page zero! \
+             Received 0 as the first page number.\",\n  \"hint\": \"Page numbers start at 1.\"\n}\n",
+        );
+
+        let err: Box<dyn Error> = "x".parse::<u32>().unwrap_err().into();
+        let report = ErrorReport::from_error("Problem parsing arguments", err.as_ref());
+        assert_eq!(report.code, "invalid_number");
+        assert!(report.to_json().contains("\"hint\": null"));
+
+        let mut config = PlanConfig::new(0, 60);
+        let report = ErrorReport::from_error("Problem planning", &config.validate().unwrap_err());
+        assert_eq!(report.code, "page_zero");
+        config.pages_per_signature = 6;
+        let report = ErrorReport::from_error("Problem planning", &config.validate().unwrap_err());
+        assert_eq!(report.code, "invalid_config");
+    }
+}
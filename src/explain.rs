@@ -0,0 +1,126 @@
+// The arithmetic behind a plan, step by step, so a plan that looks wrong
+// can be checked by hand rather than taken on trust.
+
+use crate::multi_up::MultiUpLayout;
+use crate::DocumentInfo;
+
+
+// Work
+pub fn explain(document: &DocumentInfo, layout: Option<MultiUpLayout>) -> String {
+    let pages_per_signature = document.pages_per_signature();
+    // half as many when the sheets are printed one side only
+    let pages_per_sheet = document.pages_per_sheet();
+    let sheets_per_signature = pages_per_signature / pages_per_sheet;
+    let rounded = |whole: bool| if whole { "" } else { ", rounded up" };
+    let mut lines = vec![
+        format!(
+            "Pages {} to {}: {} - {} + 1 = {} pages",
+            document.shown_page(document.first_page()),
+            document.shown_page(document.last_page()),
+            document.shown_page(document.last_page()),
+            document.shown_page(document.first_page()),
+            document.num_pages(),
+        ),
+        format!(
+            "{} pages \u{f7} {} pages/sheet = {} sheets{}",
+            document.num_pages(),
+            pages_per_sheet,
+            document.num_sheets(),
+            rounded(document.num_pages().is_multiple_of(pages_per_sheet)),
+        ),
+        format!(
+            "{} pages \u{f7} {} pages/signature = {} signatures{}",
+            document.num_pages(),
+            pages_per_signature,
+            document.num_signatures(),
+            rounded(document.num_pages().is_multiple_of(pages_per_signature)),
+        ),
+    ];
+    // only the last signature can be short
+    if let Some(last) = document.signatures().last() {
+        let pages = last.last_page() - last.first_page() + 1;
+        let key = document.label_scheme().display_key(last.key());
+        if pages < pages_per_signature {
+            let sheets = pages.div_ceil(pages_per_sheet);
+            let instead = if sheets < sheets_per_signature {
+                format!(" instead of {}", sheets_per_signature)
+            } else {
+                String::new()
+            };
+            lines.push(format!(
+                "Signature {} has {} pages, {} short of {} \u{2192} folded from {} sheets{}",
+                key,
+                pages,
+                pages_per_signature - pages,
+                pages_per_signature,
+                sheets,
+                instead,
+            ));
+        }
+        let blanks = document.num_blank_pages();
+        if blanks > 0 {
+            lines.push(format!(
+                "{} sheets \u{d7} {} pages/sheet - {} pages = {} blank pages \u{2192} {} at the back of signature {}",
+                document.num_sheets(),
+                pages_per_sheet,
+                document.num_pages(),
+                blanks,
+                blank_leaves(blanks),
+                key,
+            ));
+        }
+    }
+    if let Some(layout) = layout.filter(|layout| layout.num_strips() > 1) {
+        let strips = layout.num_strips();
+        lines.push(format!(
+            "{} sheets \u{f7} {} strips/press sheet = {} press sheets{}",
+            document.num_sheets(),
+            strips,
+            document.num_sheets().div_ceil(strips),
+            rounded(document.num_sheets().is_multiple_of(strips)),
+        ));
+    }
+    lines.push(format!("Collation: {}", document.collation_formula()));
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn blank_leaves(blanks: u32) -> String {
+    // a leaf is only blank when both its pages are
+    match (blanks / 2, blanks % 2) {
+        (0, _) => "1 blank page".to_string(),
+        (1, 0) => "1 blank leaf".to_string(),
+        (1, _) => "1 blank leaf and a blank page".to_string(),
+        (leaves, 0) => format!("{} blank leaves", leaves),
+        (leaves, _) => format!("{} blank leaves and a blank page", leaves),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain() {
+        let document = DocumentInfo::new(1, 126).unwrap();
+        assert_eq!(
+            explain(&document, None),
+            "Pages 1 to 126: 126 - 1 + 1 = 126 pages\n\
+             126 pages \u{f7} 4 pages/sheet = 32 sheets, rounded up\n\
+             126 pages \u{f7} 16 pages/signature = 8 signatures, rounded up\n\
+             Signature H has 14 pages, 2 short of 16 \u{2192} folded from 4 sheets\n\
+             32 sheets \u{d7} 4 pages/sheet - 126 pages = 2 blank pages \u{2192} 1 blank leaf at the back of signature H\n\
+             Collation: A\u{2013}H\u{2078}\n",
+        );
+
+        let document = DocumentInfo::new(1, 128).unwrap();
+        let explanation = explain(&document, MultiUpLayout::for_pages_per_sheet(24));
+        assert!(!explanation.contains("short"));
+        assert!(!explanation.contains("blank"));
+        assert!(explanation.ends_with("32 sheets \u{f7} 6 strips/press sheet = 6 press sheets, rounded up\nCollation: A\u{2013}H\u{2078}\n"));
+
+        let explanation = explain(&DocumentInfo::new(1, 40).unwrap(), None);
+        assert!(explanation.contains("Signature C has 8 pages, 8 short of 16 \u{2192} folded from 2 sheets instead of 4\n"));
+        assert_eq!(blank_leaves(3), "1 blank leaf and a blank page");
+    }
+}
@@ -0,0 +1,233 @@
+// Step by step folding diagrams for the hand-press formats, as SVG panels
+// to print and pin up by the folding bench. The folding is worked out by
+// actually folding the printed sheet, so the pages showing in each panel
+// are the ones the folder will see.
+
+use crate::chase::{ChaseFormat, ChasePage};
+use crate::imposition::Fold;
+
+// Constants
+const CELL_WIDTH: u32 = 60;
+const CELL_HEIGHT: u32 = 80;
+const PANEL_GAP: u32 = 40;
+const CAPTION_HEIGHT: u32 = 40;
+const CAPTION_LINE_HEIGHT: u32 = 16;
+
+
+// Data structs
+// one thickness of paper, with the page facing up and the one facing down,
+// each with its head as seen when that side faces up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layer {
+    pub up: ChasePage,
+    pub down: ChasePage,
+}
+
+impl Layer {
+    fn turned_over(&self, fold: Fold) -> Layer {
+        // turning over top to bottom also turns the pages head to foot
+        let turn = |page: ChasePage| match fold {
+            Fold::TopUnder => ChasePage {page: page.page, head_down: !page.head_down},
+            Fold::LeftUnder => page,
+        };
+        Layer {up: turn(self.down), down: turn(self.up)}
+    }
+}
+
+// the sheet as it lies on the bench, rows of cells, each a stack of layers
+// from the top down
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldedSheet {
+    pub cells: Vec<Vec<Vec<Layer>>>,
+}
+
+impl FoldedSheet {
+    pub fn flat(format: ChaseFormat) -> FoldedSheet {
+        // outer forme up; the inner forme is underneath, left to right
+        // reversed as it is when the sheet is turned over
+        let outer = format.outer_forme();
+        let inner = format.inner_forme();
+        let cells = outer
+            .rows
+            .iter()
+            .zip(&inner.rows)
+            .map(|(outer_row, inner_row)| {
+                outer_row
+                    .iter()
+                    .zip(inner_row.iter().rev())
+                    .map(|(up, down)| vec![Layer {up: *up, down: *down}])
+                    .collect()
+            })
+            .collect();
+        FoldedSheet {cells}
+    }
+
+    pub fn fold(&self, fold: Fold) -> FoldedSheet {
+        // The half that stays keeps its place on top; the other half is
+        // turned over beneath it, mirrored across the fold.
+        let rows = self.cells.len();
+        let cols = self.cells[0].len();
+        let (new_rows, new_cols) = match fold {
+            Fold::TopUnder => (rows / 2, cols),
+            Fold::LeftUnder => (rows, cols / 2),
+        };
+        let mut cells = Vec::new();
+        for row in 0..new_rows {
+            let mut new_row = Vec::new();
+            for col in 0..new_cols {
+                let (staying, moving) = match fold {
+                    Fold::TopUnder => (&self.cells[row + new_rows][col], &self.cells[new_rows - 1 - row][col]),
+                    Fold::LeftUnder => (&self.cells[row][col + new_cols], &self.cells[row][new_cols - 1 - col]),
+                };
+                let mut stack = staying.clone();
+                stack.extend(moving.iter().rev().map(|layer| layer.turned_over(fold)));
+                new_row.push(stack);
+            }
+            cells.push(new_row);
+        }
+        FoldedSheet {cells}
+    }
+
+    pub fn showing(&self) -> Vec<Vec<ChasePage>> {
+        // the pages on top, as seen from above
+        self.cells.iter().map(|row| row.iter().map(|stack| stack[0].up).collect()).collect()
+    }
+}
+
+
+// Work
+pub fn folds(format: ChaseFormat) -> Vec<Fold> {
+    // Each leaves the pages in order through the folded gathering, with
+    // page 1 on top and every page head up.
+    match format {
+        ChaseFormat::Folio => vec![Fold::LeftUnder],
+        ChaseFormat::Quarto => vec![Fold::TopUnder, Fold::LeftUnder],
+        ChaseFormat::Octavo => vec![Fold::LeftUnder, Fold::TopUnder, Fold::LeftUnder],
+    }
+}
+
+pub fn fold_steps_svg(format: ChaseFormat) -> String {
+    // One panel for the flat sheet and one after each fold, left to right,
+    // with the next fold dashed in.
+    let steps = folds(format);
+    let mut sheets = vec![FoldedSheet::flat(format)];
+    for fold in &steps {
+        let folded = sheets[sheets.len() - 1].fold(*fold);
+        sheets.push(folded);
+    }
+    let flat_width = sheets[0].cells[0].len() as u32 * CELL_WIDTH;
+    let flat_height = sheets[0].cells.len() as u32 * CELL_HEIGHT;
+    let width = sheets.len() as u32 * (flat_width + PANEL_GAP) + PANEL_GAP;
+    let height = flat_height + CAPTION_HEIGHT + 2 * PANEL_GAP;
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"serif\">\n",
+        width,
+        height,
+    ));
+    for (i, sheet) in sheets.iter().enumerate() {
+        let x = PANEL_GAP + i as u32 * (flat_width + PANEL_GAP);
+        let y = PANEL_GAP + CAPTION_HEIGHT;
+        let caption = match steps.get(i) {
+            Some(fold) if i == 0 => vec!["1. Lay the sheet outer forme up".to_string(), fold.describe().to_string()],
+            Some(fold) => vec![format!("{}. {}", i + 1, fold.describe())],
+            None => vec![format!("{}. Folded, page 1 on top", i + 1)],
+        };
+        for (line, text) in caption.iter().enumerate() {
+            let line_y = PANEL_GAP + line as u32 * CAPTION_LINE_HEIGHT;
+            svg.push_str(&format!("  <text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>\n", x, line_y, text));
+        }
+        svg.push_str(&draw_sheet(sheet, x, y));
+        if let Some(fold) = steps.get(i) {
+            svg.push_str(&draw_fold_line(sheet, *fold, x, y));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn draw_sheet(sheet: &FoldedSheet, x: u32, y: u32) -> String {
+    // a box per cell with its top page, turned upside down if head down
+    let mut svg = String::new();
+    for (row, pages) in sheet.showing().iter().enumerate() {
+        for (col, page) in pages.iter().enumerate() {
+            let left = x + col as u32 * CELL_WIDTH;
+            let top = y + row as u32 * CELL_HEIGHT;
+            let (centre_x, centre_y) = (left + CELL_WIDTH / 2, top + CELL_HEIGHT / 2);
+            svg.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\" stroke=\"black\"/>\n",
+                left,
+                top,
+                CELL_WIDTH,
+                CELL_HEIGHT,
+            ));
+            let rotation = if page.head_down {
+                format!(" transform=\"rotate(180 {} {})\"", centre_x, centre_y)
+            } else {
+                String::new()
+            };
+            svg.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" font-size=\"20\" text-anchor=\"middle\" dominant-baseline=\"middle\"{}>{}</text>\n",
+                centre_x,
+                centre_y,
+                rotation,
+                page.page,
+            ));
+        }
+    }
+    svg
+}
+
+fn draw_fold_line(sheet: &FoldedSheet, fold: Fold, x: u32, y: u32) -> String {
+    let width = sheet.cells[0].len() as u32 * CELL_WIDTH;
+    let height = sheet.cells.len() as u32 * CELL_HEIGHT;
+    let (x1, y1, x2, y2) = match fold {
+        Fold::TopUnder => (x, y + height / 2, x + width, y + height / 2),
+        Fold::LeftUnder => (x + width / 2, y, x + width / 2, y + height),
+    };
+    format!(
+        "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"red\" stroke-dasharray=\"6 4\"/>\n",
+        x1,
+        y1,
+        x2,
+        y2,
+    )
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded(format: ChaseFormat) -> FoldedSheet {
+        folds(format).iter().fold(FoldedSheet::flat(format), |sheet, fold| sheet.fold(*fold))
+    }
+
+    #[test]
+    fn test_folding() {
+        // folded properly, the pages run in order from the top of the pile
+        for format in [ChaseFormat::Folio, ChaseFormat::Quarto, ChaseFormat::Octavo] {
+            let sheet = folded(format);
+            assert_eq!(sheet.cells.len(), 1);
+            assert_eq!(sheet.cells[0].len(), 1);
+            let pages: Vec<u32> = sheet.cells[0][0].iter().flat_map(|layer| vec![layer.up.page, layer.down.page]).collect();
+            assert_eq!(pages, (1..=pages.len() as u32).collect::<Vec<u32>>());
+            assert!(sheet.cells[0][0].iter().all(|layer| !layer.up.head_down && !layer.down.head_down));
+        }
+
+        let quarto = FoldedSheet::flat(ChaseFormat::Quarto).fold(Fold::TopUnder);
+        assert_eq!(quarto.showing(), vec![vec![ChasePage {page: 8, head_down: false}, ChasePage {page: 1, head_down: false}]]);
+    }
+
+    #[test]
+    fn test_fold_steps_svg() {
+        let svg = fold_steps_svg(ChaseFormat::Quarto);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">1. Lay the sheet outer forme up</text>\n  <text x=\"40\" y=\"56\" font-size=\"12\">Fold the top half under</text>"));
+        assert!(svg.contains(">2. Fold the left half under</text>"));
+        assert!(svg.contains(">3. Folded, page 1 on top</text>"));
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains("transform=\"rotate(180 70 120)\">5</text>"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::fs;
+
+use eframe::egui;
+
+use crate::commands;
+use crate::config::PlanConfig;
+use crate::imposition::PageSize;
+use crate::interchange;
+use crate::scribus;
+use crate::{DocumentInfo, DOC_PAGES_PER_SHEET, DOC_PAGES_PER_SIGNATURE};
+
+// Constants
+const SIGNATURE_SIZES: [u32; 8] = [4, 8, 12, 16, 20, 24, 28, 32];
+
+
+// Data structs
+struct PlannerApp {
+    first_page: u32,
+    last_page: u32,
+    pages_per_signature: u32,
+    page_size: PageSize,
+    file_stem: String,
+    status: String,
+}
+
+impl Default for PlannerApp {
+    fn default() -> PlannerApp {
+        PlannerApp {
+            first_page: 1,
+            last_page: 60,
+            pages_per_signature: DOC_PAGES_PER_SIGNATURE,
+            page_size: PageSize::default(),
+            file_stem: "plan".to_string(),
+            status: String::new(),
+        }
+    }
+}
+
+impl PlannerApp {
+    fn inputs(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("inputs").num_columns(2).show(ui, |ui| {
+            ui.label("First page");
+            ui.add(egui::DragValue::new(&mut self.first_page).range(1..=u32::MAX));
+            ui.end_row();
+
+            ui.label("Last page");
+            ui.add(egui::DragValue::new(&mut self.last_page).range(self.first_page..=u32::MAX));
+            ui.end_row();
+
+            ui.label("Pages per signature");
+            egui::ComboBox::from_id_salt("pages_per_signature")
+                .selected_text(self.pages_per_signature.to_string())
+                .show_ui(ui, |ui| {
+                    for size in SIGNATURE_SIZES.iter() {
+                        ui.selectable_value(&mut self.pages_per_signature, *size, size.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Page size (mm)");
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut self.page_size.width).range(1.0..=2000.0));
+                ui.label("×");
+                ui.add(egui::DragValue::new(&mut self.page_size.height).range(1.0..=2000.0));
+            });
+            ui.end_row();
+        });
+        // the last page can't fall behind the first
+        self.last_page = self.last_page.max(self.first_page);
+    }
+
+    fn plan_table(&self, ui: &mut egui::Ui, document: &DocumentInfo) {
+        ui.label(format!(
+            "{} pages on {} sheets, bound as {} signatures of {} sheets.",
+            document.num_pages(),
+            document.num_sheets(),
+            document.num_signatures(),
+            document.pages_per_signature() / DOC_PAGES_PER_SHEET,
+        ));
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("plan").striped(true).num_columns(4).show(ui, |ui| {
+                ui.strong("Signature");
+                ui.strong("First page");
+                ui.strong("Last page");
+                ui.strong("Pages");
+                ui.end_row();
+                for signature in document.signatures() {
+                    ui.label(signature.key());
+                    ui.label(signature.first_page().to_string());
+                    ui.label(signature.last_page().to_string());
+                    ui.label((signature.last_page() - signature.first_page() + 1).to_string());
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn exports(&mut self, ui: &mut egui::Ui, document: &DocumentInfo) {
+        ui.horizontal(|ui| {
+            ui.label("Save as");
+            ui.text_edit_singleline(&mut self.file_stem);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Text summary").clicked() {
+                self.export("txt", document.to_string());
+            }
+            if ui.button("Scribus script").clicked() {
+                match scribus::scribus_script(document, self.page_size) {
+                    Ok(script) => self.export("py", script),
+                    Err(err) => self.status = format!("Problem writing the Scribus script: {}", err),
+                }
+            }
+            if ui.button("Tool commands").clicked() {
+                match commands::equivalent_commands(document) {
+                    Ok(commands) => self.export("sh", commands),
+                    Err(err) => self.status = format!("Problem writing the commands: {}", err),
+                }
+            }
+            if ui.button("Imposition JSON").clicked() {
+                self.export("json", interchange::imposition_json(document, self.page_size));
+            }
+        });
+        ui.label(&self.status);
+    }
+
+    fn export(&mut self, extension: &str, contents: String) {
+        let path = format!("{}.{}", self.file_stem, extension);
+        self.status = match fs::write(&path, contents) {
+            Ok(()) => format!("Saved {}", path),
+            Err(err) => format!("Problem saving {}: {}", path, err),
+        };
+    }
+}
+
+impl eframe::App for PlannerApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Signature planner");
+            self.inputs(ui);
+            ui.separator();
+            let mut config = PlanConfig::new(self.first_page, self.last_page);
+            config.pages_per_signature = self.pages_per_signature;
+            config.page_size = self.page_size;
+            let document = config.plan();
+            match document {
+                Ok(document) => {
+                    self.plan_table(ui, &document);
+                    ui.separator();
+                    self.exports(ui, &document);
+                },
+                Err(err) => {
+                    ui.label(format!("Problem planning: {}", err));
+                },
+            }
+        });
+    }
+}
+
+
+// Work
+pub fn run() -> eframe::Result {
+    // Open the planner window and block until it's closed.
+    eframe::run_native(
+        "rust-signatures",
+        eframe::NativeOptions::default(),
+        Box::new(|_creation_context| Ok(Box::new(PlannerApp::default()))),
+    )
+}
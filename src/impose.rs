@@ -0,0 +1,212 @@
+// Imposing a reading-order PDF to print: each of the plan's sheet sides, or
+// its press sheet sides when several sheets are printed on one, drawn into
+// a new PDF with the pages in place, ready to print on both sides, fold and
+// gather. Backs can be shifted to make up for a printer that doesn't print
+// them square with the fronts. Writing the PDF needs a build with the pdf
+// feature.
+
+use std::error::Error;
+#[cfg(not(feature = "pdf"))]
+use std::fmt;
+
+use crate::chase::{ChasePage, Forme};
+use crate::imposition::{self, BookletFold, Cell, FoldScheme, PageSize, SheetSide};
+use crate::multi_up::{self, MultiUpLayout};
+use crate::DocumentInfo;
+
+// Constants
+const IMPOSED_SUFFIX: &str = "-imposed.pdf";
+// drawn into a cell to leave it empty
+const BLANK: u32 = 0;
+
+
+// Custom errors
+#[cfg(not(feature = "pdf"))]
+#[derive(Debug)]
+pub(crate) struct ImposePdfError;
+
+#[cfg(not(feature = "pdf"))]
+impl Error for ImposePdfError {}
+
+#[cfg(not(feature = "pdf"))]
+impl fmt::Display for ImposePdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Writing the imposed PDF needs a build with --features pdf. Try --format commands for pdfjam instead.")
+    }
+}
+
+
+// Data structs
+// How far the printer puts the backs from where the fronts say they should
+// be, in millimetres, measured on the printed back held head up: right and
+// down are positive. The backs are drawn shifted the other way.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PrinterOffset {
+    pub right: f64,
+    pub down: f64,
+}
+
+impl PrinterOffset {
+    pub fn parse(value: &str) -> Option<PrinterOffset> {
+        // e.g. "1.5,-0.5"
+        let (right, down) = value.split_once(',')?;
+        Some(PrinterOffset {right: right.trim().parse().ok()?, down: down.trim().parse().ok()?})
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.right == 0.0 && self.down == 0.0
+    }
+}
+
+
+// Work
+pub fn imposed_path(input: &str) -> String {
+    format!("{}{}", crate::split::input_stem(input), IMPOSED_SUFFIX)
+}
+
+pub fn sheet_formes(document: &DocumentInfo, layout: Option<MultiUpLayout>, fold: BookletFold) -> Vec<Forme> {
+    // The pages on each side to print, front then back, sheet by sheet.
+    // Blanks are drawn as page 0, which the PDF doesn't have.
+    let page = |page: Option<u32>, head_down: bool| ChasePage {page: page.unwrap_or(BLANK), head_down};
+    let Ok(sides) = imposition::impose(document) else {
+        // sheets folded more than once each fill a forme of their own
+        return imposition::impose_formes(document)
+            .iter()
+            .map(|forme| Forme {
+                rows: forme.rows.iter().map(|row| row.iter().map(|cell| page(cell.page, cell.head_down)).collect()).collect(),
+            })
+            .collect();
+    };
+    if let Some(layout) = layout.filter(|layout| layout.num_strips() > 1) {
+        return multi_up::multi_up_sheets(&sides, layout)
+            .iter()
+            .flat_map(|press_sheet| [&press_sheet.front, &press_sheet.back])
+            .map(|side| Forme {
+                rows: side.cells.iter().map(|row| row.iter().map(|&cell| page(cell, false)).collect()).collect(),
+            })
+            .collect();
+    }
+    sides.iter().map(|side| side_forme(side, fold)).collect()
+}
+
+fn side_forme(side: &SheetSide, fold: BookletFold) -> Forme {
+    // side by side, or one above the other when folded at the top
+    let cell = |cell: Cell| ChasePage {
+        page: match cell {
+            Cell::Left => side.left,
+            Cell::Right => side.right,
+        }
+        .unwrap_or(BLANK),
+        head_down: fold.rotation(side.side, cell) == 180,
+    };
+    match fold {
+        BookletFold::Side => Forme {rows: vec![vec![cell(Cell::Left), cell(Cell::Right)]]},
+        BookletFold::Top if fold.is_on_top(side.side, Cell::Left) => {
+            Forme {rows: vec![vec![cell(Cell::Left)], vec![cell(Cell::Right)]]}
+        },
+        BookletFold::Top => Forme {rows: vec![vec![cell(Cell::Right)], vec![cell(Cell::Left)]]},
+    }
+}
+
+pub fn duplex(sheet: PageSize, layout: Option<MultiUpLayout>, fold: BookletFold, fold_scheme: FoldScheme) -> &'static str {
+    // A press sheet, or a sheet folded more than once, turns over left to
+    // right, on whichever edge runs top to bottom; the plan's own sheets
+    // folded once turn as their fold has them.
+    let multi_up = layout.is_some_and(|layout| layout.num_strips() > 1);
+    match multi_up || fold_scheme != FoldScheme::Folio {
+        true if sheet.height < sheet.width => "two-sided-short-edge",
+        true => "two-sided-long-edge",
+        false => fold.duplex(),
+    }
+}
+
+#[cfg(feature = "pdf")]
+pub fn impose_pdf(
+    input: &str,
+    output: &str,
+    sheet: PageSize,
+    formes: &[Forme],
+    offset: PrinterOffset,
+) -> Result<(), Box<dyn Error>> {
+    crate::pdf::impose_formes(input, output, sheet, formes, offset)
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn impose_pdf(
+    _input: &str,
+    _output: &str,
+    _sheet: PageSize,
+    _formes: &[Forme],
+    _offset: PrinterOffset,
+) -> Result<(), Box<dyn Error>> {
+    Err(ImposePdfError.into())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages(forme: &Forme) -> Vec<Vec<u32>> {
+        forme.rows.iter().map(|row| row.iter().map(|cell| cell.page).collect()).collect()
+    }
+
+    #[test]
+    fn test_sheet_formes() {
+        // 6 pages on two sheets: - 1 | 2 - | 6 3 | 4 5
+        let document = DocumentInfo::with_signature_size(1, 6, 8).unwrap();
+        let formes = sheet_formes(&document, None, BookletFold::Side);
+        assert_eq!(formes.len(), 4);
+        assert_eq!(pages(&formes[0]), vec![vec![0, 1]]);
+        assert_eq!(pages(&formes[2]), vec![vec![6, 3]]);
+        assert!(formes.iter().flat_map(|forme| forme.rows.iter().flatten()).all(|cell| !cell.head_down));
+        assert_eq!(imposed_path("books/poems.pdf"), "books/poems-imposed.pdf");
+    }
+
+    #[test]
+    fn test_printer_offset() {
+        assert_eq!(PrinterOffset::parse("1.5, -0.5"), Some(PrinterOffset {right: 1.5, down: -0.5}));
+        assert_eq!(PrinterOffset::parse("1.5"), None);
+        assert_eq!(PrinterOffset::parse("left,2"), None);
+        assert!(PrinterOffset::default().is_zero());
+    }
+
+    #[test]
+    fn test_sheet_formes_top_fold() {
+        // the front's left cell on top and turned round, the back upside down
+        let document = DocumentInfo::with_signature_size(1, 8, 8).unwrap();
+        let formes = sheet_formes(&document, None, BookletFold::Top);
+        assert_eq!(pages(&formes[0]), vec![vec![8], vec![1]]);
+        assert!(formes[0].rows[0][0].head_down && !formes[0].rows[1][0].head_down);
+        assert_eq!(pages(&formes[1]), vec![vec![7], vec![2]]);
+        assert!(formes[1].rows.iter().flatten().all(|cell| cell.head_down));
+    }
+
+    #[test]
+    fn test_sheet_formes_four_up() {
+        // two of the plan's sheets on each press sheet, one above the other
+        let document = DocumentInfo::with_signature_size(1, 8, 8).unwrap();
+        let layout = MultiUpLayout::for_pages_per_sheet(8);
+        let formes = sheet_formes(&document, layout, BookletFold::Side);
+        assert_eq!(formes.len(), 2);
+        assert_eq!(pages(&formes[0]), vec![vec![8, 1], vec![6, 3]]);
+        assert_eq!(pages(&formes[1]), vec![vec![2, 7], vec![4, 5]]);
+        let sheet = PageSize {width: 297.0, height: 420.0};
+        assert_eq!(duplex(sheet, layout, BookletFold::Side, FoldScheme::Folio), "two-sided-long-edge");
+        assert_eq!(duplex(sheet, None, BookletFold::Side, FoldScheme::Folio), "two-sided-short-edge");
+    }
+
+    #[test]
+    fn test_sheet_formes_octavo() {
+        // a whole octavo sheet to each side, eight pages, the top row head down
+        let mut document = DocumentInfo::with_signature_size(1, 16, 16).unwrap();
+        document.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        let formes = sheet_formes(&document, None, BookletFold::Side);
+        assert_eq!(formes.len(), 2);
+        assert_eq!(pages(&formes[0]), vec![vec![5, 12, 9, 8], vec![4, 13, 16, 1]]);
+        assert_eq!(pages(&formes[1]), vec![vec![7, 10, 11, 6], vec![2, 15, 14, 3]]);
+        assert!(formes[1].rows[0][0].head_down && !formes[1].rows[1][0].head_down);
+        let sheet = PageSize {width: 592.0, height: 420.0};
+        assert_eq!(duplex(sheet, None, BookletFold::Side, FoldScheme::Octavo), "two-sided-short-edge");
+    }
+}
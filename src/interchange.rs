@@ -0,0 +1,166 @@
+// The imposition interchange format: a full description of every sheet of
+// the job, for RIP plugins and other third-party tools. Unlike the plan
+// summary this format is versioned, and a version's layout never changes
+// once released; anything incompatible goes into a new version. The
+// formal definition lives in schema/imposition-v1.schema.json.
+//
+// Version 1:
+//   schema       always "rust-signatures/imposition"
+//   version      always 1
+//   unit         always "mm"
+//   page_size    {width, height} of one finished page
+//   sheet_size   {width, height} of one printed sheet side
+//   sheets       one entry per physical sheet, in printing order:
+//     signature  key of the signature the sheet belongs to
+//     sheet      1-based position of the sheet within its signature
+//     sides      the front then the back of the sheet:
+//       side     "front" or "back"
+//       cells    one entry per page position on the sheet side:
+//         page      page number, or null for a blank; counted from 1, or
+//                   from 0 for plans made with --zero-indexed
+//         x, y      offset of the cell from the top left of the sheet side
+//         width, height
+//         rotation  clockwise rotation of the page in degrees (0, 90, 180, 270)
+//       marks    printer's marks on the sheet side:
+//         kind      "fold" or "signature"
+//         fold marks have x1, y1, x2, y2 for the fold line
+//         signature marks have the text and the page they are printed on
+
+use crate::imposition::{self, PageSize, SheetForme, Side};
+use crate::json::Json;
+use crate::DocumentInfo;
+
+// Constants
+pub const SCHEMA_NAME: &str = "rust-signatures/imposition";
+pub const SCHEMA_VERSION: i64 = 1;
+
+
+// Work
+pub fn imposition_json(document: &DocumentInfo, page_size: PageSize) -> String {
+    let formes = imposition::impose_formes(document);
+    let sheets = formes
+        .chunks(2) // front and back of each sheet
+        .map(|sheet| {
+            Json::object(vec![
+                ("signature", Json::string(&sheet[0].signature_key)),
+                ("sheet", Json::Int(sheet[0].sheet as i64 + 1)),
+                ("sides", Json::Array(sheet.iter().map(|forme| side_json(document, forme, page_size)).collect())),
+            ])
+        })
+        .collect();
+    // as many pages across and down as the formes have
+    let (rows, columns) = formes.first().map_or((1, 2), |forme| (forme.rows.len(), forme.rows[0].len()));
+    let mut fields = vec![
+        ("schema", Json::string(SCHEMA_NAME)),
+        ("version", Json::Int(SCHEMA_VERSION)),
+        ("unit", Json::string("mm")),
+        ("page_size", size_json(page_size.width, page_size.height)),
+        ("sheet_size", size_json(page_size.width * columns as f64, page_size.height * rows as f64)),
+        ("sheets", Json::Array(sheets)),
+    ];
+    if !document.job().is_empty() {
+        fields.insert(2, ("job", document.job().to_json()));
+    }
+    Json::object(fields).to_pretty_string()
+}
+
+fn side_json(document: &DocumentInfo, forme: &SheetForme, page_size: PageSize) -> Json {
+    let (rows, columns) = (forme.rows.len(), forme.rows[0].len());
+    let (width, height) = (page_size.width * columns as f64, page_size.height * rows as f64);
+    let cells = forme
+        .rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells.iter().enumerate().map(move |(column, cell)| {
+                let (x, y) = (page_size.width * column as f64, page_size.height * row as f64);
+                cell_json(cell.page.map(|page| document.shown_page(page)), x, y, cell.head_down, page_size)
+            })
+        })
+        .collect();
+    // a fold between every two columns and every two rows
+    let mut marks: Vec<Json> = (1..columns)
+        .map(|column| fold_json(page_size.width * column as f64, 0.0, page_size.width * column as f64, height))
+        .chain((1..rows).map(|row| fold_json(0.0, page_size.height * row as f64, width, page_size.height * row as f64)))
+        .collect();
+    if forme.sheet == 0 && forme.side == Side::Front {
+        // the signature mark goes on the first page of the gathering
+        let first_page = forme.rows.iter().flatten().filter_map(|cell| cell.page).min();
+        marks.push(Json::object(vec![
+            ("kind", Json::string("signature")),
+            ("text", Json::string(&forme.signature_key)),
+            ("page", Json::page(first_page.map(|page| document.shown_page(page)))),
+        ]));
+    }
+    Json::object(vec![
+        (
+            "side",
+            Json::string(match forme.side {
+                Side::Front => "front",
+                Side::Back => "back",
+            }),
+        ),
+        ("cells", Json::Array(cells)),
+        ("marks", Json::Array(marks)),
+    ])
+}
+
+fn cell_json(page: Option<u32>, x: f64, y: f64, head_down: bool, page_size: PageSize) -> Json {
+    Json::object(vec![
+        ("page", Json::page(page)),
+        ("x", Json::Float(x)),
+        ("y", Json::Float(y)),
+        ("width", Json::Float(page_size.width)),
+        ("height", Json::Float(page_size.height)),
+        ("rotation", Json::Int(if head_down { 180 } else { 0 })),
+    ])
+}
+
+fn fold_json(x1: f64, y1: f64, x2: f64, y2: f64) -> Json {
+    Json::object(vec![
+        ("kind", Json::string("fold")),
+        ("x1", Json::Float(x1)),
+        ("y1", Json::Float(y1)),
+        ("x2", Json::Float(x2)),
+        ("y2", Json::Float(y2)),
+    ])
+}
+
+fn size_json(width: f64, height: f64) -> Json {
+    Json::object(vec![("width", Json::Float(width)), ("height", Json::Float(height))])
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imposition::FoldScheme;
+
+    #[test]
+    fn test_imposition_json() {
+        let document = DocumentInfo::new(1, 3).unwrap();
+        let json = imposition_json(&document, PageSize::default());
+        assert!(json.starts_with(
+            "{\n  \"schema\": \"rust-signatures/imposition\",\n  \"version\": 1,\n  \"unit\": \"mm\",\n"
+        ));
+        assert!(json.contains("  \"sheet_size\": {\n    \"width\": 296.0,\n    \"height\": 210.0\n  },\n"));
+        assert!(json.contains("\"signature\": \"A\",\n      \"sheet\": 1,\n"));
+        // blank fourth page on the front, signature mark on page 1
+        assert!(json.contains("\"page\": null,\n              \"x\": 0.0,"));
+        assert!(json.contains("\"kind\": \"signature\",\n              \"text\": \"A\",\n              \"page\": 1\n"));
+        assert_eq!(json.matches("\"kind\": \"fold\"").count(), 2);
+
+        // an octavo sheet is one forme of four pages across and two down,
+        // the top row head down, with a fold marked between every two
+        // columns and rows
+        let mut document = DocumentInfo::with_signature_size(1, 16, 16).unwrap();
+        document.set_fold_scheme(FoldScheme::Octavo).unwrap();
+        let json = imposition_json(&document, PageSize::default());
+        assert!(json.contains("  \"sheet_size\": {\n    \"width\": 592.0,\n    \"height\": 420.0\n  },\n"));
+        assert_eq!(json.matches("\"signature\": \"A\"").count(), 1);
+        assert_eq!(json.matches("\"x\": ").count(), 16);
+        assert_eq!(json.matches("\"rotation\": 180").count(), 8);
+        assert_eq!(json.matches("\"kind\": \"fold\"").count(), 8);
+        assert!(json.contains("\"kind\": \"signature\",\n              \"text\": \"A\",\n              \"page\": 1\n"));
+    }
+}
@@ -1,22 +1,838 @@
 use std::env;
-use std::process;
+use std::error::Error;
+use std::fs;
+use std::process::{self, Command};
 
+use rust_signatures::analyse;
+use rust_signatures::batch::{self, BatchState, Job};
+use rust_signatures::binding;
+use rust_signatures::catchwords;
+use rust_signatures::chase::{self, ChaseFormat};
+use rust_signatures::collation;
+use rust_signatures::colour::{self, ColourPages};
+use rust_signatures::commands;
+use rust_signatures::compare::Comparison;
+use rust_signatures::config::PlanConfig;
+use rust_signatures::conservation;
+use rust_signatures::constraints::{self, Constraint};
+use rust_signatures::contact;
+use rust_signatures::costs::{self, Costs};
+use rust_signatures::cutting::{self, CuttingPlan};
+use rust_signatures::deimpose::{self, PdfMetadata};
+use rust_signatures::dot;
+use rust_signatures::edition::{self, SlipLayout};
+use rust_signatures::enclosure;
+use rust_signatures::folds;
+use rust_signatures::errors::ErrorReport;
+use rust_signatures::explain;
+use rust_signatures::impose;
+use rust_signatures::imposition::{self, FoldScheme, PageSize};
+use rust_signatures::interchange;
+use rust_signatures::job::JobInfo;
+use rust_signatures::labels::{self, LabelScheme};
+use rust_signatures::materials;
+use rust_signatures::multi_up;
+use rust_signatures::notepad::{self, Notepad};
+use rust_signatures::options::{Format, Options, Subcommand};
+use rust_signatures::pad;
+use rust_signatures::page_count;
+use rust_signatures::pdfx;
+use rust_signatures::press::{self, Stocks};
+use rust_signatures::presets;
+use rust_signatures::pressing;
+use rust_signatures::printing;
+use rust_signatures::scans;
+use rust_signatures::scribus;
+use rust_signatures::spine;
+use rust_signatures::split;
+use rust_signatures::stamp::{self, RunningHeads};
+use rust_signatures::templates;
+use rust_signatures::verify;
+use rust_signatures::volume::{self, Volume};
+use rust_signatures::watch;
+use rust_signatures::web;
+use rust_signatures::zine;
 use rust_signatures::DocumentInfo;
 
 
 fn main() {
     let all_args: Vec<String> = env::args().collect();
-    let (first_number, second_number) = rust_signatures::parse_args(all_args).unwrap_or_else(|err| {
-        eprintln!("Problem parsing arguments: {}", err);
+    let json_errors = rust_signatures::options::wants_json_errors(&all_args);
+    let (positional_args, options) = rust_signatures::options::parse_options(all_args.clone()).unwrap_or_else(|err| {
+        fail(ErrorReport::from_error("Problem parsing arguments", err.as_ref()), json_errors);
+    });
+    if options.watch {
+        watch(&all_args, &options);
+    }
+    match options.subcommand {
+        Subcommand::Analyse => analyse(&positional_args, &options),
+        Subcommand::Chase => chase(&positional_args, &options),
+        Subcommand::Batch => batch(&positional_args, &options),
+        Subcommand::Folds => fold_steps(&positional_args, &options),
+        Subcommand::Zine => zine(&options),
+        Subcommand::Notepad => notepad(&positional_args, &options),
+        Subcommand::Plan
+        | Subcommand::Deimpose
+        | Subcommand::Explain
+        | Subcommand::Scans
+        | Subcommand::Split
+        | Subcommand::Pad
+        | Subcommand::Impose
+        | Subcommand::Contact
+        | Subcommand::Verify => {
+            let files: Vec<String> = positional_args.iter().skip(3).cloned().collect();
+            let output = positional_args.get(1).cloned();
+            let page_range = if let Some(gatherings) = read_collation(&options) {
+                Ok((1, gatherings.iter().sum()))
+            } else if let Some(volume) = read_volume(&options) {
+                Ok((1, volume.num_pages()))
+            } else if let Some(path) = &options.input_pdf {
+                page_count::page_count(path).map(|count| (1, count))
+            } else if options.zero_indexed {
+                rust_signatures::parse_zero_indexed_args(positional_args)
+            } else {
+                rust_signatures::parse_args(positional_args)
+            };
+            let (first_number, second_number) = page_range.unwrap_or_else(|err| {
+                fail_with("Problem parsing arguments", err.as_ref(), &options);
+            });
+            match options.subcommand {
+                Subcommand::Deimpose => deimpose(first_number, second_number, &files, &options),
+                Subcommand::Explain => explain(first_number, second_number, &options),
+                Subcommand::Scans => scans(first_number, second_number, &options),
+                Subcommand::Split => split(first_number, second_number, &options),
+                Subcommand::Pad => pad(first_number, second_number, &options),
+                Subcommand::Impose => impose(first_number, second_number, output.as_deref(), &options),
+                Subcommand::Contact => contact_sheets(first_number, second_number, &options),
+                Subcommand::Verify => verify(first_number, second_number, &files, &options),
+                _ => plan(first_number, second_number, &options),
+            }
+        },
+    }
+}
+
+fn plan(first_number: u32, second_number: u32, options: &Options) {
+    if let Some(sizes) = &options.compare {
+        let mut comparison = Comparison::new(first_number, second_number, sizes).unwrap_or_else(|err| {
+            fail_with("Problem planning", err.as_ref(), options);
+        });
+        comparison.set_zero_indexed(options.zero_indexed);
+        comparison.display();
+        return;
+    }
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    document_info.set_job(job_info(options));
+    if let Some(path) = &options.template {
+        let report = templates::render_file(&document_info, path).unwrap_or_else(|err| {
+            fail_with(&format!("Problem rendering {}", path), err.as_ref(), options);
+        });
+        print!("{}", report);
+        return;
+    }
+    match options.format {
+        Format::Text => {
+            document_info.display();
+            if let Some(preset) = options.preset {
+                presets::display_preset(&document_info, preset);
+            }
+            if let Some(strategy) = options.padding {
+                pad::display_padding(&document_info, &pad::padding(&document_info, strategy)).unwrap_or_else(|err| {
+                    fail_with("Problem padding the plan", err.as_ref(), options);
+                });
+            }
+            if let Some(layout) = config.layout().filter(|layout| layout.num_strips() > 1) {
+                let press_sheets = multi_up::impose_multi_up(&document_info, layout).unwrap_or_else(|err| {
+                    fail_with("Problem planning the press sheets", &err, options);
+                });
+                multi_up::display_multi_up(&document_info, layout, &press_sheets);
+            }
+            if let Some(order) = options.gathering_order {
+                binding::display_binding_order(&document_info, "Gathering", order);
+            }
+            if let Some(order) = options.sewing_order {
+                binding::display_binding_order(&document_info, "Sewing", order);
+            }
+            if let Some(convention) = options.catchwords {
+                catchwords::display_catchwords(&document_info, &catchwords::catchwords(&document_info, convention));
+            }
+            if options.direction_line {
+                let lines = stamp::direction_lines(&document_info, options.volume.as_deref());
+                stamp::display_direction_lines(&document_info, &lines);
+            }
+            if let Some(signing) = options.sheet_signing {
+                stamp::display_sheet_marks(&document_info, &stamp::sheet_marks(&document_info, signing));
+            }
+            let runs = press::run_order(&document_info, &read_stocks(options));
+            if options.stocks.is_some() {
+                press::display_run_order(&document_info, &runs);
+            }
+            let copies = options.copies.unwrap_or(1);
+            let pulls = press::inventory(&runs, copies, options.spoilage);
+            if options.copies.is_some() || options.spoilage > 0 {
+                press::display_inventory(&pulls, copies);
+            }
+            if let Some(cuts) = cutting_plan(&config, options) {
+                let strips = config.layout().map_or(1, |layout| layout.num_strips());
+                cutting::display_cutting_plan(&cuts, &pulls, strips);
+            }
+            if let Some(web) = options.web {
+                let plan = web::web_plan(&document_info, web, config.sheet_size_mm()).unwrap_or_else(|err| {
+                    fail_with("Problem planning the web", err.as_ref(), options);
+                });
+                web::display_web_plan(&document_info, &plan, copies, options.spoilage);
+            }
+            if let Some(spine_config) = &options.spine {
+                if spine_config.caliper <= 0.0 {
+                    let message = "Problem parsing arguments: the spine needs the --caliper of the paper, in millimetres.";
+                    fail(ErrorReport::new("missing_argument", message, None), false);
+                }
+                let spine = spine::spine(&document_info, spine_config, config.page_size_mm()).unwrap_or_else(|err| {
+                    fail_with("Problem working out the spine", err.as_ref(), options);
+                });
+                spine::display_spine(&document_info, spine_config, &spine);
+                let pieces =
+                    materials::materials(&document_info, &spine, spine_config, options.sewing, config.page_size_mm());
+                materials::display_materials("Materials", &pieces, copies);
+                materials::display_materials("Cutting list", &materials::cutting_list(&spine, config.page_size_mm()), copies);
+                if let Some(kind) = options.enclosure {
+                    let book = enclosure::book_size(&spine, spine_config, config.page_size_mm());
+                    println!("Finished book: {:.1}x{:.1}x{:.1}mm", book.width, book.height, book.thickness);
+                    let panels =
+                        enclosure::enclosure_panels(kind, book, options.clearance, spine_config.board_thickness);
+                    materials::display_materials(kind.name(), &panels, copies);
+                }
+            }
+            if let Some(capacity) = options.press_capacity {
+                let hours = options.pressing_hours.unwrap_or(pressing::DEFAULT_PRESSING_HOURS);
+                pressing::display_pressing_batches(&pressing::pressing_batches(copies, capacity, hours), capacity, hours);
+            }
+            if let Some(path) = &options.edition_slips {
+                write_edition_slips(path, config.sheet_size_mm(), options);
+            }
+            let colour = read_colour_pages(first_number, options).map(|spans| colour::colour_report(&document_info, &spans));
+            if let Some(report) = &colour {
+                colour::display_colour_report(&document_info, report, copies);
+            }
+            if let Some(costs) = read_costs(options) {
+                let mut report = costs::cost_report(&runs, &pulls, &costs, copies);
+                let colour_sides = colour.as_ref().map_or(0, |colour| colour.colour_sides());
+                report.lines.extend(costs::colour_cost_line(&costs, colour_sides, copies));
+                costs::display_cost_report(&report, &costs);
+            }
+            display_stamps(&document_info, options);
+            if let Some(volume) = read_volume(options) {
+                volume::display_part_spans(&document_info, &volume, &volume::part_spans(&document_info, &volume));
+            }
+        },
+        Format::Scribus => {
+            let script = scribus::scribus_script(&document_info, config.page_size_mm()).unwrap_or_else(|err| {
+                fail_with("Problem writing the Scribus script", &err, options);
+            });
+            print!("{}", script);
+        },
+        Format::Commands => {
+            let commands = commands::equivalent_commands(&document_info).unwrap_or_else(|err| {
+                fail_with("Problem writing the commands", err.as_ref(), options);
+            });
+            print!("{}", commands);
+        },
+        Format::Json => print!("{}", document_info.to_json()),
+        Format::ImpositionJson => print!("{}", interchange::imposition_json(&document_info, config.page_size_mm())),
+        Format::Conjugates => imposition::display_conjugates(&document_info),
+        Format::Formes => imposition::display_formes(&document_info, config.booklet_fold),
+        Format::Plain => print!("{}", document_info.plain_text()),
+        Format::PlainV1 => print!("{}", document_info.plain_v1()),
+        Format::Dot => print!("{}", dot::dot_graph(&document_info)),
+        Format::Csv => print!("{}", document_info.to_csv()),
+        Format::ConservationCsv => print!("{}", conservation::conservation_csv(&document_info)),
+        Format::ConservationHtml => print!("{}", conservation::conservation_html(&document_info)),
+        Format::Lp => {
+            let runs = printing::print_runs(&document_info, &read_stocks(options));
+            print!("{}", printing::print_script(&document_info, &runs, config.booklet_fold));
+        },
+        Format::CuttingSvg => match cutting_plan(&config, options) {
+            Some(cuts) => {
+                let job = document_info.job().header("");
+                if !job.is_empty() {
+                    println!("<!--\n{}-->", job.replace("--", "\u{2013}"));
+                }
+                print!("{}", cutting::cutting_svg(&cuts, options.grain));
+            },
+            None => {
+                let message = "Problem parsing arguments: --format cutting-svg needs the --parent-sheet to cut from.";
+                fail(ErrorReport::new("missing_argument", message, None), false);
+            },
+        },
+    }
+}
+
+fn explain(first_number: u32, second_number: u32, options: &Options) {
+    // rust-signatures explain FIRST LAST, with the same options as a plan
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    print!("{}", explain::explain(&document_info, config.layout()));
+}
+
+fn scans(first_number: u32, second_number: u32, options: &Options) {
+    // rust-signatures scans FIRST LAST, or with --format commands for a
+    // script doing the reordering, or with --check SCANS to check the scans
+    // taken so far
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    // scans are of sheet sides two pages wide
+    let sides = imposition::impose(&document_info).unwrap_or_else(|err| {
+        fail_with("Problem planning the scans", &err, options);
+    });
+    if let Some(path) = &options.check_scans {
+        let check = scans::check_scans(&document_info, path).unwrap_or_else(|err| {
+            fail_with(&format!("Problem checking {}", path), err.as_ref(), options);
+        });
+        let files = if page_count::is_pdf_path(path) { Vec::new() } else { scans::read_scans(path).unwrap_or_default() };
+        scans::display_scan_check(&document_info, &sides, &check, &files);
+        if !check.is_ok() {
+            process::exit(1);
+        }
+        return;
+    }
+    let map = scans::scan_map(&document_info).unwrap_or_else(|err| {
+        fail_with("Problem planning the scans", &err, options);
+    });
+    match options.format {
+        Format::Commands => print!("{}", scans::reorder_script(&document_info, &map)),
+        _ => scans::display_scan_map(&document_info, &sides, &map),
+    }
+}
+
+fn split(first_number: u32, second_number: u32, options: &Options) {
+    // rust-signatures split BOOK.pdf, writing BOOK-A.pdf and so on, and a
+    // manifest of them in BOOK-manifest.txt
+    let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+        let message = "Problem parsing arguments: split needs the PDF to split.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    let files = split::split_files(&document_info, input);
+    if options.format == Format::Commands {
+        for command in split::split_commands(input, &files) {
+            println!("{}", commands::shell_line(&command));
+        }
+        return;
+    }
+    split::split(input, &files).unwrap_or_else(|err| {
+        fail_with(&format!("Problem splitting {}", input), err.as_ref(), options);
+    });
+    // marked before the manifest is hashed, so it matches what's sent
+    let reports = if options.pdfx {
+        pdfx::convert(&document_info, &files).unwrap_or_else(|err| {
+            fail_with("Problem making the PDF/X files", err.as_ref(), options);
+        })
+    } else {
+        Vec::new()
+    };
+    let manifest = split::manifest(&document_info, &files).unwrap_or_else(|err| {
+        fail_with(&format!("Problem splitting {}", input), err.as_ref(), options);
+    });
+    fs::write(split::manifest_path(input), &manifest).unwrap_or_else(|err| {
+        fail_with("Problem writing the manifest", &err, options);
+    });
+    print!("{}", manifest);
+    if options.pdfx {
+        pdfx::display_pdfx_reports(&reports);
+    }
+}
+
+fn pad(first_number: u32, second_number: u32, options: &Options) {
+    // rust-signatures pad BOOK.pdf, writing BOOK-padded.pdf with the plan's
+    // blank pages, or copies of --filler-page, after the last page
+    let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+        let message = "Problem parsing arguments: pad needs the PDF to pad.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let config = plan_config(first_number, second_number, options);
+    let document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    let output = pad::padded_path(input);
+    if options.format == Format::Commands {
+        let command = pad::pad_command(&document_info, input, &output, &config.blank_policy).unwrap_or_else(|err| {
+            fail_with(&format!("Problem padding {}", input), err.as_ref(), options);
+        });
+        println!("{}", commands::shell_line(&command));
+        return;
+    }
+    pad::pad(&document_info, input, &output, &config.blank_policy).unwrap_or_else(|err| {
+        fail_with(&format!("Problem padding {}", input), err.as_ref(), options);
+    });
+    println!("Wrote {} with {} padding pages", output, document_info.num_blank_pages());
+}
+
+fn impose(first_number: u32, second_number: u32, output: Option<&str>, options: &Options) {
+    // rust-signatures impose BOOK.pdf [OUTPUT.pdf], writing the sheet sides
+    // to print, to BOOK-imposed.pdf if no output is given
+    let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+        let message = "Problem parsing arguments: impose needs the PDF to impose.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let output = output.map_or_else(|| impose::imposed_path(input), str::to_string);
+    let config = plan_config(first_number, second_number, options);
+    let document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    let formes = impose::sheet_formes(&document_info, config.layout(), config.booklet_fold);
+    let sheet = config.press_sheet_size_mm();
+    let offset = options.back_offset;
+    impose::impose_pdf(input, &output, sheet, &formes, offset).unwrap_or_else(|err| {
+        fail_with(&format!("Problem imposing {}", input), err.as_ref(), options);
+    });
+    println!("Wrote {} with {} sheet sides of {:.0}x{:.0}mm", output, formes.len(), sheet.width, sheet.height);
+    if !offset.is_zero() {
+        println!("Backs shifted {:.1}mm left and {:.1}mm up for the printer's offset", offset.right, offset.down);
+    }
+    println!("Print both sides with sides={}", impose::duplex(sheet, config.layout(), config.booklet_fold, config.fold_scheme));
+}
+
+fn contact_sheets(first_number: u32, second_number: u32, options: &Options) {
+    // rust-signatures contact BOOK.pdf, writing BOOK-A-contact.pdf and so
+    // on, a page of thumbnails for each signature
+    let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+        let message = "Problem parsing arguments: contact needs the PDF to draw the pages of.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    let sheets = contact::contact_sheets(&document_info, input);
+    contact::write_contact_sheets(input, &sheets).unwrap_or_else(|err| {
+        fail_with(&format!("Problem drawing the contact sheets for {}", input), err.as_ref(), options);
+    });
+    for sheet in &sheets {
+        println!("{} ({})", sheet.title, sheet.path);
+    }
+}
+
+fn plan_config(first_number: u32, second_number: u32, options: &Options) -> PlanConfig {
+    let mut config = PlanConfig::new(first_number, second_number);
+    config.label_scheme = label_scheme(options);
+    config.page_size = options.page_size;
+    config.units = options.units;
+    config.blank_policy = options.blank_policy;
+    config.booklet_fold = options.booklet_fold;
+    config.one_sided = options.one_sided;
+    config.reading_direction = options.reading_direction;
+    config.fold_scheme = options.fold_scheme.unwrap_or_default();
+    config.balance = options.balance;
+    config.pages_per_signature = pages_per_signature(first_number, second_number, options);
+    if let Some(gatherings) = read_collation(options) {
+        config.pages_per_signature = gatherings.iter().copied().max().unwrap_or(config.pages_per_signature);
+        config.gatherings = gatherings;
+    }
+    if let Some(pages_per_side) = options.pages_per_side {
+        config.pages_per_sheet = pages_per_side.saturating_mul(2);
+    }
+    config
+}
+
+fn pages_per_signature(first_number: u32, second_number: u32, options: &Options) -> u32 {
+    // The size meeting any constraints given, or else the usual size, a
+    // sheet apiece when folded more than once. A --signature-size given as
+    // well has to meet them too.
+    let mut constraints = options.constraints.clone();
+    if options.segregate_colour {
+        let spans = read_colour_pages(first_number, options).unwrap_or_default();
+        constraints.extend(colour::segregating_constraints(&spans, first_number, second_number));
+    }
+    if let Some(size) = options.signature_size {
+        if constraints.is_empty() {
+            return size;
+        }
+        constraints.extend([Constraint::MinSize(size), Constraint::MaxSize(size)].iter());
+    }
+    if constraints.is_empty() {
+        return options.fold_scheme.unwrap_or_default().default_signature_size();
+    }
+    constraints::choose_size(first_number, second_number, &constraints).unwrap_or_else(|err| {
+        fail_with("Problem choosing a signature size", err.as_ref(), options);
+    })
+}
+
+fn analyse(positional_args: &[String], options: &Options) {
+    // rust-signatures analyse CELLS.txt
+    let path = positional_args.get(1).unwrap_or_else(|| {
+        let message = "Problem parsing arguments: analyse needs a file listing the pages on each sheet side.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    // the cells file has two pages to a side, so only folios are tried
+    if let Some(fold_scheme) = options.fold_scheme.filter(|&fold_scheme| fold_scheme != FoldScheme::Folio) {
+        fail_with("Problem parsing arguments", &imposition::TwoUpError {fold_scheme}, options);
+    }
+    let analysis = analyse::read_cells(path).and_then(|cells| analyse::analyse(&cells)).unwrap_or_else(|err| {
+        fail_with(&format!("Problem analysing {}", path), err.as_ref(), options);
+    });
+    analysis.display();
+}
+
+fn batch(positional_args: &[String], options: &Options) {
+    // rust-signatures batch JOBS.txt, planning each job and then adding
+    // them all up; with --resume, only the jobs not done last time
+    let path = positional_args.get(1).unwrap_or_else(|| {
+        let message = "Problem parsing arguments: batch needs a file listing the jobs to plan.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let jobs = batch::read_jobs(path).unwrap_or_else(|err| {
+        fail_with(&format!("Problem reading {}", path), err.as_ref(), options);
+    });
+    let mut state = BatchState::start(path, options.resume).unwrap_or_else(|err| {
+        fail_with("Problem starting the batch state file", err.as_ref(), options);
+    });
+    let (done, jobs): (Vec<Job>, Vec<Job>) = jobs.into_iter().partition(|job| state.is_done(job));
+    for job in &done {
+        println!("Job: {} (done in an earlier run)", job.name);
+    }
+    let stocks = read_stocks(options);
+    let costs = read_costs(options);
+    let copies = options.copies.unwrap_or(1);
+    let mut total_cost: u64 = 0;
+    let mut plans: Vec<(Job, DocumentInfo)> = Vec::new();
+    for (job, plan) in batch::plan_jobs(jobs, options.pipeline) {
+        let mut document_info = plan.map_or_else(|| job.plan(), Ok).unwrap_or_else(|err| {
+            fail_with(&format!("Problem planning {}", job.name), err.as_ref(), options);
+        });
+        relabel(&mut document_info, options);
+        println!("Job: {}", job.name);
+        document_info.display();
+        if let Some(costs) = &costs {
+            let runs = press::run_order(&document_info, &stocks);
+            let pulls = press::inventory(&runs, copies, options.spoilage);
+            let per_run = costs::cost_report(&runs, &pulls, costs, copies).per_run();
+            total_cost = total_cost.saturating_add(per_run);
+        }
+        state.mark_done(&job).unwrap_or_else(|err| {
+            fail_with("Problem updating the batch state file", err.as_ref(), options);
+        });
+        plans.push((job, document_info));
+    }
+    batch::display_summary(&batch::summarise(&plans, &stocks));
+    if let Some(costs) = &costs {
+        println!("Total estimated cost for {} of each job: {}", copies, costs.format_price(total_cost));
+    }
+}
+
+fn watch(all_args: &[String], options: &Options) -> ! {
+    // Run the same command, less --watch, every time one of its input files
+    // changes. Each run is a separate process, so a problem with one version
+    // of the inputs is reported without stopping the watching.
+    let paths = options.input_files();
+    watch::check_watchable(&paths).unwrap_or_else(|err| {
+        fail_with("Problem parsing arguments", err.as_ref(), options);
+    });
+    let binary = env::current_exe().unwrap_or_else(|err| {
+        fail_with("Problem starting to watch", &err, options);
+    });
+    let args: Vec<&String> = all_args[1..].iter().filter(|arg| *arg != "--watch").collect();
+    loop {
+        if let Err(err) = Command::new(&binary).args(&args).status() {
+            fail_with("Problem planning", &err, options);
+        }
+        let changed = watch::wait_for_change(&paths);
+        eprintln!("{} changed, planning again.", changed.join(", "));
+    }
+}
+
+fn chase(positional_args: &[String], options: &Options) {
+    // rust-signatures chase [folio|quarto|octavo], all three if none is given
+    let formats = match positional_args.get(1) {
+        Some(name) => vec![ChaseFormat::parse(name).unwrap_or_else(|err| {
+            fail_with("Problem parsing arguments", err.as_ref(), options);
+        })],
+        None => vec![ChaseFormat::Folio, ChaseFormat::Quarto, ChaseFormat::Octavo],
+    };
+    for format in formats {
+        print!("{}", chase::chase_diagram(format));
+    }
+}
+
+fn fold_steps(positional_args: &[String], options: &Options) {
+    // rust-signatures folds [folio|quarto|octavo] > folds.svg
+    let name = positional_args.get(1).unwrap_or_else(|| {
+        let message = "Problem parsing arguments: folds needs a format to fold: folio, quarto or octavo.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let format = ChaseFormat::parse(name).unwrap_or_else(|err| {
+        fail_with("Problem parsing arguments", err.as_ref(), options);
+    });
+    print!("{}", folds::fold_steps_svg(format));
+}
+
+fn zine(options: &Options) {
+    // rust-signatures zine ZINE.pdf, writing ZINE-zine.pdf laid out on its
+    // sheet, with how to fold it
+    let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+        let message = "Problem parsing arguments: zine needs the PDF of the zine's pages.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let num_pages = page_count::page_count(input).and_then(zine::check_pages).unwrap_or_else(|err| {
+        fail_with(&format!("Problem reading {}", input), err.as_ref(), options);
+    });
+    let output = zine::zine_path(input);
+    zine::zine(options.zine_format, input, &output).unwrap_or_else(|err| {
+        fail_with(&format!("Problem laying out {}", input), err.as_ref(), options);
+    });
+    println!("Wrote {} with the zine's {} pages", output, num_pages);
+    zine::display_zine(options.zine_format, &output);
+}
+
+fn notepad(positional_args: &[String], options: &Options) {
+    // rust-signatures notepad LEAVES, for pads and tear-off calendars padded
+    // at the head: the sheets to cut, the paper, the edge and the costs
+    let leaves = positional_args.get(1).map(|leaves| leaves.parse::<u32>()).unwrap_or_else(|| {
+        let message = "Problem parsing arguments: notepad needs the number of leaves in each pad.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let leaves = leaves.unwrap_or_else(|err| {
+        fail_with("Problem parsing arguments", &err, options);
+    });
+    let notepad = Notepad {leaves, leaves_per_sheet: options.pages_per_side.unwrap_or(notepad::DEFAULT_LEAVES_PER_SHEET)};
+    let page_size = PageSize {
+        width: options.units.to_millimetres(options.page_size.width),
+        height: options.units.to_millimetres(options.page_size.height),
+    };
+    notepad::display_notepad(notepad);
+    let runs = notepad::stock_runs(notepad, &read_stocks(options));
+    let copies = options.copies.unwrap_or(1);
+    let pulls = press::inventory(&runs, copies, options.spoilage);
+    press::display_inventory(&pulls, copies);
+    let edge = notepad::padded_edge(notepad, options.spine.as_ref().map(|spine| spine.caliper), page_size);
+    materials::display_materials("Materials", &notepad::notepad_materials(edge, page_size), copies);
+    if let Some(costs) = read_costs(options) {
+        costs::display_cost_report(&costs::cost_report(&runs, &pulls, &costs, copies), &costs);
+    }
+}
+
+fn deimpose(first_number: u32, second_number: u32, files: &[String], options: &Options) {
+    // rust-signatures deimpose FIRST LAST IMPOSED.pdf OUTPUT.pdf
+    if files.len() < 2 {
+        let message = "Problem parsing arguments: deimpose needs the imposed PDF and a path for the output PDF.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    }
+    // the same plan the PDF was imposed from, or the pages come out of order
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    if options.format == Format::Commands {
+        let tool_commands = deimpose::deimpose_commands(&document_info, &files[0], &files[1]).unwrap_or_else(|err| {
+            fail_with("Problem planning", &err, options);
+        });
+        for command in tool_commands {
+            println!("{}", commands::shell_line(&command));
+        }
+        return;
+    }
+    document_info.set_job(job_info(options));
+    let metadata = PdfMetadata {
+        page_labels: deimpose::page_labels(&document_info, options.front_matter),
+        ..PdfMetadata::for_plan(&document_info)
+    };
+    deimpose::deimpose(&document_info, &files[0], &files[1], &metadata).unwrap_or_else(|err| {
+        fail_with(&format!("Problem de-imposing {}", files[0]), err.as_ref(), options);
+    });
+}
+
+fn verify(first_number: u32, second_number: u32, files: &[String], options: &Options) {
+    // rust-signatures verify FIRST LAST IMPOSED.pdf, reading the page
+    // numbers off a few of its sheet sides
+    if files.is_empty() {
+        let message = "Problem parsing arguments: verify needs the imposed PDF to read.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    }
+    let config = plan_config(first_number, second_number, options);
+    let mut document_info = config.plan().unwrap_or_else(|err| {
+        fail_with("Problem planning", err.as_ref(), options);
+    });
+    document_info.set_zero_indexed(options.zero_indexed);
+    let samples = options.sample_sides.unwrap_or(verify::DEFAULT_SAMPLE_SIDES);
+    let checks = verify::verify_imposed(&document_info, &files[0], samples).unwrap_or_else(|err| {
+        fail_with(&format!("Problem verifying {}", files[0]), err.as_ref(), options);
+    });
+    verify::display_verification(&checks);
+    if checks.iter().any(|check| !check.is_ok()) {
         process::exit(1);
+    }
+}
+
+fn write_edition_slips(path: &str, sheet: PageSize, options: &Options) {
+    // one slip for each of the --copies, numbered, on sheets the plan's size
+    let copies = options.copies.unwrap_or_else(|| {
+        let message = "Problem parsing arguments: --edition-slips needs the number of --copies in the edition.";
+        fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+    });
+    let layout = SlipLayout::for_slips_per_sheet(options.slips_per_sheet.unwrap_or(8));
+    fs::write(path, edition::slips_pdf(copies, layout, sheet)).unwrap_or_else(|err| {
+        fail_with(&format!("Problem writing {}", path), &err, options);
     });
-    let document_info = DocumentInfo::new(first_number, second_number);
-    document_info.display();
+    println!("Wrote {} with {} slips on {} sheets", path, copies, edition::num_slip_sheets(copies, layout));
+    println!("#####################################");
+}
+
+fn cutting_plan(config: &PlanConfig, options: &Options) -> Option<CuttingPlan> {
+    // how the press sheets are cut from the --parent-sheet, if one is given
+    options.parent_sheet.map(|parent| {
+        cutting::cutting_plan(parent, config.press_sheet_size_mm()).unwrap_or_else(|err| {
+            fail_with("Problem planning cuts", err.as_ref(), options);
+        })
+    })
+}
+
+fn job_info(options: &Options) -> JobInfo {
+    // the --project file's job details, with any given as flags instead
+    let project = match &options.project {
+        Some(path) => JobInfo::from_file(path).unwrap_or_else(|err| {
+            fail_with("Problem reading the project file", err.as_ref(), options);
+        }),
+        None => JobInfo::default(),
+    };
+    project.overridden_by(&options.job)
+}
+
+fn read_stocks(options: &Options) -> Stocks {
+    // the stocks file, or every signature on an unspecified stock
+    match &options.stocks {
+        Some(path) => Stocks::from_file(path).unwrap_or_else(|err| {
+            fail_with("Problem reading stocks", err.as_ref(), options);
+        }),
+        None => Stocks::default(),
+    }
+}
+
+fn read_collation(options: &Options) -> Option<Vec<u32>> {
+    // the pages in each gathering of the --collation formula, if one is given
+    options.collation.as_ref().map(|formula| {
+        collation::parse_collation_formula(formula, &label_scheme(options)).unwrap_or_else(|err| {
+            fail_with("Problem reading the collation formula", err.as_ref(), options);
+        })
+    })
+}
+
+fn read_colour_pages(first_number: u32, options: &Options) -> Option<Vec<(u32, u32)>> {
+    // the --colour-pages, counted as the plan counts pages, measured from
+    // the input PDF if asked to
+    let spans = match options.colour_pages.as_ref()? {
+        ColourPages::Spans(spans) if options.zero_indexed => spans.iter().map(|&(from, to)| (from + 1, to + 1)).collect(),
+        ColourPages::Spans(spans) => spans.clone(),
+        ColourPages::Detect => {
+            let input = options.input_pdf.as_ref().unwrap_or_else(|| {
+                let message = "Problem parsing arguments: --colour-pages auto needs the PDF to measure.";
+                fail(ErrorReport::new("missing_argument", message, None), options.format.is_json());
+            });
+            colour::detect_colour_pages(input, first_number).unwrap_or_else(|err| {
+                fail_with(&format!("Problem finding the colour pages of {}", input), err.as_ref(), options);
+            })
+        },
+    };
+    Some(spans)
+}
+
+fn read_volume(options: &Options) -> Option<Volume> {
+    // the --part documents bound together, if there are any, each starting
+    // a signature with --align-parts
+    if options.parts.is_empty() {
+        return None;
+    }
+    let volume = Volume::read(&options.parts).unwrap_or_else(|err| {
+        fail_with("Problem reading the parts", err.as_ref(), options);
+    });
+    if options.align_parts {
+        return Some(volume.aligned(pages_per_signature(1, volume.num_pages(), options)));
+    }
+    Some(volume)
+}
+
+fn read_costs(options: &Options) -> Option<Costs> {
+    options.costs.as_ref().map(|path| {
+        Costs::from_file(path).unwrap_or_else(|err| {
+            fail_with("Problem reading costs", err.as_ref(), options);
+        })
+    })
+}
+
+fn label_scheme(options: &Options) -> LabelScheme {
+    let mut scheme = options.labels.clone();
+    if let Some(path) = &options.labels_file {
+        scheme.sequence = labels::read_label_file(path).unwrap_or_else(|err| {
+            fail_with("Problem reading signature labels", err.as_ref(), options);
+        });
+    }
+    scheme
+}
+
+fn relabel(document_info: &mut DocumentInfo, options: &Options) {
+    document_info.relabel(&label_scheme(options)).unwrap_or_else(|err| {
+        fail_with("Problem labelling signatures", err.as_ref(), options);
+    });
+}
+
+fn display_stamps(document_info: &DocumentInfo, options: &Options) {
+    let mut stamps = Vec::new();
+    if let Some(style) = &options.folios {
+        stamps.extend(stamp::folio_stamps(document_info, style));
+    }
+    if let Some(path) = &options.running_heads {
+        let running_heads = RunningHeads::from_file(path).unwrap_or_else(|err| {
+            fail_with("Problem reading running heads", err.as_ref(), options);
+        });
+        stamps.extend(stamp::running_head_stamps(document_info, &running_heads));
+    }
+    if let Some(signing) = options.sheet_signing {
+        stamps.extend(stamp::sheet_mark_stamps(&stamp::sheet_marks(document_info, signing)));
+    }
+    if options.direction_line {
+        stamps.extend(stamp::direction_line_stamps(&stamp::direction_lines(document_info, options.volume.as_deref())));
+    }
+    if let Some(text) = &options.watermark {
+        stamps.extend(stamp::watermark_stamps(document_info, text));
+    }
+    if !stamps.is_empty() {
+        stamps.sort_by_key(|stamp| stamp.page);
+        stamp::display_stamps(document_info, &stamps);
+    }
+}
+
+fn fail_with(problem: &str, err: &(dyn Error + 'static), options: &Options) -> ! {
+    fail(ErrorReport::from_error(problem, err), options.format.is_json());
+}
+
+fn fail(report: ErrorReport, json: bool) -> ! {
+    // JSON output formats get JSON errors too, for the scripts reading them
+    if json {
+        eprint!("{}", report.to_json());
+    } else {
+        eprintln!("{}", report.message);
+    }
+    process::exit(1);
 }
 
 // Number of document pages to print: 60
 // Number of sheets to print: 15
 // Number of 4-sheet signatures to bind: 4
+// Sheet utilisation: 100.0% (0 blank pages, 0.0%)
+// Pages short of a full last signature: 4
 // #####################################
 // Signature A. First page: 1, last page: 16
 // Signature B. First page: 17, last page: 32
@@ -0,0 +1,207 @@
+// The pieces of material each copy is made up with besides its printed
+// sheets, sized from the spine, for cutting them out before the run: the
+// headbands at head and tail, and the mull and kraft paper lining the spine,
+// and the thread to sew the signatures with; and a cutting list for the
+// case: the boards, the spine strip, the cloth covering them and the
+// endpapers.
+
+use crate::imposition::PageSize;
+use crate::spine::{Spine, SpineConfig, FLAT_SPINE};
+use crate::DocumentInfo;
+
+// Constants
+// in millimetres; the mull reaches onto the boards each side, and both
+// linings stop short of the head and tail so they don't show
+const MULL_OVERLAP: f64 = 30.0;
+const MULL_INSET: f64 = 10.0;
+const KRAFT_INSET: f64 = 3.0;
+const HEADBANDS_PER_COPY: u32 = 2;
+// the thread taken out of the fold and back in at each sewing station
+const STATION_ALLOWANCE: f64 = 10.0;
+const MILLIMETRES_PER_METRE: f64 = 1000.0;
+// in millimetres; the boards stand out past the pages at head, tail and
+// fore-edge by the square, with a gap between each and the spine strip for
+// the joint, and the cloth turns in over them all
+pub(crate) const SQUARE: f64 = 3.0;
+const JOINT: f64 = 7.0;
+const TURN_IN: f64 = 15.0;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sewing {
+    pub stations: u32,
+    // extra thread for tying on, kettle stitches and breaks, in hundredths
+    // of a percent, e.g. 1000 for 10%
+    pub waste: u32,
+}
+
+impl Default for Sewing {
+    fn default() -> Sewing {
+        Sewing {stations: 5, waste: 1000}
+    }
+}
+
+// in millimetres: across the spine, and along it for sheet materials; for
+// materials bought by length, like thread, just the length
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    pub item: String,
+    pub per_copy: u32,
+    pub width: f64,
+    pub height: Option<f64>,
+}
+
+impl Piece {
+    fn size(&self) -> String {
+        match self.height {
+            Some(height) => format!("{:.1}x{:.1}mm", self.width, height),
+            None => format_length(self.width),
+        }
+    }
+
+    fn per_run(&self, copies: u32) -> String {
+        // lengths added up, pieces counted
+        let count = self.per_copy.saturating_mul(copies);
+        match self.height {
+            Some(_) => format!("{} {}", count, if count == 1 { "piece" } else { "pieces" }),
+            None => format_length(self.width * f64::from(count)),
+        }
+    }
+}
+
+
+// Work
+pub fn materials(
+    document: &DocumentInfo,
+    spine: &Spine,
+    config: &SpineConfig,
+    sewing: Sewing,
+    page_size: PageSize,
+) -> Vec<Piece> {
+    // The headbands are as long as the text block's spine is round; the
+    // linings are glued over the spine, the mull out onto the boards.
+    let text_arc = spine.width * config.rounding.unwrap_or(FLAT_SPINE);
+    vec![
+        Piece {item: "Thread".to_string(), per_copy: 1, width: thread_length(document, sewing, page_size), height: None},
+        Piece {item: "Headband".to_string(), per_copy: HEADBANDS_PER_COPY, width: text_arc, height: None},
+        Piece {
+            item: "Mull".to_string(),
+            per_copy: 1,
+            width: text_arc + 2.0 * MULL_OVERLAP,
+            height: Some(page_size.height - 2.0 * MULL_INSET),
+        },
+        Piece {
+            item: "Kraft lining".to_string(),
+            per_copy: 1,
+            width: text_arc,
+            height: Some(page_size.height - 2.0 * KRAFT_INSET),
+        },
+    ]
+}
+
+pub fn thread_length(document: &DocumentInfo, sewing: Sewing, page_size: PageSize) -> f64 {
+    // Each signature is sewn through its fold from head to tail, out and
+    // back in at every station, with the waste allowance on top.
+    let per_signature = page_size.height + f64::from(sewing.stations) * STATION_ALLOWANCE;
+    let sewn = f64::from(document.num_signatures()) * per_signature;
+    sewn * (1.0 + f64::from(sewing.waste) / 10_000.0)
+}
+
+pub fn cutting_list(spine: &Spine, page_size: PageSize) -> Vec<Piece> {
+    // The boards butt up against the shoulders, so backing narrows them; the
+    // spine strip is cut to go round the rounded spine.
+    let board = PageSize {width: page_size.width - spine.shoulder + SQUARE, height: page_size.height + 2.0 * SQUARE};
+    vec![
+        Piece {item: "Board".to_string(), per_copy: 2, width: board.width, height: Some(board.height)},
+        Piece {item: "Spine strip".to_string(), per_copy: 1, width: spine.spine_arc, height: Some(board.height)},
+        Piece {
+            item: "Cloth".to_string(),
+            per_copy: 1,
+            width: 2.0 * (board.width + JOINT + TURN_IN) + spine.spine_arc,
+            height: Some(board.height + 2.0 * TURN_IN),
+        },
+        // folded, one at each end
+        Piece {item: "Endpaper".to_string(), per_copy: 2, width: 2.0 * page_size.width, height: Some(page_size.height)},
+    ]
+}
+
+pub fn display_materials(heading: &str, pieces: &[Piece], copies: u32) {
+    println!("{}, per copy and for {} {}:", heading, copies, if copies == 1 { "copy" } else { "copies" });
+    for piece in pieces {
+        let per_copy = if piece.per_copy == 1 { piece.size() } else { format!("{} x {}", piece.per_copy, piece.size()) };
+        println!("{}: {} per copy, {} per run", piece.item, per_copy, piece.per_run(copies));
+    }
+    println!("#####################################");
+}
+
+fn format_length(length: f64) -> String {
+    // in metres from a metre up
+    if length < MILLIMETRES_PER_METRE {
+        format!("{:.1}mm", length)
+    } else {
+        format!("{:.2}m", length / MILLIMETRES_PER_METRE)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DocumentInfo;
+
+    #[test]
+    fn test_materials() {
+        // 30 leaves at 0.4mm make a 12mm spine, 13.2mm round
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let config = SpineConfig {caliper: 0.4, rounding: Some(1.1), ..SpineConfig::default()};
+        let spine = crate::spine::spine(&document, &config, page_size).unwrap();
+        let sizes: Vec<(String, String, String)> = materials(&document, &spine, &config, Sewing::default(), page_size)
+            .iter()
+            .map(|piece| (piece.item.clone(), piece.size(), piece.per_run(10)))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("Thread".to_string(), "1.14m".to_string(), "11.44m".to_string()),
+                ("Headband".to_string(), "13.2mm".to_string(), "264.0mm".to_string()),
+                ("Mull".to_string(), "73.2x190.0mm".to_string(), "10 pieces".to_string()),
+                ("Kraft lining".to_string(), "13.2x204.0mm".to_string(), "10 pieces".to_string()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_cutting_list() {
+        // a 12mm spine backed for 2.5mm boards, 18.7mm round
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let config = SpineConfig {caliper: 0.4, board_thickness: Some(2.5), rounding: Some(1.1), ..SpineConfig::default()};
+        let spine = crate::spine::spine(&document, &config, page_size).unwrap();
+        let sizes: Vec<(String, u32, String)> = cutting_list(&spine, page_size)
+            .iter()
+            .map(|piece| (piece.item.clone(), piece.per_copy, piece.size()))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("Board".to_string(), 2, "148.5x216.0mm".to_string()),
+                ("Spine strip".to_string(), 1, "18.7x216.0mm".to_string()),
+                ("Cloth".to_string(), 1, "359.7x246.0mm".to_string()),
+                ("Endpaper".to_string(), 2, "296.0x210.0mm".to_string()),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_thread_length() {
+        // 4 signatures, each 210mm through the fold and 3 stations of 10mm
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let sewing = Sewing {stations: 3, waste: 0};
+        assert!((thread_length(&document, sewing, page_size) - 960.0).abs() < 1e-9);
+        let sewing = Sewing {stations: 3, waste: 2500};
+        assert!((thread_length(&document, sewing, page_size) - 1200.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,107 @@
+// Notepads and tear-off calendars: a stack of single leaves glued together
+// along the head with padding compound, on a backing board. There are no
+// signatures to fold, just sheets to cut into leaves, so the plan is of
+// the sheets per pad, the paper to pull and the edge to pad.
+
+use crate::imposition::PageSize;
+use crate::materials::Piece;
+use crate::press::{StockRun, Stocks};
+
+// Constants
+// how the pads are named in a stocks file, e.g. "pad: 80gsm bond, SRA3"
+pub const NOTEPAD_KEY: &str = "pad";
+// a sheet of the plan is two pages wide
+pub const DEFAULT_LEAVES_PER_SHEET: u32 = 2;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Notepad {
+    pub leaves: u32,
+    // the leaves cut from each sheet
+    pub leaves_per_sheet: u32,
+}
+
+impl Notepad {
+    pub fn sheets(&self) -> u32 {
+        self.leaves.div_ceil(self.leaves_per_sheet.max(1))
+    }
+
+    pub fn spare_leaves(&self) -> u32 {
+        // cut from the last sheet but not needed
+        self.sheets() * self.leaves_per_sheet.max(1) - self.leaves
+    }
+}
+
+// the head of the stack, in millimetres, where the compound is brushed on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaddedEdge {
+    pub width: f64,
+    pub thickness: Option<f64>,
+}
+
+
+// Work
+pub fn stock_runs(notepad: Notepad, stocks: &Stocks) -> Vec<StockRun> {
+    // every pad's sheets on the one stock
+    vec![StockRun {
+        stock: stocks.stock_for(NOTEPAD_KEY).to_string(),
+        signatures: vec![(NOTEPAD_KEY.to_string(), notepad.sheets())],
+    }]
+}
+
+pub fn padded_edge(notepad: Notepad, caliper: Option<f64>, page_size: PageSize) -> PaddedEdge {
+    // as thick as the stack, when the paper's caliper is known
+    PaddedEdge {
+        width: page_size.width,
+        thickness: caliper.map(|caliper| f64::from(notepad.leaves) * caliper),
+    }
+}
+
+pub fn notepad_materials(edge: PaddedEdge, page_size: PageSize) -> Vec<Piece> {
+    let mut pieces = vec![Piece {item: "Backing board".to_string(), per_copy: 1, width: page_size.width, height: Some(page_size.height)}];
+    if let Some(thickness) = edge.thickness {
+        pieces.push(Piece {item: "Padded edge".to_string(), per_copy: 1, width: edge.width, height: Some(thickness)});
+    }
+    pieces
+}
+
+pub fn display_notepad(notepad: Notepad) {
+    println!("Pads of {} leaves, padded at the head", notepad.leaves);
+    println!("Sheets per pad: {}, {} leaves cut from each", notepad.sheets(), notepad.leaves_per_sheet);
+    if notepad.spare_leaves() > 0 {
+        println!("Spare leaves per pad: {}", notepad.spare_leaves());
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notepad() {
+        let notepad = Notepad {leaves: 50, leaves_per_sheet: 4};
+        assert_eq!(notepad.sheets(), 13);
+        assert_eq!(notepad.spare_leaves(), 2);
+        let stocks = Stocks::parse("default: 90gsm cream\npad: 80gsm bond\n").unwrap();
+        let runs = stock_runs(notepad, &stocks);
+        assert_eq!(runs[0].stock, "80gsm bond");
+        assert_eq!(runs[0].num_sheets(), 13);
+    }
+
+    #[test]
+    fn test_notepad_materials() {
+        // a tear-off calendar of 365 leaves at 0.1mm
+        let notepad = Notepad {leaves: 365, leaves_per_sheet: DEFAULT_LEAVES_PER_SHEET};
+        let page_size = PageSize {width: 105.0, height: 148.0};
+        let edge = padded_edge(notepad, Some(0.1), page_size);
+        assert_eq!(edge.width, 105.0);
+        assert!((edge.thickness.unwrap() - 36.5).abs() < 1e-9);
+        let pieces = notepad_materials(edge, page_size);
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].item, "Backing board");
+        assert_eq!(notepad_materials(padded_edge(notepad, None, page_size), page_size).len(), 1);
+    }
+}
@@ -0,0 +1,1216 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::batch::Pipeline;
+use crate::binding::BindingOrder;
+use crate::catchwords::CatchwordConvention;
+use crate::colour::{self, ColourPages};
+use crate::config::{BlankPolicy, Units};
+use crate::constraints::{self, Constraint};
+use crate::cutting::{self, Grain};
+use crate::enclosure::Enclosure;
+use crate::impose::PrinterOffset;
+use crate::imposition::{BookletFold, FoldScheme, PageSize, ReadingDirection};
+use crate::job::{self, JobInfo};
+use crate::labels::{self, Alphabet, Continuation, LabelScheme};
+use crate::materials::Sewing;
+use crate::pad::PaddingStrategy;
+use crate::page_count::is_pdf_path;
+use crate::{parse_signature_size, parse_signature_sizes, BalanceStrategy};
+use crate::presets::{Convention, Preset};
+use crate::spine::SpineConfig;
+use crate::stamp::{FolioPosition, FolioStyle, NumeralStyle, SheetSigning};
+use crate::web::WebPress;
+use crate::zine::ZineFormat;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct UnknownFlagError {
+    flag: String,
+}
+
+impl Error for UnknownFlagError {}
+
+impl fmt::Display for UnknownFlagError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown option: {}", self.flag)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct MissingFlagValueError {
+    flag: String,
+}
+
+impl Error for MissingFlagValueError {}
+
+impl fmt::Display for MissingFlagValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Option {} needs a value!", self.flag)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct InvalidOptionValueError {
+    flag: String,
+    value: String,
+    expected: &'static str,
+}
+
+impl Error for InvalidOptionValueError {}
+
+impl fmt::Display for InvalidOptionValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value for {}: {}. Expected one of: {}", self.flag, self.value, self.expected)
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Scribus,
+    Commands,
+    Json,
+    ImpositionJson,
+    Conjugates,
+    Formes,
+    // whole sentences with no decorations, for screen readers
+    Plain,
+    // a fixed layout for scripts to parse, see DocumentInfo::plain_v1
+    PlainV1,
+    Dot,
+    // the --parent-sheet cutting diagram
+    CuttingSvg,
+    // lp commands printing the signatures from the trays in --stocks
+    Lp,
+    // the signature table for a spreadsheet, see DocumentInfo::to_csv
+    Csv,
+    // the gatherings, with columns for treatment records
+    ConservationCsv,
+    ConservationHtml,
+}
+
+impl Format {
+    pub fn is_json(&self) -> bool {
+        matches!(self, Format::Json | Format::ImpositionJson)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Subcommand {
+    #[default]
+    Plan,
+    Deimpose,
+    Analyse,
+    Chase,
+    Batch,
+    Folds,
+    Explain,
+    Scans,
+    Split,
+    Pad,
+    Verify,
+    Zine,
+    Notepad,
+    Impose,
+    Contact,
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub subcommand: Subcommand,
+    pub format: Format,
+    pub page_size: PageSize,
+    pub labels: LabelScheme,
+    pub labels_file: Option<String>,
+    pub compare: Option<Vec<u32>>,
+    pub folios: Option<FolioStyle>,
+    pub running_heads: Option<String>,
+    pub watermark: Option<String>,
+    pub zero_indexed: bool,
+    pub sewing_order: Option<BindingOrder>,
+    pub gathering_order: Option<BindingOrder>,
+    pub stocks: Option<String>,
+    pub watch: bool,
+    // a PDF to plan all the pages of, in place of a page range
+    pub input_pdf: Option<String>,
+    // mark the split signature files PDF/X-1a for a commercial printer
+    pub pdfx: bool,
+    pub sheet_signing: Option<SheetSigning>,
+    pub catchwords: Option<CatchwordConvention>,
+    pub direction_line: bool,
+    // the volume indicator printed on the left of the direction line
+    pub volume: Option<String>,
+    pub copies: Option<u32>,
+    // the spoilage allowance in hundredths of a percent, e.g. 500 for 5%
+    pub spoilage: u32,
+    // a price list to cost the run from
+    pub costs: Option<String>,
+    // the units --page-size is given in
+    pub units: Units,
+    // pages on each side of the press sheet, which is cut into the plan's
+    // sheets if more than 2
+    pub pages_per_side: Option<u32>,
+    // the size of sheet the paper comes in, in millimetres, to cut the press
+    // sheets from
+    pub parent_sheet: Option<PageSize>,
+    pub grain: Grain,
+    // print on a web press rather than sheet fed
+    pub web: Option<WebPress>,
+    // shown in the reports and recorded in the PDFs written, over anything
+    // in the --project file
+    pub job: JobInfo,
+    pub project: Option<String>,
+    // a Tera template to render the plan through instead of the --format
+    pub template: Option<String>,
+    // pages before page 1 of the book, numbered i, ii, ...
+    pub front_matter: u32,
+    // a directory of scans, or a scanned PDF, to check against the plan
+    pub check_scans: Option<String>,
+    // how many sheet sides verify reads the page numbers off
+    pub sample_sides: Option<usize>,
+    pub pipeline: Pipeline,
+    // carry on a batch from the jobs not yet done
+    pub resume: bool,
+    pub blank_policy: BlankPolicy,
+    pub booklet_fold: BookletFold,
+    // print each sheet on one side only, cut rather than folded
+    pub one_sided: bool,
+    pub reading_direction: ReadingDirection,
+    // its constraints are added to any given as flags
+    pub preset: Option<Preset>,
+    // sets the labels and sheet signing, which flags after it can change
+    pub convention: Option<Convention>,
+    pub zine_format: ZineFormat,
+    // pages printed in colour, to count and optionally keep to their own
+    // signatures
+    pub colour_pages: Option<ColourPages>,
+    pub segregate_colour: bool,
+    // the printer's measured misregistration of the backs, corrected when
+    // imposing
+    pub back_offset: PrinterOffset,
+    // where the blanks go, reported with the plan
+    pub padding: Option<PaddingStrategy>,
+    // how many times each sheet is folded, when given
+    pub fold_scheme: Option<FoldScheme>,
+    // how the pages are shared out between the signatures
+    pub balance: BalanceStrategy,
+    // pages in every signature, in place of the usual 16
+    pub signature_size: Option<u32>,
+    // what the signature size has to allow, for it to be chosen to fit
+    pub constraints: Vec<Constraint>,
+    // the documents bound together as one volume, in order, each a PDF or a
+    // span of pages, in place of a page range
+    pub parts: Vec<String>,
+    // start each part on a fresh signature, padding the one before with blanks
+    pub align_parts: bool,
+    // a collation formula describing the gatherings, in place of a page range
+    pub collation: Option<String>,
+    // the paper's thickness and any guards, to work out the spine from
+    pub spine: Option<SpineConfig>,
+    // the sewing stations and thread waste, for the thread in the materials
+    pub sewing: Sewing,
+    // a box to make for the finished book, and the room to leave round it in
+    // millimetres
+    pub enclosure: Option<Enclosure>,
+    pub clearance: Option<f64>,
+    // a PDF to write the numbered slips of a limited edition of --copies to
+    pub edition_slips: Option<String>,
+    pub slips_per_sheet: Option<u32>,
+    // text blocks that fit in the standing press at once, and the hours each
+    // batch stays in
+    pub press_capacity: Option<u32>,
+    pub pressing_hours: Option<u32>,
+}
+
+impl Options {
+    pub fn input_files(&self) -> Vec<String> {
+        // the files the plan is read from, besides the page range
+        [&self.input_pdf, &self.labels_file, &self.running_heads, &self.stocks, &self.costs, &self.project, &self.template]
+            .iter()
+            .filter_map(|path| path.as_ref().cloned())
+            .chain(self.parts.iter().filter(|part| is_pdf_path(part)).cloned())
+            .collect()
+    }
+}
+
+
+// Work
+pub fn parse_options(all_args: Vec<String>) -> Result<(Vec<String>, Options), Box<dyn Error>> {
+    // Pull the --flags and any subcommand out of the command line arguments,
+    // leaving the positional arguments (including the binary name) for
+    // parse_args.
+    let mut positional_args = Vec::new();
+    let mut options = Options::default();
+    let mut page_size_given = false;
+    let mut args = all_args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            match arg.as_str() {
+                "deimpose" if positional_args.len() == 1 => options.subcommand = Subcommand::Deimpose,
+                "analyse" if positional_args.len() == 1 => options.subcommand = Subcommand::Analyse,
+                "chase" if positional_args.len() == 1 => options.subcommand = Subcommand::Chase,
+                "batch" if positional_args.len() == 1 => options.subcommand = Subcommand::Batch,
+                "folds" if positional_args.len() == 1 => options.subcommand = Subcommand::Folds,
+                "explain" if positional_args.len() == 1 => options.subcommand = Subcommand::Explain,
+                "scans" if positional_args.len() == 1 => options.subcommand = Subcommand::Scans,
+                "split" if positional_args.len() == 1 => options.subcommand = Subcommand::Split,
+                "pad" if positional_args.len() == 1 => options.subcommand = Subcommand::Pad,
+                "verify" if positional_args.len() == 1 => options.subcommand = Subcommand::Verify,
+                "zine" if positional_args.len() == 1 => options.subcommand = Subcommand::Zine,
+                "notepad" if positional_args.len() == 1 => options.subcommand = Subcommand::Notepad,
+                "impose" if positional_args.len() == 1 => options.subcommand = Subcommand::Impose,
+                "contact" if positional_args.len() == 1 => options.subcommand = Subcommand::Contact,
+                _ if positional_args.len() == 1
+                    && options.input_pdf.is_none()
+                    && is_pdf_path(&arg)
+                    && matches!(
+                        options.subcommand,
+                        Subcommand::Plan
+                            | Subcommand::Explain
+                            | Subcommand::Scans
+                            | Subcommand::Split
+                            | Subcommand::Pad
+                            | Subcommand::Zine
+                            | Subcommand::Impose
+                            | Subcommand::Contact
+                    ) =>
+                {
+                    options.input_pdf = Some(arg);
+                },
+                _ => positional_args.push(arg),
+            }
+            continue;
+        }
+        match arg.as_str() {
+            "--format" => {
+                let value = next_value(&arg, &mut args)?;
+                options.format = parse_format(&arg, &value)?;
+            },
+            "--page-size" => {
+                options.page_size = PageSize::parse(&next_value(&arg, &mut args)?)?;
+                page_size_given = true;
+            },
+            "--pages-per-side" => options.pages_per_side = Some(next_value(&arg, &mut args)?.parse()?),
+            "--parent-sheet" => options.parent_sheet = Some(cutting::parse_parent_size(&next_value(&arg, &mut args)?)?),
+            "--title" => options.job.title = Some(next_value(&arg, &mut args)?),
+            "--author" => options.job.author = Some(next_value(&arg, &mut args)?),
+            "--job-id" => options.job.job_id = Some(next_value(&arg, &mut args)?),
+            "--client" => options.job.client = Some(next_value(&arg, &mut args)?),
+            "--due" => options.job.due = Some(job::parse_due_date(&next_value(&arg, &mut args)?)?),
+            "--project" => options.project = Some(next_value(&arg, &mut args)?),
+            "--part" => options.parts.push(next_value(&arg, &mut args)?),
+            "--align-parts" => options.align_parts = true,
+            "--caliper" => {
+                let caliper = next_value(&arg, &mut args)?.parse()?;
+                options.spine.get_or_insert_with(SpineConfig::default).caliper = caliper;
+            },
+            "--guard-leaves" => {
+                let guarded_leaves = next_value(&arg, &mut args)?.parse()?;
+                options.spine.get_or_insert_with(SpineConfig::default).guarded_leaves = guarded_leaves;
+            },
+            "--backing" => {
+                let board_thickness = next_value(&arg, &mut args)?.parse()?;
+                options.spine.get_or_insert_with(SpineConfig::default).board_thickness = Some(board_thickness);
+            },
+            "--rounding" => {
+                let rounding = next_value(&arg, &mut args)?.parse()?;
+                options.spine.get_or_insert_with(SpineConfig::default).rounding = Some(rounding);
+            },
+            "--guard-caliper" => {
+                let guard_caliper = next_value(&arg, &mut args)?.parse()?;
+                options.spine.get_or_insert_with(SpineConfig::default).guard_caliper = Some(guard_caliper);
+            },
+            "--stations" => options.sewing.stations = next_value(&arg, &mut args)?.parse()?,
+            "--thread-waste" => {
+                let value = next_value(&arg, &mut args)?;
+                options.sewing.waste = parse_spoilage(&arg, &value)?;
+            },
+            "--enclosure" => {
+                let value = next_value(&arg, &mut args)?;
+                options.enclosure = Some(parse_enclosure(&arg, &value)?);
+            },
+            "--clearance" => options.clearance = Some(next_value(&arg, &mut args)?.parse()?),
+            "--edition-slips" => options.edition_slips = Some(next_value(&arg, &mut args)?),
+            "--slips-per-sheet" => {
+                let value = next_value(&arg, &mut args)?;
+                match value.parse() {
+                    Ok(slips) if slips > 0 => options.slips_per_sheet = Some(slips),
+                    _ => return Err(invalid_value(&arg, &value, "a number of slips, e.g. 8")),
+                }
+            },
+            "--press-capacity" => {
+                let value = next_value(&arg, &mut args)?;
+                match value.parse() {
+                    Ok(capacity) if capacity > 0 => options.press_capacity = Some(capacity),
+                    _ => return Err(invalid_value(&arg, &value, "a number of text blocks, e.g. 40")),
+                }
+            },
+            "--pressing-hours" => options.pressing_hours = Some(next_value(&arg, &mut args)?.parse()?),
+            "--collation" => options.collation = Some(next_value(&arg, &mut args)?),
+            "--template" => options.template = Some(next_value(&arg, &mut args)?),
+            "--resume" => options.resume = true,
+            "--threads" => {
+                let value = next_value(&arg, &mut args)?;
+                options.pipeline = parse_pipeline(&arg, &value)?;
+            },
+            "--check" => options.check_scans = Some(next_value(&arg, &mut args)?),
+            "--sample-sides" => options.sample_sides = Some(next_value(&arg, &mut args)?.parse()?),
+            "--front-matter" => options.front_matter = next_value(&arg, &mut args)?.parse()?,
+            "--filler-page" => options.blank_policy = BlankPolicy::Filler(next_value(&arg, &mut args)?.parse()?),
+            "--web" => options.web = Some(WebPress::parse(&next_value(&arg, &mut args)?)?),
+            "--min-size" => options.constraints.push(Constraint::MinSize(next_value(&arg, &mut args)?.parse()?)),
+            "--colour-pages" => {
+                let value = next_value(&arg, &mut args)?;
+                let colour_pages = colour::parse_colour_pages(&value)
+                    .ok_or_else(|| invalid_value(&arg, &value, "auto, or pages and spans of pages, e.g. 5,33-48"))?;
+                options.colour_pages = Some(colour_pages);
+            },
+            "--segregate-colour" => options.segregate_colour = true,
+            "--back-offset" => {
+                let value = next_value(&arg, &mut args)?;
+                options.back_offset = PrinterOffset::parse(&value)
+                    .ok_or_else(|| invalid_value(&arg, &value, "millimetres right and down, e.g. 1.5,-0.5"))?;
+            },
+            "--signature-size" => options.signature_size = Some(parse_signature_size(&next_value(&arg, &mut args)?)?),
+            "--max-size" => options.constraints.push(Constraint::MaxSize(next_value(&arg, &mut args)?.parse()?)),
+            "--keep-together" => {
+                let value = next_value(&arg, &mut args)?;
+                let (from, to) = constraints::parse_page_span(&value)
+                    .ok_or_else(|| invalid_value(&arg, &value, "a span of pages, e.g. 33-48"))?;
+                options.constraints.push(Constraint::KeepTogether(from, to));
+            },
+            "--start-signature" => {
+                options.constraints.push(Constraint::StartsSignature(next_value(&arg, &mut args)?.parse()?));
+            },
+            "--recto-start" => options.constraints.push(Constraint::RectoStart(next_value(&arg, &mut args)?.parse()?)),
+            "--page-turn" => options.constraints.push(Constraint::PageTurn(next_value(&arg, &mut args)?.parse()?)),
+            "--preset" => {
+                let preset = parse_preset(&arg, &next_value(&arg, &mut args)?)?;
+                options.constraints.extend(preset.constraints());
+                options.preset = Some(preset);
+            },
+            "--convention" => {
+                let convention = parse_convention(&arg, &next_value(&arg, &mut args)?)?;
+                let half_suffix = options.labels.half_suffix.take();
+                options.labels = LabelScheme {half_suffix, ..convention.label_scheme()};
+                options.sheet_signing = Some(convention.sheet_signing());
+                options.convention = Some(convention);
+            },
+            "--fold" => {
+                let value = next_value(&arg, &mut args)?;
+                options.fold_scheme = Some(parse_fold_scheme(&arg, &value)?);
+            },
+            "--balance" => {
+                let value = next_value(&arg, &mut args)?;
+                options.balance = parse_balance(&arg, &value)?;
+            },
+            "--booklet-fold" => {
+                let value = next_value(&arg, &mut args)?;
+                options.booklet_fold = parse_booklet_fold(&arg, &value)?;
+            },
+            "--padding" => {
+                let value = next_value(&arg, &mut args)?;
+                options.padding = Some(parse_padding(&arg, &value)?);
+            },
+            "--one-sided" => options.one_sided = true,
+            "--pdfx" => options.pdfx = true,
+            "--reading-direction" => {
+                let value = next_value(&arg, &mut args)?;
+                options.reading_direction = parse_reading_direction(&arg, &value)?;
+            },
+            "--zine-format" => {
+                let value = next_value(&arg, &mut args)?;
+                options.zine_format = parse_zine_format(&arg, &value)?;
+            },
+            "--grain" => {
+                let value = next_value(&arg, &mut args)?;
+                options.grain = parse_grain(&arg, &value)?;
+            },
+            "--units" => {
+                let value = next_value(&arg, &mut args)?;
+                options.units = parse_units(&arg, &value)?;
+            },
+            "--labels" => {
+                let value = next_value(&arg, &mut args)?;
+                options.labels.alphabet = parse_alphabet(&arg, &value)?;
+            },
+            "--skip-letters" => options.labels.skip_letters = labels::parse_skip_letters(&next_value(&arg, &mut args)?)?,
+            "--continuation" => {
+                let value = next_value(&arg, &mut args)?;
+                options.labels.continuation = parse_continuation(&arg, &value)?;
+            },
+            "--half-suffix" => options.labels.half_suffix = Some(next_value(&arg, &mut args)?),
+            "--labels-file" => options.labels_file = Some(next_value(&arg, &mut args)?),
+            "--compare" => {
+                let value = next_value(&arg, &mut args)?;
+                options.compare = Some(parse_signature_sizes(&value)?);
+            },
+            "--folios" => {
+                let value = next_value(&arg, &mut args)?;
+                let position = parse_folio_position(&arg, &value)?;
+                options.folios.get_or_insert_with(FolioStyle::default).position = position;
+            },
+            "--folio-font" => {
+                let value = next_value(&arg, &mut args)?;
+                options.folios.get_or_insert_with(FolioStyle::default).font = value;
+            },
+            "--folio-size" => {
+                let value = next_value(&arg, &mut args)?;
+                options.folios.get_or_insert_with(FolioStyle::default).font_size = value.parse()?;
+            },
+            "--folio-numerals" => {
+                let value = next_value(&arg, &mut args)?;
+                let numerals = parse_numeral_style(&arg, &value)?;
+                options.folios.get_or_insert_with(FolioStyle::default).numerals = numerals;
+            },
+            "--sewing-order" => {
+                let value = next_value(&arg, &mut args)?;
+                options.sewing_order = Some(parse_binding_order(&arg, &value)?);
+            },
+            "--gathering-order" => {
+                let value = next_value(&arg, &mut args)?;
+                options.gathering_order = Some(parse_binding_order(&arg, &value)?);
+            },
+            "--stocks" => options.stocks = Some(next_value(&arg, &mut args)?),
+            "--sheet-signing" => {
+                let value = next_value(&arg, &mut args)?;
+                options.sheet_signing = Some(parse_sheet_signing(&arg, &value)?);
+            },
+            "--catchwords" => {
+                let value = next_value(&arg, &mut args)?;
+                options.catchwords = Some(parse_catchword_convention(&arg, &value)?);
+            },
+            "--direction-line" => options.direction_line = true,
+            "--volume" => {
+                options.volume = Some(next_value(&arg, &mut args)?);
+                options.direction_line = true;
+            },
+            "--copies" => options.copies = Some(next_value(&arg, &mut args)?.parse()?),
+            "--spoilage" => {
+                let value = next_value(&arg, &mut args)?;
+                options.spoilage = parse_spoilage(&arg, &value)?;
+            },
+            "--costs" => options.costs = Some(next_value(&arg, &mut args)?),
+            "--running-heads" => options.running_heads = Some(next_value(&arg, &mut args)?),
+            "--proof" => options.watermark = Some("PROOF".to_string()),
+            "--watermark" => options.watermark = Some(next_value(&arg, &mut args)?),
+            "--zero-indexed" => options.zero_indexed = true,
+            "--watch" => options.watch = true,
+            _ => return Err((UnknownFlagError {flag: arg}).into()),
+        }
+    }
+    if !page_size_given {
+        // the default page size, in whatever units were asked for
+        let default = PageSize::default();
+        options.page_size = PageSize {
+            width: options.units.from_millimetres(default.width),
+            height: options.units.from_millimetres(default.height),
+        };
+    }
+    Ok((positional_args, options))
+}
+
+pub fn wants_json_errors(all_args: &[String]) -> bool {
+    // Whether a JSON format was asked for, looking at the raw arguments so
+    // that even problems parsing the options can be reported as JSON.
+    all_args
+        .windows(2)
+        .any(|pair| pair[0] == "--format" && parse_format(&pair[0], &pair[1]).is_ok_and(|format| format.is_json()))
+}
+
+fn next_value<I: Iterator<Item = String>>(flag: &str, args: &mut I) -> Result<String, Box<dyn Error>> {
+    // the value that follows a flag, e.g. the "16,20" of "--compare 16,20"
+    match args.next() {
+        Some(value) => Ok(value),
+        None => Err((MissingFlagValueError {flag: flag.to_string()}).into()),
+    }
+}
+
+fn parse_format(flag: &str, value: &str) -> Result<Format, Box<dyn Error>> {
+    match value {
+        "text" => Ok(Format::Text),
+        "scribus" => Ok(Format::Scribus),
+        "commands" => Ok(Format::Commands),
+        "json" => Ok(Format::Json),
+        "imposition-json" => Ok(Format::ImpositionJson),
+        "conjugates" => Ok(Format::Conjugates),
+        "formes" => Ok(Format::Formes),
+        "plain" => Ok(Format::Plain),
+        "plain-v1" => Ok(Format::PlainV1),
+        "dot" => Ok(Format::Dot),
+        "cutting-svg" => Ok(Format::CuttingSvg),
+        "lp" => Ok(Format::Lp),
+        "csv" => Ok(Format::Csv),
+        "conservation-csv" => Ok(Format::ConservationCsv),
+        "conservation-html" => Ok(Format::ConservationHtml),
+        _ => Err(invalid_value(
+            flag,
+            value,
+            "text, scribus, commands, json, imposition-json, conjugates, formes, plain, plain-v1, dot, cutting-svg, lp, \
+             csv, conservation-csv, conservation-html",
+        )),
+    }
+}
+
+fn parse_alphabet(flag: &str, value: &str) -> Result<Alphabet, Box<dyn Error>> {
+    match value {
+        "latin" => Ok(Alphabet::Latin),
+        "greek" => Ok(Alphabet::Greek),
+        "cyrillic" => Ok(Alphabet::Cyrillic),
+        "hebrew" => Ok(Alphabet::Hebrew),
+        _ => Err(invalid_value(flag, value, "latin, greek, cyrillic, hebrew")),
+    }
+}
+
+fn parse_continuation(flag: &str, value: &str) -> Result<Continuation, Box<dyn Error>> {
+    match value {
+        "sequential" => Ok(Continuation::Sequential),
+        "doubled" => Ok(Continuation::Doubled),
+        "paired" => Ok(Continuation::Paired),
+        "numbered" => Ok(Continuation::Numbered),
+        _ => Err(invalid_value(flag, value, "sequential, doubled, paired, numbered")),
+    }
+}
+
+fn parse_folio_position(flag: &str, value: &str) -> Result<FolioPosition, Box<dyn Error>> {
+    match value {
+        "foot-centre" => Ok(FolioPosition::FootCentre),
+        "foot-outer" => Ok(FolioPosition::FootOuter),
+        "head-outer" => Ok(FolioPosition::HeadOuter),
+        _ => Err(invalid_value(flag, value, "foot-centre, foot-outer, head-outer")),
+    }
+}
+
+fn parse_numeral_style(flag: &str, value: &str) -> Result<NumeralStyle, Box<dyn Error>> {
+    match value {
+        "arabic" => Ok(NumeralStyle::Arabic),
+        "lower-roman" => Ok(NumeralStyle::LowerRoman),
+        "upper-roman" => Ok(NumeralStyle::UpperRoman),
+        _ => Err(invalid_value(flag, value, "arabic, lower-roman, upper-roman")),
+    }
+}
+
+fn parse_sheet_signing(flag: &str, value: &str) -> Result<SheetSigning, Box<dyn Error>> {
+    match value {
+        "star" => Ok(SheetSigning::Star),
+        "letters" => Ok(SheetSigning::Letters),
+        "secondary" => Ok(SheetSigning::Secondary),
+        _ => Err(invalid_value(flag, value, "star, letters, secondary")),
+    }
+}
+
+fn parse_units(flag: &str, value: &str) -> Result<Units, Box<dyn Error>> {
+    match value {
+        "mm" => Ok(Units::Millimetres),
+        "in" => Ok(Units::Inches),
+        "pt" => Ok(Units::Points),
+        _ => Err(invalid_value(flag, value, "mm, in, pt")),
+    }
+}
+
+fn parse_pipeline(flag: &str, value: &str) -> Result<Pipeline, Box<dyn Error>> {
+    // "4" for 4 threads on every stage, or "8,2" for 8 reading and 2 planning
+    let invalid = || invalid_value(flag, value, "a number of threads, or threads for reading and planning, e.g. 8,2");
+    let threads: Vec<usize> = value
+        .split(',')
+        .map(|threads| threads.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    match threads.as_slice() {
+        [threads] if *threads > 0 => Ok(Pipeline {readers: *threads, planners: *threads}),
+        [readers, planners] if *readers > 0 && *planners > 0 => Ok(Pipeline {readers: *readers, planners: *planners}),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_grain(flag: &str, value: &str) -> Result<Grain, Box<dyn Error>> {
+    match value {
+        "long" => Ok(Grain::Long),
+        "short" => Ok(Grain::Short),
+        _ => Err(invalid_value(flag, value, "long, short")),
+    }
+}
+
+fn parse_fold_scheme(flag: &str, value: &str) -> Result<FoldScheme, Box<dyn Error>> {
+    match value {
+        "folio" => Ok(FoldScheme::Folio),
+        "quarto" | "4to" => Ok(FoldScheme::Quarto),
+        "octavo" | "8vo" => Ok(FoldScheme::Octavo),
+        "sextodecimo" | "16mo" => Ok(FoldScheme::Sextodecimo),
+        _ => Err(invalid_value(flag, value, "folio, quarto, octavo, sextodecimo")),
+    }
+}
+
+fn parse_balance(flag: &str, value: &str) -> Result<BalanceStrategy, Box<dyn Error>> {
+    match value {
+        "none" => Ok(BalanceStrategy::None),
+        "even" => Ok(BalanceStrategy::Even),
+        "last" => Ok(BalanceStrategy::MinimizeLast),
+        _ => Err(invalid_value(flag, value, "none, even, last")),
+    }
+}
+
+fn parse_booklet_fold(flag: &str, value: &str) -> Result<BookletFold, Box<dyn Error>> {
+    match value {
+        "side" => Ok(BookletFold::Side),
+        "top" => Ok(BookletFold::Top),
+        _ => Err(invalid_value(flag, value, "side, top")),
+    }
+}
+
+fn parse_padding(flag: &str, value: &str) -> Result<PaddingStrategy, Box<dyn Error>> {
+    match value {
+        "end" => Ok(PaddingStrategy::EndOfBook),
+        "signature" => Ok(PaddingStrategy::EndOfLastSignature),
+        "split" => Ok(PaddingStrategy::Split),
+        _ => Err(invalid_value(flag, value, "end, signature, split")),
+    }
+}
+
+fn parse_preset(flag: &str, value: &str) -> Result<Preset, Box<dyn Error>> {
+    match value {
+        "music" => Ok(Preset::Music),
+        "thesis" => Ok(Preset::Thesis),
+        _ => Err(invalid_value(flag, value, "music, thesis")),
+    }
+}
+
+fn parse_convention(flag: &str, value: &str) -> Result<Convention, Box<dyn Error>> {
+    match value {
+        "british" => Ok(Convention::BritishTrade),
+        "german" => Ok(Convention::German),
+        "french" => Ok(Convention::French),
+        _ => Err(invalid_value(flag, value, "british, german, french")),
+    }
+}
+
+fn parse_zine_format(flag: &str, value: &str) -> Result<ZineFormat, Box<dyn Error>> {
+    match value {
+        "mini" => Ok(ZineFormat::Mini),
+        "quarter-letter" => Ok(ZineFormat::QuarterLetter),
+        _ => Err(invalid_value(flag, value, "mini, quarter-letter")),
+    }
+}
+
+fn parse_reading_direction(flag: &str, value: &str) -> Result<ReadingDirection, Box<dyn Error>> {
+    match value {
+        "ltr" => Ok(ReadingDirection::LeftToRight),
+        "rtl" => Ok(ReadingDirection::RightToLeft),
+        _ => Err(invalid_value(flag, value, "ltr, rtl")),
+    }
+}
+
+fn parse_enclosure(flag: &str, value: &str) -> Result<Enclosure, Box<dyn Error>> {
+    match value {
+        "slipcase" => Ok(Enclosure::Slipcase),
+        "clamshell" => Ok(Enclosure::Clamshell),
+        _ => Err(invalid_value(flag, value, "slipcase, clamshell")),
+    }
+}
+
+fn parse_catchword_convention(flag: &str, value: &str) -> Result<CatchwordConvention, Box<dyn Error>> {
+    match value {
+        "every-page" => Ok(CatchwordConvention::EveryPage),
+        "versos" => Ok(CatchwordConvention::Versos),
+        "gathering-ends" => Ok(CatchwordConvention::GatheringEnds),
+        _ => Err(invalid_value(flag, value, "every-page, versos, gathering-ends")),
+    }
+}
+
+fn parse_binding_order(flag: &str, value: &str) -> Result<BindingOrder, Box<dyn Error>> {
+    match value {
+        "first-to-last" => Ok(BindingOrder::FirstToLast),
+        "last-to-first" => Ok(BindingOrder::LastToFirst),
+        _ => Err(invalid_value(flag, value, "first-to-last, last-to-first")),
+    }
+}
+
+fn parse_spoilage(flag: &str, value: &str) -> Result<u32, Box<dyn Error>> {
+    // "5%", "2.5%" or "0.25", to hundredths of a percent
+    let invalid = || invalid_value(flag, value, "a percentage, e.g. 5%");
+    let percent = value.strip_suffix('%').unwrap_or(value);
+    let (whole, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+    if fraction.len() > 2 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let whole: u32 = whole.parse().map_err(|_| invalid())?;
+    let fraction: u32 = format!("{:0<2}", fraction).parse().map_err(|_| invalid())?;
+    whole.checked_mul(100).and_then(|whole| whole.checked_add(fraction)).ok_or_else(invalid)
+}
+
+fn invalid_value(flag: &str, value: &str, expected: &'static str) -> Box<dyn Error> {
+    (InvalidOptionValueError {flag: flag.to_string(), value: value.to_string(), expected}).into()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60"]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures", "1", "60"]));
+                assert!(options.compare.is_none());
+                assert_eq!(options.format, Format::Text);
+                assert_eq!(options.page_size, PageSize::default());
+                assert_eq!(options.subcommand, Subcommand::Plan);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+
+        // flags can come before or after the page numbers
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "--compare", "16,20,24", "1", "60"]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures", "1", "60"]));
+                assert_eq!(options.compare, Some(vec![16, 20, 24]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_subcommand() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "deimpose", "1", "60", "booklet.pdf", "reading.pdf",
+            "--title", "Poems", "--job-id", "J-1041", "--front-matter", "12", "--client", "Marriot", "--due", "2026-11-01",
+        ]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Deimpose);
+                assert_eq!(options.job.title, Some("Poems".to_string()));
+                assert_eq!(options.job.job_id, Some("J-1041".to_string()));
+                assert_eq!(options.job.client, Some("Marriot".to_string()));
+                assert_eq!(options.job.due, Some("2026-11-01".to_string()));
+                assert_eq!(options.job.author, None);
+                assert_eq!(options.front_matter, 12);
+                assert_eq!(
+                    positional_args,
+                    to_args(&["target/debug/rust-signatures", "1", "60", "booklet.pdf", "reading.pdf"]),
+                );
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_split_pdfx() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "split", "book.pdf", "--pdfx"]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Split);
+                assert_eq!(options.input_pdf, Some("book.pdf".to_string()));
+                assert!(options.pdfx);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_impose() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "impose", "book.pdf", "book-print.pdf", "--pages-per-side", "4",
+        ]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Impose);
+                assert_eq!(options.input_pdf, Some("book.pdf".to_string()));
+                assert_eq!(options.pages_per_side, Some(4));
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures", "book-print.pdf"]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_contact() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "contact", "book.pdf", "--signature-size", "8"]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Contact);
+                assert_eq!(options.input_pdf, Some("book.pdf".to_string()));
+                assert_eq!(options.signature_size, Some(8));
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures"]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_notepad() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "notepad", "50", "--copies", "200", "--caliper", "0.1",
+        ]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Notepad);
+                assert_eq!(options.copies, Some(200));
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures", "50"]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_zine() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "zine", "issue-3.pdf", "--zine-format", "quarter-letter",
+        ]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Zine);
+                assert_eq!(options.input_pdf, Some("issue-3.pdf".to_string()));
+                assert_eq!(options.zine_format, ZineFormat::QuarterLetter);
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures"]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_input_pdf() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "book.pdf", "--stocks", "stocks.txt", "--template", "report.tera",
+            "--part", "issue-1.pdf", "--part", "1-48", "--align-parts",
+        ]));
+        match result {
+            Ok((positional_args, options)) => {
+                assert_eq!(options.input_pdf, Some("book.pdf".to_string()));
+                assert_eq!(options.parts, to_args(&["issue-1.pdf", "1-48"]));
+                assert!(options.align_parts);
+                assert_eq!(options.input_files(), to_args(&["book.pdf", "stocks.txt", "report.tera", "issue-1.pdf"]));
+                assert_eq!(positional_args, to_args(&["target/debug/rust-signatures"]));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_pad() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "pad", "book.pdf", "--filler-page", "3"]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.subcommand, Subcommand::Pad);
+                assert_eq!(options.input_pdf, Some("book.pdf".to_string()));
+                assert_eq!(options.blank_policy, BlankPolicy::Filler(3));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_constraints() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "200", "--max-size", "24", "--keep-together", "9-24",
+            "--start-signature", "41", "--recto-start", "57", "--preset", "music", "--page-turn", "13",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(
+                    options.constraints,
+                    vec![
+                        Constraint::MaxSize(24),
+                        Constraint::KeepTogether(9, 24),
+                        Constraint::StartsSignature(41),
+                        Constraint::RectoStart(57),
+                        Constraint::MaxSize(8),
+                        Constraint::PageTurn(13),
+                    ],
+                );
+                assert_eq!(options.preset, Some(Preset::Music));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "1", "200", "--keep-together", "24"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_signature_size() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "200", "--signature-size", "24"]));
+        match result {
+            Ok((_, options)) => assert_eq!(options.signature_size, Some(24)),
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        match parse_options(to_args(&["target/debug/rust-signatures", "1", "200", "--signature-size", "18"])) {
+            Ok(_) => panic!("Should have errored because 18 is not a multiple of 4!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Signature size must be a positive multiple of 4 pages! Got 18.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_colour_pages() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "64", "--colour-pages", "33-40", "--segregate-colour",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.colour_pages, Some(ColourPages::Spans(vec![(33, 40)])));
+                assert!(options.segregate_colour);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "1", "64", "--colour-pages", "plates"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_back_offset() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "impose", "book.pdf", "--back-offset", "1.5,-0.5",
+        ]));
+        match result {
+            Ok((_, options)) => assert_eq!(options.back_offset, PrinterOffset {right: 1.5, down: -0.5}),
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "impose", "book.pdf", "--back-offset", "2mm"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_fold() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "64", "--fold", "octavo"]));
+        match result {
+            Ok((_, options)) => assert_eq!(options.fold_scheme, Some(FoldScheme::Octavo)),
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "64", "--fold", "12mo"]));
+        match result {
+            Ok(_) => panic!("Should have errored because 12mo isn't a fold scheme!"),
+            Err(result_error) => assert!(format!("{}", result_error).contains("folio, quarto, octavo, sextodecimo")),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_convention() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "480", "--convention", "german", "--sheet-signing", "letters",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.convention, Some(Convention::German));
+                assert_eq!(options.labels.skip_letters, vec!['J', 'U', 'W']);
+                assert_eq!(options.labels.continuation, Continuation::Paired);
+                assert_eq!(options.sheet_signing, Some(SheetSigning::Letters));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "1", "480", "--convention", "dutch"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_balance() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "68", "--balance", "even"]));
+        match result {
+            Ok((_, options)) => assert_eq!(options.balance, BalanceStrategy::Even),
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "1", "68", "--balance", "odd"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_padding() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "58", "--padding", "split"]));
+        match result {
+            Ok((_, options)) => assert_eq!(options.padding, Some(PaddingStrategy::Split)),
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        assert!(parse_options(to_args(&["target/debug/rust-signatures", "1", "58", "--padding", "front"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_options_units() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--units", "in"]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.units, Units::Inches);
+                let page_size_mm = PageSize {
+                    width: options.units.to_millimetres(options.page_size.width),
+                    height: options.units.to_millimetres(options.page_size.height),
+                };
+                assert!((page_size_mm.width - 148.0).abs() < 1e-9);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--units", "cm"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_options_format() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "60", "--format", "scribus", "--page-size", "105x148",
+            "--zero-indexed", "--booklet-fold", "top", "--one-sided", "--reading-direction", "rtl",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.format, Format::Scribus);
+                assert_eq!(options.page_size, PageSize {width: 105.0, height: 148.0});
+                assert_eq!(options.labels.alphabet, Alphabet::Latin);
+                assert!(options.zero_indexed);
+                assert_eq!(options.booklet_fold, BookletFold::Top);
+                assert!(options.one_sided);
+                assert_eq!(options.reading_direction, ReadingDirection::RightToLeft);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_parent_sheet() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "60", "--pages-per-side", "6", "--parent-sheet", "SRA1",
+            "--grain", "short", "--format", "cutting-svg",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.format, Format::CuttingSvg);
+                assert_eq!(options.grain, Grain::Short);
+                assert_eq!(options.web, None);
+                assert_eq!(options.pages_per_side, Some(6));
+                assert_eq!(options.parent_sheet, Some(PageSize {width: 640.0, height: 900.0}));
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_pipeline() {
+        assert_eq!(parse_pipeline("--threads", "4").unwrap(), Pipeline {readers: 4, planners: 4});
+        assert_eq!(parse_pipeline("--threads", "8, 2").unwrap(), Pipeline {readers: 8, planners: 2});
+        assert!(parse_pipeline("--threads", "0").is_err());
+        assert!(parse_pipeline("--threads", "1,2,3").is_err());
+        assert!(parse_pipeline("--threads", "many").is_err());
+    }
+
+    #[test]
+    fn test_wants_json_errors() {
+        assert!(wants_json_errors(&to_args(&["target/debug/rust-signatures", "0", "60", "--format", "json"])));
+        assert!(wants_json_errors(&to_args(&["target/debug/rust-signatures", "--format", "imposition-json", "--bogus"])));
+        assert!(!wants_json_errors(&to_args(&["target/debug/rust-signatures", "1", "60", "--format", "scribus"])));
+        assert!(!wants_json_errors(&to_args(&["target/debug/rust-signatures", "1", "60", "json"])));
+    }
+
+    #[test]
+    fn test_parse_options_labels() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "60", "--labels", "greek", "--skip-letters", "ο,υ",
+            "--continuation", "numbered", "--half-suffix", "\u{bd}",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.labels.alphabet, Alphabet::Greek);
+                assert_eq!(options.labels.continuation, Continuation::Numbered);
+                assert_eq!(options.labels.half_suffix.as_deref(), Some("\u{bd}"));
+                assert_eq!(options.labels.skip_letters, vec!['ο', 'υ']);
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_folios() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "60", "--folio-numerals", "lower-roman", "--folios", "foot-outer",
+            "--sheet-signing", "letters", "--catchwords", "versos", "--volume", "Vol. I",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert!(options.direction_line);
+                assert_eq!(options.volume, Some("Vol. I".to_string()));
+                assert_eq!(options.catchwords, Some(CatchwordConvention::Versos));
+                assert_eq!(options.sheet_signing, Some(SheetSigning::Letters));
+                let style = options.folios.expect("--folios should set a folio style");
+                assert_eq!(style.position, FolioPosition::FootOuter);
+                assert_eq!(style.numerals, NumeralStyle::LowerRoman);
+                assert_eq!(style.font, "Times-Roman");
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--folios", "middle"]));
+        match result {
+            Ok(_) => panic!("Should have errored because of an unknown folio position!"),
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("Invalid value for --folios: middle."));
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_spoilage() {
+        assert_eq!(parse_spoilage("--spoilage", "5%").unwrap(), 500);
+        assert_eq!(parse_spoilage("--spoilage", "2.5%").unwrap(), 250);
+        assert_eq!(parse_spoilage("--spoilage", "0.25").unwrap(), 25);
+        match parse_spoilage("--spoilage", "five") {
+            Ok(_) => panic!("Should have errored because the spoilage isn't a number!"),
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("Invalid value for --spoilage: five."));
+            },
+        }
+        assert!(parse_spoilage("--spoilage", "1.125%").is_err());
+        assert!(parse_spoilage("--spoilage", "-5%").is_err());
+    }
+
+    #[test]
+    fn test_parse_options_binding_order() {
+        let result = parse_options(to_args(&[
+            "target/debug/rust-signatures", "1", "60", "--sewing-order", "last-to-first",
+        ]));
+        match result {
+            Ok((_, options)) => {
+                assert_eq!(options.sewing_order, Some(BindingOrder::LastToFirst));
+                assert_eq!(options.gathering_order, None);
+                assert_eq!(options.stocks, None);
+                assert!(options.input_files().is_empty());
+            },
+            Err(result_error) => panic!("parse_options should be returning Ok. Returned Err('{}').", result_error),
+        }
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--gathering-order", "backwards"]));
+        match result {
+            Ok(_) => panic!("parse_options should have rejected the gathering order"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Invalid value for --gathering-order: backwards. Expected one of: first-to-last, last-to-first",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_parse_options_errors() {
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--compare", "16,18"]));
+        match result {
+            Ok(_) => panic!("Should have errored because 18 is not a multiple of 4!"),
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("Signature size must be a positive multiple of 4"));
+            },
+        }
+
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--compare"]));
+        match result {
+            Ok(_) => panic!("Should have errored because --compare has no value!"),
+            Err(result_error) => assert_eq!(format!("{}", result_error), "Option --compare needs a value!"),
+        }
+
+        let result = parse_options(to_args(&["target/debug/rust-signatures", "1", "60", "--bogus"]));
+        match result {
+            Ok(_) => panic!("Should have errored because of an unknown flag!"),
+            Err(result_error) => assert_eq!(format!("{}", result_error), "Unknown option: --bogus"),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// Padding a reading-order PDF out to whole sheets, so imposition software
+// that expects a multiple of four pages gets the plan's blanks, or copies
+// of a filler page in their place. How many blanks the binder has to add,
+// and where, is worked out by a padding strategy.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::config::BlankPolicy;
+use crate::{DocumentInfo, OverflowError};
+
+// Constants
+const PADDED_SUFFIX: &str = "-padded.pdf";
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct BlankPaddingError {
+    num_blanks: u32,
+}
+
+impl Error for BlankPaddingError {}
+
+impl fmt::Display for BlankPaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Adding {} blank pages needs a build with --features pdf, since mutool can't make blank pages.",
+            self.num_blanks,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PaddingStrategy {
+    // blanks after the last page, out to whole sheets
+    #[default]
+    EndOfBook,
+    // blanks after the last page, out to fill the last signature
+    EndOfLastSignature,
+    // the blanks filling the last signature shared between the front and
+    // the back, the front's in whole leaves so page one stays a recto
+    Split,
+}
+
+impl PaddingStrategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaddingStrategy::EndOfBook => "at the end of the book",
+            PaddingStrategy::EndOfLastSignature => "at the end of the last signature",
+            PaddingStrategy::Split => "split between the front and the back",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Padding {
+    pub strategy: PaddingStrategy,
+    // blank pages before the first page and after the last
+    pub front: u32,
+    pub back: u32,
+    pub num_pages: u32,
+}
+
+// A run of the padded book's pages, counted from 1, and the document's
+// pages printed on them, or None for blanks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaddedRange {
+    pub from: u32,
+    pub to: u32,
+    pub pages: Option<(u32, u32)>,
+}
+
+impl Padding {
+    pub fn num_blanks(&self) -> u32 {
+        self.front + self.back
+    }
+
+    pub fn padded_pages(&self) -> u32 {
+        self.num_pages + self.num_blanks()
+    }
+
+    pub fn ranges(&self, first_page: u32) -> Result<Vec<PaddedRange>, Box<dyn Error>> {
+        // every page number checked, since the last pages may be near the
+        // end of a u32
+        let overflow = || OverflowError {first_page, last_page: u32::MAX};
+        let last_page = first_page.checked_add(self.num_pages.saturating_sub(1)).ok_or_else(overflow)?;
+        let last_printed = self.front.checked_add(self.num_pages).ok_or_else(overflow)?;
+        let padded_pages = last_printed.checked_add(self.back).ok_or_else(overflow)?;
+        let mut ranges = Vec::new();
+        if self.front > 0 {
+            ranges.push(PaddedRange {from: 1, to: self.front, pages: None});
+        }
+        ranges.push(PaddedRange {from: self.front + 1, to: last_printed, pages: Some((first_page, last_page))});
+        if self.back > 0 {
+            ranges.push(PaddedRange {from: last_printed + 1, to: padded_pages, pages: None});
+        }
+        Ok(ranges)
+    }
+}
+
+
+// Work
+pub fn padding(document: &DocumentInfo, strategy: PaddingStrategy) -> Padding {
+    let num_blanks = match strategy {
+        PaddingStrategy::EndOfBook => document.num_blank_pages(),
+        PaddingStrategy::EndOfLastSignature | PaddingStrategy::Split => document.pages_short_of_full_signature(),
+    };
+    let front = match strategy {
+        PaddingStrategy::Split => {
+            // half the leaves, rounded down; a one-sided leaf is one page
+            let leaf = if document.is_one_sided() { 1 } else { 2 };
+            num_blanks / leaf / 2 * leaf
+        },
+        _ => 0,
+    };
+    Padding {strategy, front, back: num_blanks - front, num_pages: document.num_pages()}
+}
+
+pub fn display_padding(document: &DocumentInfo, padding: &Padding) -> Result<(), Box<dyn Error>> {
+    // page numbers as the reports show them
+    let page = |page: u32| document.shown_page(page);
+    let ranges = padding.ranges(document.first_page())?;
+    println!("Padding {}: {} blank pages", padding.strategy.name(), padding.num_blanks());
+    if padding.num_blanks() > 0 {
+        println!("Blank pages at the front: {}, at the back: {}", padding.front, padding.back);
+    }
+    for range in ranges {
+        match range.pages {
+            Some((from, to)) => println!("{}-{}: pages {}-{}", range.from, range.to, page(from), page(to)),
+            None => println!("{}-{}: blank", range.from, range.to),
+        }
+    }
+    println!("Padded to {} pages", padding.padded_pages());
+    println!("#####################################");
+    Ok(())
+}
+
+pub fn padded_path(input: &str) -> String {
+    format!("{}{}", crate::split::input_stem(input), PADDED_SUFFIX)
+}
+
+pub fn pdf_padding(document: &DocumentInfo, policy: &BlankPolicy) -> Vec<Option<u32>> {
+    // The padding pages by the input PDF's page numbers, which start at 1
+    // whatever the document's first page is.
+    policy
+        .padding(document)
+        .into_iter()
+        .map(|page| page.map(|page| page - document.first_page() + 1))
+        .collect()
+}
+
+pub fn pad_command(document: &DocumentInfo, input: &str, output: &str, policy: &BlankPolicy) -> Result<Vec<String>, Box<dyn Error>> {
+    // the mutool invocation copying the input with the filler pages after it
+    let mut pages = vec![format!("1-{}", document.num_pages())];
+    for page in pdf_padding(document, policy) {
+        let page = page.ok_or(BlankPaddingError {num_blanks: document.num_blank_pages()})?;
+        pages.push(page.to_string());
+    }
+    Ok(vec![
+        "mutool".to_string(),
+        "merge".to_string(),
+        "-o".to_string(),
+        output.to_string(),
+        input.to_string(),
+        pages.join(","),
+    ])
+}
+
+#[cfg(feature = "pdf")]
+pub fn pad(document: &DocumentInfo, input: &str, output: &str, policy: &BlankPolicy) -> Result<(), Box<dyn Error>> {
+    crate::pdf::pad(input, output, &pdf_padding(document, policy))
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn pad(document: &DocumentInfo, input: &str, output: &str, policy: &BlankPolicy) -> Result<(), Box<dyn Error>> {
+    crate::deimpose::run(&pad_command(document, input, output, policy)?)
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::shell_line;
+
+    #[test]
+    fn test_padding() {
+        // 58 pages: 2 blanks to whole sheets, 6 to fill the last signature
+        let document = DocumentInfo::new(1, 58).unwrap();
+        let end = padding(&document, PaddingStrategy::EndOfBook);
+        assert_eq!((end.front, end.back, end.padded_pages()), (0, 2, 60));
+        assert_eq!(
+            end.ranges(1).unwrap(),
+            vec![PaddedRange {from: 1, to: 58, pages: Some((1, 58))}, PaddedRange {from: 59, to: 60, pages: None}],
+        );
+        let signature = padding(&document, PaddingStrategy::EndOfLastSignature);
+        assert_eq!((signature.front, signature.back, signature.padded_pages()), (0, 6, 64));
+        let split = padding(&document, PaddingStrategy::Split);
+        assert_eq!((split.front, split.back), (2, 4));
+        assert_eq!(split.ranges(1).unwrap()[1], PaddedRange {from: 3, to: 60, pages: Some((1, 58))});
+        assert_eq!(padding(&DocumentInfo::new(1, 64).unwrap(), PaddingStrategy::Split).ranges(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_padding_near_u32_max() {
+        // the last signature ends on the last u32, whichever way it's padded
+        let document = DocumentInfo::new(u32::MAX - 15, u32::MAX).unwrap();
+        for &strategy in [PaddingStrategy::EndOfBook, PaddingStrategy::EndOfLastSignature, PaddingStrategy::Split].iter() {
+            let ranges = padding(&document, strategy).ranges(document.first_page()).unwrap();
+            assert_eq!(ranges, vec![PaddedRange {from: 1, to: 16, pages: Some((u32::MAX - 15, u32::MAX))}]);
+        }
+        let padding = Padding {strategy: PaddingStrategy::EndOfBook, front: 0, back: 2, num_pages: 18};
+        match padding.ranges(u32::MAX - 15) {
+            Ok(ranges) => panic!("Should have errored because page 4294967297 is past the last u32! Got {:?}.", ranges),
+            Err(result_error) => assert!(result_error.is::<OverflowError>()),
+        }
+    }
+
+    #[test]
+    fn test_pad_command() {
+        // pages 5 to 22 with the colophon on page 22 twice more
+        let document = DocumentInfo::new(5, 22).unwrap();
+        assert_eq!(padded_path("books/angler.pdf"), "books/angler-padded.pdf");
+        assert_eq!(pdf_padding(&document, &BlankPolicy::Filler(22)), vec![Some(18), Some(18)]);
+        assert_eq!(
+            pad_command(&document, "angler.pdf", "angler-padded.pdf", &BlankPolicy::Filler(22)).unwrap().join(" "),
+            "mutool merge -o angler-padded.pdf angler.pdf 1-18,18,18",
+        );
+        // paths the shell would split or expand are quoted
+        assert_eq!(
+            shell_line(&pad_command(&document, "my angler.pdf", "my angler-padded.pdf", &BlankPolicy::Filler(22)).unwrap()),
+            "mutool merge -o 'my angler-padded.pdf' 'my angler.pdf' 1-18,18,18",
+        );
+        match pad_command(&document, "angler.pdf", "angler-padded.pdf", &BlankPolicy::AtEnd) {
+            Ok(_) => panic!("Should have errored because mutool can't make blank pages!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Adding 2 blank pages needs a build with --features pdf, since mutool can't make blank pages.",
+            ),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Counting the pages of a PDF, natively with the pdf feature, or else by
+// asking whichever of the common command line PDF tools is installed, so
+// plain builds of the crate can still take a PDF instead of a page range.
+
+use std::error::Error;
+use std::fmt;
+use std::process::Command;
+
+// Constants
+const TOOLS: [&str; 3] = ["pdfinfo", "qpdf", "mutool"];
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct PageCountError {
+    path: String,
+    problems: Vec<String>,
+}
+
+impl Error for PageCountError {}
+
+impl fmt::Display for PageCountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Couldn't count the pages of {}! {}", self.path, self.problems.join("; "))
+    }
+}
+
+
+// Work
+pub fn is_pdf_path(arg: &str) -> bool {
+    arg.to_lowercase().ends_with(".pdf")
+}
+
+pub fn page_count(path: &str) -> Result<u32, Box<dyn Error>> {
+    // Try each tool in turn, taking the first answer that makes sense.
+    let mut problems = Vec::new();
+    #[cfg(feature = "pdf")]
+    match crate::pdf::page_count(path) {
+        Ok(count) => return Ok(count),
+        Err(err) => problems.push(format!("reading it failed ({})", err)),
+    }
+    for tool in TOOLS.iter() {
+        match tool_page_count(tool, path) {
+            Ok(count) => return Ok(count),
+            Err(problem) => problems.push(format!("{} {}", tool, problem)),
+        }
+    }
+    Err((PageCountError {path: path.to_string(), problems}).into())
+}
+
+fn tool_page_count(tool: &str, path: &str) -> Result<u32, String> {
+    let args: &[&str] = match tool {
+        "pdfinfo" => &[path],
+        "qpdf" => &["--show-npages", path],
+        _ => &["info", path],
+    };
+    let output = Command::new(tool).args(args).output().map_err(|_| "isn't installed".to_string())?;
+    if !output.status.success() {
+        return Err(format!("failed ({})", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let count = match tool {
+        "qpdf" => stdout.trim().parse().ok(),
+        _ => parse_pages_line(&stdout),
+    };
+    count.filter(|count| *count > 0).ok_or_else(|| "gave no page count".to_string())
+}
+
+fn parse_pages_line(output: &str) -> Option<u32> {
+    // pdfinfo and mutool info both print a line like "Pages:          212"
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Pages:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pages_line() {
+        let pdfinfo = "Title:          The Compleat Angler\nProducer:       pdfTeX\nPages:          212\nEncrypted:      no\n";
+        assert_eq!(parse_pages_line(pdfinfo), Some(212));
+        let mutool = "book.pdf:\n\nPDF-1.5\nInfo object (1 0 R):\n<</Producer(pdfTeX)>>\nPages: 48\n\nRetrieving info from pages 1-48...\n";
+        assert_eq!(parse_pages_line(mutool), Some(48));
+        assert_eq!(parse_pages_line("Encrypted: no\n"), None);
+    }
+
+    #[test]
+    fn test_is_pdf_path() {
+        assert!(is_pdf_path("book.pdf"));
+        assert!(is_pdf_path("scans/BOOK.PDF"));
+        assert!(!is_pdf_path("60"));
+    }
+}
@@ -0,0 +1,874 @@
+// Reading and writing PDFs natively, for builds with the pdf feature.
+// Without it the same jobs are handed to command line tools instead.
+
+use std::error::Error;
+use std::fmt;
+
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+
+use crate::chase::Forme;
+use crate::contact::ContactSheet;
+use crate::deimpose::{self, PageLabelRange, PdfMetadata};
+use crate::impose::PrinterOffset;
+use crate::imposition::PageSize;
+use crate::pdfx::{PdfxBlocker, OUTPUT_CONDITION_PLACEHOLDER, PDFX_VERSION};
+use crate::stamp::NumeralStyle;
+use crate::DocumentInfo;
+
+
+// Constants
+const POINTS_PER_MILLIMETRE: f64 = 72.0 / 25.4;
+// how deep forms drawn inside forms are looked through
+const MAX_FORM_DEPTH: u32 = 8;
+// a contact sheet's margin, title and labels, in points
+const CONTACT_MARGIN: f32 = 28.0;
+const CONTACT_TITLE_SIZE: f32 = 14.0;
+const CONTACT_LABEL_SIZE: f32 = 9.0;
+// roughly how wide Helvetica is on average for a given size
+const HELVETICA_WIDTH: f32 = 0.5;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct PdfLayoutError {
+    path: String,
+    problem: String,
+}
+
+impl Error for PdfLayoutError {}
+
+impl fmt::Display for PdfLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} doesn't look like an imposed PDF from this plan: {}", self.path, self.problem)
+    }
+}
+
+
+// Data structs
+// what's been found on a page that PDF/X-1a doesn't allow
+#[derive(Debug, Default)]
+struct PdfxScan {
+    transparency: bool,
+    rgb: bool,
+    unembedded_fonts: Vec<String>,
+}
+
+// an input page as a form to draw, with its media box
+type PageForm = (ObjectId, [f32; 4]);
+
+
+// Work
+pub fn page_count(path: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(Document::load(path)?.get_pages().len() as u32)
+}
+
+pub fn deimpose(document: &DocumentInfo, imposed: &str, output: &str, metadata: &PdfMetadata) -> Result<(), Box<dyn Error>> {
+    // Cut every sheet side in two by giving each half its own page, with
+    // the sheet side's content and half of its media box, then put the
+    // halves in reading order in place of the sheet sides.
+    let layout_error = |problem: String| PdfLayoutError {path: imposed.to_string(), problem};
+    let order = deimpose::reading_order(document)?;
+    let mut pdf = Document::load(imposed)?;
+    let sheet_sides: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+    let pages_id = pdf.catalog()?.get(b"Pages")?.as_reference()?;
+    let mut halves = Vec::new();
+    for half in order {
+        let sheet_side = *sheet_sides
+            .get((half as usize - 1) / 2)
+            .ok_or_else(|| layout_error(format!("it has {} pages, too few for the plan", sheet_sides.len())))?;
+        let media_box = inherited(&pdf, sheet_side, b"MediaBox")
+            .and_then(|media_box| rectangle(&media_box))
+            .ok_or_else(|| layout_error("a page has no media box".to_string()))?;
+        let [x1, y1, x2, y2] = media_box;
+        let middle = (x1 + x2) / 2.0;
+        let (left, right) = if half % 2 == 1 { (x1, middle) } else { (middle, x2) };
+        let mut page = pdf.get_dictionary(sheet_side)?.clone();
+        page.set("MediaBox", vec![left.into(), y1.into(), right.into(), y2.into()]);
+        page.remove(b"CropBox");
+        if let Some(resources) = inherited(&pdf, sheet_side, b"Resources") {
+            page.set("Resources", resources);
+        }
+        page.set("Parent", pages_id);
+        halves.push(pdf.add_object(page));
+    }
+    add_signature_outline(&mut pdf, document, &halves)?;
+    let pages = pdf.get_dictionary_mut(pages_id)?;
+    pages.set("Count", halves.len() as i64);
+    pages.set("Kids", halves.into_iter().map(Object::Reference).collect::<Vec<Object>>());
+    set_metadata(&mut pdf, metadata);
+    set_page_labels(&mut pdf, &metadata.page_labels)?;
+    pdf.prune_objects();
+    pdf.save(output)?;
+    Ok(())
+}
+
+pub fn extract_pages(input: &str, output: &str, first_page: u32, last_page: u32) -> Result<(), Box<dyn Error>> {
+    // a copy of the input with only pages first_page to last_page
+    let mut pdf = Document::load(input)?;
+    let num_pages = pdf.get_pages().len() as u32;
+    if last_page > num_pages {
+        let problem = format!("it has {} pages, too few for the plan", num_pages);
+        return Err((PdfLayoutError {path: input.to_string(), problem}).into());
+    }
+    let others: Vec<u32> = (1..=num_pages).filter(|page| *page < first_page || *page > last_page).collect();
+    pdf.delete_pages(&others);
+    pdf.prune_objects();
+    pdf.save(output)?;
+    Ok(())
+}
+
+pub fn pad(input: &str, output: &str, padding: &[Option<u32>]) -> Result<(), Box<dyn Error>> {
+    // Add a page after the last for each padding page: a copy of the given
+    // page, sharing its content, or an empty page the size of the last.
+    let mut pdf = Document::load(input)?;
+    let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+    let pages_id = pdf.catalog()?.get(b"Pages")?.as_reference()?;
+    let last_page = *pages
+        .last()
+        .ok_or_else(|| PdfLayoutError {path: input.to_string(), problem: "it has no pages".to_string()})?;
+    let mut added = Vec::new();
+    for filler in padding {
+        let source = match filler {
+            Some(filler) => *pages.get(*filler as usize - 1).ok_or_else(|| PdfLayoutError {
+                path: input.to_string(),
+                problem: format!("it has {} pages, too few for the plan", pages.len()),
+            })?,
+            None => last_page,
+        };
+        let mut page = Dictionary::new();
+        if filler.is_some() {
+            page = pdf.get_dictionary(source)?.clone();
+            if let Some(resources) = inherited(&pdf, source, b"Resources") {
+                page.set("Resources", resources);
+            }
+        }
+        page.set("Type", "Page");
+        if let Some(media_box) = inherited(&pdf, source, b"MediaBox") {
+            page.set("MediaBox", media_box);
+        }
+        page.set("Parent", pages_id);
+        added.push(pdf.add_object(page));
+    }
+    let root = pdf.get_dictionary_mut(pages_id)?;
+    let count = root.get(b"Count")?.as_i64()?;
+    root.set("Count", count + added.len() as i64);
+    let mut kids = root.get(b"Kids")?.as_array()?.clone();
+    kids.extend(added.into_iter().map(Object::Reference));
+    root.set("Kids", kids);
+    pdf.save(output)?;
+    Ok(())
+}
+
+pub fn set_metadata(pdf: &mut Document, metadata: &PdfMetadata) {
+    // A fresh Info dictionary, so nothing is left over from the input. The
+    // job id, client, due date and plan checksum go in keys of their own,
+    // which viewers list as custom properties.
+    let mut info = Dictionary::new();
+    let mut set = |key: &str, value: &str| info.set(key, text_string(value));
+    let job = &metadata.job;
+    for (key, value) in [
+        ("Title", &job.title),
+        ("Author", &job.author),
+        ("JobID", &job.job_id),
+        ("Client", &job.client),
+        ("DueDate", &job.due),
+    ] {
+        if let Some(value) = value {
+            set(key, value);
+        }
+    }
+    set("PlanChecksum", &metadata.plan_checksum);
+    set("Creator", &metadata.creator());
+    set("Producer", &metadata.creator());
+    let info_id = pdf.add_object(info);
+    pdf.trailer.set("Info", info_id);
+}
+
+pub fn set_page_labels(pdf: &mut Document, ranges: &[PageLabelRange]) -> Result<(), Box<dyn Error>> {
+    // a number tree of label dictionaries by starting page index
+    let mut nums = Vec::new();
+    for range in ranges {
+        let style = match range.numerals {
+            NumeralStyle::Arabic => "D",
+            NumeralStyle::LowerRoman => "r",
+            NumeralStyle::UpperRoman => "R",
+        };
+        let mut label = Dictionary::new();
+        label.set("S", Object::Name(style.as_bytes().to_vec()));
+        label.set("St", i64::from(range.first_number));
+        nums.push(Object::Integer(i64::from(range.start)));
+        nums.push(Object::Dictionary(label));
+    }
+    let mut page_labels = Dictionary::new();
+    page_labels.set("Nums", nums);
+    pdf.catalog_mut()?.set("PageLabels", page_labels);
+    Ok(())
+}
+
+pub fn add_signature_outline(pdf: &mut Document, document: &DocumentInfo, pages: &[ObjectId]) -> Result<(), Box<dyn Error>> {
+    // A bookmark for each signature, going to its first page, with pages
+    // the document's pages in reading order. The viewer opens with the
+    // bookmarks showing.
+    let outlines_id = pdf.new_object_id();
+    let item_ids: Vec<ObjectId> = document.signatures().iter().map(|_| pdf.new_object_id()).collect();
+    for (i, signature) in document.signatures().iter().enumerate() {
+        let page = pages[(signature.first_page() - document.first_page()) as usize];
+        let title = format!(
+            "Signature {}: pages {}\u{2013}{}",
+            signature.key(),
+            document.shown_page(signature.first_page()),
+            document.shown_page(signature.last_page()),
+        );
+        let mut item = Dictionary::new();
+        item.set("Title", text_string(&title));
+        item.set("Parent", outlines_id);
+        item.set("Dest", vec![page.into(), "Fit".into()]);
+        if i > 0 {
+            item.set("Prev", item_ids[i - 1]);
+        }
+        if let Some(next) = item_ids.get(i + 1) {
+            item.set("Next", *next);
+        }
+        pdf.objects.insert(item_ids[i], Object::Dictionary(item));
+    }
+    let mut outlines = Dictionary::new();
+    outlines.set("Type", "Outlines");
+    outlines.set("Count", item_ids.len() as i64);
+    if let (Some(first), Some(last)) = (item_ids.first(), item_ids.last()) {
+        outlines.set("First", *first);
+        outlines.set("Last", *last);
+    }
+    pdf.objects.insert(outlines_id, Object::Dictionary(outlines));
+    let catalog = pdf.catalog_mut()?;
+    catalog.set("Outlines", outlines_id);
+    catalog.set("PageMode", "UseOutlines");
+    Ok(())
+}
+
+pub fn impose_formes(
+    input: &str,
+    output: &str,
+    sheet: PageSize,
+    formes: &[Forme],
+    offset: PrinterOffset,
+) -> Result<(), Box<dyn Error>> {
+    // Each of the input's pages becomes a form drawn into its cell of a
+    // sheet side, scaled to fit and centred, turned round if it stands head
+    // down. The sheet sides then take the place of the input's pages, and
+    // cells for page 0 or pages past the end are left empty. The formes
+    // alternate fronts and backs, and every back is shifted against the
+    // printer's offset.
+    let mut pdf = Document::load(input)?;
+    let pages_id = pdf.catalog()?.get(b"Pages")?.as_reference()?;
+    let forms = page_forms(&mut pdf, input)?;
+    let width = (sheet.width * POINTS_PER_MILLIMETRE) as f32;
+    let height = (sheet.height * POINTS_PER_MILLIMETRE) as f32;
+    let mut sides = Vec::new();
+    for (i, forme) in formes.iter().enumerate() {
+        let cell_height = height / forme.rows.len() as f32;
+        let mut content = String::new();
+        if i % 2 == 1 && !offset.is_zero() {
+            let (right, up) = (-offset.right * POINTS_PER_MILLIMETRE, offset.down * POINTS_PER_MILLIMETRE);
+            content.push_str(&format!("1 0 0 1 {:.2} {:.2} cm\n", right as f32, up as f32));
+        }
+        let mut xobjects = Dictionary::new();
+        for (row_i, row) in forme.rows.iter().enumerate() {
+            let cell_width = width / row.len() as f32;
+            // rows count down from the top of the sheet
+            let y0 = height - cell_height * (row_i + 1) as f32;
+            for (column, cell) in row.iter().enumerate() {
+                let (form, [x1, y1, x2, y2]) = match cell.page.checked_sub(1).and_then(|i| forms.get(i as usize)) {
+                    Some(form) => *form,
+                    None => continue,
+                };
+                let scale = (cell_width / (x2 - x1)).min(cell_height / (y2 - y1));
+                let x0 = cell_width * column as f32 + (cell_width - (x2 - x1) * scale) / 2.0;
+                let y0 = y0 + (cell_height - (y2 - y1) * scale) / 2.0;
+                let matrix = if cell.head_down {
+                    [-scale, x0 + (x2 - x1) * scale + x1 * scale, y0 + (y2 - y1) * scale + y1 * scale]
+                } else {
+                    [scale, x0 - x1 * scale, y0 - y1 * scale]
+                };
+                let name = format!("P{}", cell.page);
+                content.push_str(&format!(
+                    "q {} 0 0 {} {} {} cm /{} Do Q\n",
+                    matrix[0], matrix[0], matrix[1], matrix[2], name,
+                ));
+                xobjects.set(name, form);
+            }
+        }
+        let mut resources = Dictionary::new();
+        resources.set("XObject", xobjects);
+        let mut side = Dictionary::new();
+        side.set("Type", "Page");
+        side.set("Parent", pages_id);
+        side.set("MediaBox", vec![0.into(), 0.into(), width.into(), height.into()]);
+        side.set("Resources", resources);
+        side.set("Contents", pdf.add_object(Stream::new(Dictionary::new(), content.into_bytes())));
+        sides.push(pdf.add_object(side));
+    }
+    let root = pdf.get_dictionary_mut(pages_id)?;
+    root.set("Count", sides.len() as i64);
+    root.set("Kids", sides.into_iter().map(Object::Reference).collect::<Vec<Object>>());
+    pdf.prune_objects();
+    pdf.save(output)?;
+    Ok(())
+}
+
+pub fn contact_sheet(input: &str, sheet: &ContactSheet, size: PageSize) -> Result<(), Box<dyn Error>> {
+    // The signature's pages as thumbnails on one page, in rows from the top,
+    // each framed with its page number underneath and blanks framed empty,
+    // under a title.
+    let mut pdf = Document::load(input)?;
+    let pages_id = pdf.catalog()?.get(b"Pages")?.as_reference()?;
+    let forms = page_forms(&mut pdf, input)?;
+    let width = (size.width * POINTS_PER_MILLIMETRE) as f32;
+    let height = (size.height * POINTS_PER_MILLIMETRE) as f32;
+    let columns = sheet.columns();
+    let cell_width = (width - 2.0 * CONTACT_MARGIN) / columns as f32;
+    let cell_height = (height - 2.0 * CONTACT_MARGIN - 2.0 * CONTACT_TITLE_SIZE) / sheet.rows() as f32;
+    // each thumbnail inset in its cell, above its label
+    let inset = CONTACT_LABEL_SIZE / 2.0;
+    let (box_width, box_height) = (cell_width - 2.0 * inset, cell_height - 3.0 * inset - CONTACT_LABEL_SIZE);
+    let mut content = format!(
+        "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n0.5 w\n",
+        CONTACT_TITLE_SIZE,
+        CONTACT_MARGIN,
+        height - CONTACT_MARGIN - CONTACT_TITLE_SIZE,
+        sheet.title,
+    );
+    let mut xobjects = Dictionary::new();
+    for (i, cell) in sheet.cells.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        let x0 = CONTACT_MARGIN + cell_width * column as f32 + inset;
+        let y0 = height - CONTACT_MARGIN - 2.0 * CONTACT_TITLE_SIZE - cell_height * (row + 1) as f32;
+        let label_width = cell.label.chars().count() as f32 * CONTACT_LABEL_SIZE * HELVETICA_WIDTH;
+        content.push_str(&format!(
+            "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+            CONTACT_LABEL_SIZE,
+            x0 + (box_width - label_width) / 2.0,
+            y0 + inset,
+            cell.label,
+        ));
+        let y0 = y0 + 2.0 * inset + CONTACT_LABEL_SIZE;
+        let (form, [x1, y1, x2, y2]) = match cell.page.checked_sub(1).and_then(|i| forms.get(i as usize)) {
+            Some(form) => *form,
+            None => {
+                content.push_str(&format!("{:.2} {:.2} {:.2} {:.2} re S\n", x0, y0, box_width, box_height));
+                continue;
+            },
+        };
+        let scale = (box_width / (x2 - x1)).min(box_height / (y2 - y1));
+        let (thumb_width, thumb_height) = ((x2 - x1) * scale, (y2 - y1) * scale);
+        let x0 = x0 + (box_width - thumb_width) / 2.0;
+        let y0 = y0 + (box_height - thumb_height) / 2.0;
+        let name = format!("P{}", cell.page);
+        content.push_str(&format!(
+            "q {} 0 0 {} {} {} cm /{} Do Q\n{:.2} {:.2} {:.2} {:.2} re S\n",
+            scale,
+            scale,
+            x0 - x1 * scale,
+            y0 - y1 * scale,
+            name,
+            x0,
+            y0,
+            thumb_width,
+            thumb_height,
+        ));
+        xobjects.set(name, form);
+    }
+    let mut font = Dictionary::new();
+    font.set("Type", "Font");
+    font.set("Subtype", "Type1");
+    font.set("BaseFont", "Helvetica");
+    let mut fonts = Dictionary::new();
+    fonts.set("F1", font);
+    let mut resources = Dictionary::new();
+    resources.set("XObject", xobjects);
+    resources.set("Font", fonts);
+    let mut page = Dictionary::new();
+    page.set("Type", "Page");
+    page.set("Parent", pages_id);
+    page.set("MediaBox", vec![0.into(), 0.into(), width.into(), height.into()]);
+    page.set("Resources", resources);
+    page.set("Contents", pdf.add_object(Stream::new(Dictionary::new(), content.into_bytes())));
+    let page = pdf.add_object(page);
+    let root = pdf.get_dictionary_mut(pages_id)?;
+    root.set("Count", 1);
+    root.set("Kids", vec![Object::Reference(page)]);
+    pdf.prune_objects();
+    pdf.save(&sheet.path)?;
+    Ok(())
+}
+
+pub fn make_pdfx(path: &str) -> Result<Vec<PdfxBlocker>, Box<dyn Error>> {
+    // Mark the file as PDF/X-1a in place: PDF 1.3, an output intent, a trim
+    // box on every page, the keys the Info dictionary needs and an ID, with
+    // annotations and forms taken out. Whatever still keeps it from
+    // complying is returned, pages counted from 1.
+    let mut pdf = Document::load(path)?;
+    let mut blockers = Vec::new();
+    if pdf.is_encrypted() {
+        blockers.push(PdfxBlocker::Encrypted);
+    }
+    let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+    let mut fonts = Vec::new();
+    for (i, page) in pages.iter().enumerate() {
+        let mut scan = PdfxScan {transparency: is_transparency_group(&pdf, pdf.get_dictionary(*page)?), ..PdfxScan::default()};
+        if let Some(resources) = inherited(&pdf, *page, b"Resources") {
+            scan_resources(&pdf, &resources, &mut scan, 0);
+        }
+        if let Ok(content) = pdf.get_and_decode_page_content(*page) {
+            scan.rgb |= content.operations.iter().any(|operation| operation.operator == "rg" || operation.operator == "RG");
+        }
+        let number = i as u32 + 1;
+        if scan.transparency {
+            blockers.push(PdfxBlocker::Transparency(number));
+        }
+        if scan.rgb {
+            blockers.push(PdfxBlocker::RgbColour(number));
+        }
+        for font in scan.unembedded_fonts {
+            if !fonts.contains(&font) {
+                fonts.push(font);
+            }
+        }
+    }
+    blockers.extend(fonts.into_iter().map(PdfxBlocker::UnembeddedFont));
+
+    pdf.version = "1.3".to_string();
+    for page in &pages {
+        let media_box = inherited(&pdf, *page, b"MediaBox");
+        let dictionary = pdf.get_dictionary_mut(*page)?;
+        dictionary.remove(b"Annots");
+        if let (Err(_), Some(media_box)) = (dictionary.get(b"TrimBox"), media_box) {
+            dictionary.set("TrimBox", media_box);
+        }
+    }
+    let mut intent = Dictionary::new();
+    intent.set("Type", "OutputIntent");
+    intent.set("S", "GTS_PDFX");
+    intent.set("OutputConditionIdentifier", text_string(OUTPUT_CONDITION_PLACEHOLDER));
+    intent.set("RegistryName", text_string("http://www.color.org"));
+    intent.set("Info", text_string("Replace with the printer's output condition"));
+    let catalog = pdf.catalog_mut()?;
+    catalog.remove(b"AcroForm");
+    catalog.set("OutputIntents", vec![Object::Dictionary(intent)]);
+    let info_id = match pdf.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(info_id) => info_id,
+        Err(_) => {
+            let info_id = pdf.add_object(Dictionary::new());
+            pdf.trailer.set("Info", info_id);
+            info_id
+        },
+    };
+    let info = pdf.get_dictionary_mut(info_id)?;
+    info.set("GTS_PDFXVersion", text_string("PDF/X-1:2001"));
+    info.set("GTS_PDFXConformance", text_string(PDFX_VERSION));
+    info.set("Trapped", "False");
+    let id = Object::String(crate::fnv1a(path.as_bytes()).to_be_bytes().to_vec(), StringFormat::Hexadecimal);
+    pdf.trailer.set("ID", vec![id.clone(), id]);
+    pdf.save(path)?;
+    Ok(blockers)
+}
+
+fn page_forms(pdf: &mut Document, input: &str) -> Result<Vec<PageForm>, Box<dyn Error>> {
+    // each of the input's pages as a form to draw elsewhere, with its media box
+    let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+    let mut forms = Vec::new();
+    for page in &pages {
+        let media_box = inherited(pdf, *page, b"MediaBox")
+            .and_then(|media_box| rectangle(&media_box))
+            .ok_or_else(|| PdfLayoutError {path: input.to_string(), problem: "a page has no media box".to_string()})?;
+        let mut form = Dictionary::new();
+        form.set("Type", "XObject");
+        form.set("Subtype", "Form");
+        form.set("BBox", media_box.iter().map(|&edge| edge.into()).collect::<Vec<Object>>());
+        if let Some(resources) = inherited(pdf, *page, b"Resources") {
+            form.set("Resources", resources);
+        }
+        let content = pdf.get_page_content(*page)?;
+        forms.push((pdf.add_object(Stream::new(form, content)), media_box));
+    }
+    Ok(forms)
+}
+
+fn scan_resources(pdf: &Document, resources: &Object, scan: &mut PdfxScan, depth: u32) {
+    // the graphics states, colour spaces, images, forms and fonts a page or
+    // form draws with
+    let resources = match resolve(pdf, resources).and_then(|resources| resources.as_dict().ok()) {
+        Some(resources) => resources,
+        None => return,
+    };
+    for state in resource_entries(pdf, resources, b"ExtGState") {
+        let state = match state.as_dict() {
+            Ok(state) => state,
+            Err(_) => continue,
+        };
+        let soft_mask = state.get(b"SMask").is_ok_and(|mask| mask.as_name().ok() != Some(b"None"));
+        let alpha = [b"CA".as_slice(), b"ca"].iter().any(|key| state.get(key).and_then(Object::as_float).is_ok_and(|alpha| alpha < 1.0));
+        let blend = state.get(b"BM").and_then(Object::as_name).is_ok_and(|mode| mode != b"Normal" && mode != b"Compatible");
+        scan.transparency |= soft_mask || alpha || blend;
+    }
+    for colour_space in resource_entries(pdf, resources, b"ColorSpace") {
+        scan.rgb |= is_rgb(pdf, colour_space);
+    }
+    for xobject in resource_entries(pdf, resources, b"XObject") {
+        let xobject = match xobject.as_stream() {
+            Ok(xobject) => &xobject.dict,
+            Err(_) => continue,
+        };
+        match xobject.get(b"Subtype").and_then(Object::as_name) {
+            Ok(b"Image") => {
+                scan.transparency |= xobject.get(b"SMask").is_ok();
+                scan.rgb |= xobject.get(b"ColorSpace").is_ok_and(|colour_space| is_rgb(pdf, colour_space));
+            },
+            Ok(b"Form") => {
+                scan.transparency |= is_transparency_group(pdf, xobject);
+                if let (Ok(resources), true) = (xobject.get(b"Resources"), depth < MAX_FORM_DEPTH) {
+                    scan_resources(pdf, resources, scan, depth + 1);
+                }
+            },
+            _ => (),
+        }
+    }
+    for font in resource_entries(pdf, resources, b"Font") {
+        let font = match font.as_dict() {
+            Ok(font) => font,
+            Err(_) => continue,
+        };
+        // Type 3 fonts are drawn with the file's own content
+        if font.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type3") {
+            continue;
+        }
+        let descendant = font
+            .get(b"DescendantFonts")
+            .ok()
+            .and_then(|fonts| resolve(pdf, fonts))
+            .and_then(|fonts| fonts.as_array().ok()?.first())
+            .and_then(|descendant| resolve(pdf, descendant)?.as_dict().ok());
+        let descriptor = descendant
+            .unwrap_or(font)
+            .get(b"FontDescriptor")
+            .ok()
+            .and_then(|descriptor| resolve(pdf, descriptor)?.as_dict().ok());
+        let embedded = descriptor.is_some_and(|descriptor| {
+            [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"].iter().any(|key| descriptor.get(key).is_ok())
+        });
+        let name = font.get(b"BaseFont").and_then(Object::as_name).map_or("unnamed".to_string(), |name| {
+            String::from_utf8_lossy(name).to_string()
+        });
+        if !embedded && !scan.unembedded_fonts.contains(&name) {
+            scan.unembedded_fonts.push(name);
+        }
+    }
+}
+
+fn resource_entries<'a>(pdf: &'a Document, resources: &'a Dictionary, key: &[u8]) -> Vec<&'a Object> {
+    // the values of one kind of resource, e.g. every font
+    match resources.get(key).ok().and_then(|entries| resolve(pdf, entries)).and_then(|entries| entries.as_dict().ok()) {
+        Some(entries) => entries.iter().filter_map(|(_, entry)| resolve(pdf, entry)).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn is_transparency_group(pdf: &Document, dictionary: &Dictionary) -> bool {
+    dictionary
+        .get(b"Group")
+        .ok()
+        .and_then(|group| resolve(pdf, group)?.as_dict().ok())
+        .is_some_and(|group| group.get(b"S").and_then(Object::as_name).ok() == Some(b"Transparency"))
+}
+
+fn is_rgb(pdf: &Document, colour_space: &Object) -> bool {
+    // RGB itself, or an indexed, separation or ICC space built on it
+    let colour_space = match resolve(pdf, colour_space) {
+        Some(colour_space) => colour_space,
+        None => return false,
+    };
+    if let Ok(name) = colour_space.as_name() {
+        return name == b"DeviceRGB" || name == b"CalRGB";
+    }
+    let family = match colour_space.as_array() {
+        Ok(family) => family,
+        Err(_) => return false,
+    };
+    match family.first().and_then(|name| name.as_name().ok()) {
+        Some(b"CalRGB") => true,
+        Some(b"ICCBased") => family
+            .get(1)
+            .and_then(|profile| resolve(pdf, profile)?.as_stream().ok())
+            .is_some_and(|profile| profile.dict.get(b"N").and_then(Object::as_i64).ok() == Some(3)),
+        Some(b"Indexed") => family.get(1).is_some_and(|base| is_rgb(pdf, base)),
+        Some(b"Separation") | Some(b"DeviceN") => family.get(2).is_some_and(|alternate| is_rgb(pdf, alternate)),
+        _ => false,
+    }
+}
+
+fn resolve<'a>(pdf: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    pdf.dereference(object).ok().map(|(_, object)| object)
+}
+
+fn text_string(value: &str) -> Object {
+    // plain ASCII as it is, anything else as UTF-16 with a byte order mark
+    if value.is_ascii() {
+        return Object::string_literal(value);
+    }
+    let mut bytes = vec![0xfe, 0xff];
+    bytes.extend(value.encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+fn inherited(pdf: &Document, page: ObjectId, key: &[u8]) -> Option<Object> {
+    // a page attribute, looking up the page tree for one set on a parent
+    let mut node = page;
+    loop {
+        let dictionary = pdf.get_dictionary(node).ok()?;
+        if let Ok(value) = dictionary.get(key) {
+            return Some(value.clone());
+        }
+        node = dictionary.get(b"Parent").and_then(Object::as_reference).ok()?;
+    }
+}
+
+fn rectangle(object: &Object) -> Option<[f32; 4]> {
+    match object.as_array().ok()?.as_slice() {
+        [x1, y1, x2, y2] => Some([x1.as_float().ok()?, y1.as_float().ok()?, x2.as_float().ok()?, y2.as_float().ok()?]),
+        _ => None,
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobInfo;
+    use lopdf::dictionary;
+    use std::env;
+
+    fn imposed_pdf(path: &str, num_sides: u32) {
+        // landscape A4 sheet sides, each with a little content stream
+        let mut pdf = Document::with_version("1.5");
+        let pages_id = pdf.new_object_id();
+        let kids: Vec<Object> = (0..num_sides)
+            .map(|i| {
+                let content = pdf.add_object(Stream::new(dictionary! {}, format!("% side {}", i).into_bytes()));
+                pdf.add_object(dictionary! {"Type" => "Page", "Parent" => pages_id, "Contents" => content}).into()
+            })
+            .collect();
+        pdf.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => num_sides as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 842.into(), 595.into()],
+            }),
+        );
+        let catalog_id = pdf.add_object(dictionary! {"Type" => "Catalog", "Pages" => pages_id});
+        pdf.trailer.set("Root", catalog_id);
+        pdf.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_deimpose() {
+        let dir = env::temp_dir();
+        let imposed = dir.join("rust-signatures-test-imposed.pdf").to_string_lossy().to_string();
+        let output = dir.join("rust-signatures-test-reading.pdf").to_string_lossy().to_string();
+        // - 1 | 2 - | 6 3 | 4 5
+        imposed_pdf(&imposed, 4);
+        assert_eq!(page_count(&imposed).unwrap(), 4);
+
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let metadata = PdfMetadata {
+            job: JobInfo {title: Some("Poems".to_string()), client: Some("Marriot".to_string()), ..JobInfo::default()},
+            page_labels: deimpose::page_labels(&document, 2),
+            ..PdfMetadata::for_plan(&document)
+        };
+        deimpose(&document, &imposed, &output, &metadata).unwrap();
+        let pdf = Document::load(&output).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 6);
+        // page 1 is the right half of the first side, page 3 the right of the third
+        let media_box = |page: ObjectId| rectangle(pdf.get_dictionary(page).unwrap().get(b"MediaBox").unwrap());
+        assert_eq!(media_box(pages[0]), Some([421.0, 0.0, 842.0, 595.0]));
+        assert_eq!(media_box(pages[1]), Some([0.0, 0.0, 421.0, 595.0]));
+        let info_id = pdf.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        let info = pdf.get_dictionary(info_id).unwrap();
+        assert_eq!(info.get(b"Title").unwrap().as_str().unwrap(), b"Poems");
+        assert_eq!(info.get(b"PlanChecksum").unwrap().as_str().unwrap(), document.checksum().as_bytes());
+        assert_eq!(info.get(b"Client").unwrap().as_str().unwrap(), b"Marriot");
+        assert!(info.get(b"Author").is_err());
+
+        // one bookmark, for the one signature
+        let outlines_id = pdf.catalog().unwrap().get(b"Outlines").unwrap().as_reference().unwrap();
+        let outlines = pdf.get_dictionary(outlines_id).unwrap();
+        assert_eq!(outlines.get(b"Count").unwrap().as_i64().unwrap(), 1);
+        let item = pdf.get_dictionary(outlines.get(b"First").unwrap().as_reference().unwrap()).unwrap();
+        assert_eq!(item.get(b"Title").unwrap(), &text_string("Signature A: pages 1\u{2013}6"));
+        let dest = item.get(b"Dest").unwrap().as_array().unwrap();
+        assert_eq!(dest[0].as_reference().unwrap(), pages[0]);
+
+        // i-ii then 1-4
+        let page_labels = pdf.catalog().unwrap().get(b"PageLabels").unwrap().as_dict().unwrap();
+        let nums = page_labels.get(b"Nums").unwrap().as_array().unwrap();
+        assert_eq!(nums.len(), 4);
+        assert_eq!(nums[1].as_dict().unwrap().get(b"S").unwrap().as_name().unwrap(), b"r");
+        assert_eq!(nums[2].as_i64().unwrap(), 2);
+        assert_eq!(nums[3].as_dict().unwrap().get(b"St").unwrap().as_i64().unwrap(), 1);
+
+        // the third and fourth sides on their own
+        let extract = dir.join("rust-signatures-test-extract.pdf").to_string_lossy().to_string();
+        extract_pages(&imposed, &extract, 3, 4).unwrap();
+        assert_eq!(page_count(&extract).unwrap(), 2);
+        assert!(extract_pages(&imposed, &extract, 3, 5).is_err());
+
+        let document = DocumentInfo::new(1, 20).unwrap();
+        assert!(deimpose(&document, &imposed, &output, &metadata).is_err());
+    }
+
+    #[test]
+    fn test_deimpose_signature_size() {
+        // twelve pages imposed in signatures of 8, then cut back apart with
+        // the same plan: each page's half of its sheet side is where it
+        // was drawn
+        let dir = env::temp_dir();
+        let input = dir.join("rust-signatures-test-eights-pages.pdf").to_string_lossy().to_string();
+        let imposed = dir.join("rust-signatures-test-eights-imposed.pdf").to_string_lossy().to_string();
+        let output = dir.join("rust-signatures-test-eights-reading.pdf").to_string_lossy().to_string();
+        imposed_pdf(&input, 12);
+        let mut config = crate::config::PlanConfig::new(1, 12);
+        config.pages_per_signature = 8;
+        let document = config.plan().unwrap();
+        let formes = crate::impose::sheet_formes(&document, None, crate::imposition::BookletFold::Side);
+        impose_formes(&input, &imposed, PageSize {width: 297.0, height: 210.0}, &formes, PrinterOffset::default()).unwrap();
+        deimpose(&document, &imposed, &output, &PdfMetadata::for_plan(&document)).unwrap();
+        let pdf = Document::load(&output).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 12);
+        for (i, page) in pages.iter().enumerate() {
+            let [x1, _, x2, _] = rectangle(pdf.get_dictionary(*page).unwrap().get(b"MediaBox").unwrap()).unwrap();
+            let content = String::from_utf8(pdf.get_page_content(*page).unwrap()).unwrap();
+            let drawn = content.lines().find(|line| line.contains(&format!("/P{} ", i + 1))).unwrap();
+            let x: f32 = drawn.split_whitespace().nth(5).unwrap().parse().unwrap();
+            assert!(x1 <= x && x < x2, "page {} is drawn at {}, outside {}-{}", i + 1, x, x1, x2);
+        }
+    }
+
+    #[test]
+    fn test_impose_formes() {
+        let dir = env::temp_dir();
+        let input = dir.join("rust-signatures-test-zine-pages.pdf").to_string_lossy().to_string();
+        let output = dir.join("rust-signatures-test-zine.pdf").to_string_lossy().to_string();
+        // six pages on the mini zine's eight cells, the last two left empty
+        imposed_pdf(&input, 6);
+        let format = crate::zine::ZineFormat::Mini;
+        impose_formes(&input, &output, format.sheet(), &format.sides(), PrinterOffset::default()).unwrap();
+        let pdf = Document::load(&output).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 1);
+        let media_box = rectangle(pdf.get_dictionary(pages[0]).unwrap().get(b"MediaBox").unwrap()).unwrap();
+        assert!((media_box[2] - 792.0).abs() < 0.1 && (media_box[3] - 612.0).abs() < 0.1);
+        let content = String::from_utf8(pdf.get_page_content(pages[0]).unwrap()).unwrap();
+        assert_eq!(content.lines().count(), 6);
+        assert!(!content.contains("/P7 ") && !content.contains("/P8 "));
+        // the top row is turned round
+        let line = |page: &str| content.lines().find(|line| line.contains(page)).unwrap().to_string();
+        assert!(line("/P5 ").starts_with("q -"));
+        assert!(!line("/P1 ").starts_with("q -"));
+
+        // a plan's sheet sides, with the blanks as page 0: - 1 | 2 - | 6 3 | 4 5
+        let document = DocumentInfo::with_signature_size(1, 6, 8).unwrap();
+        let formes = crate::impose::sheet_formes(&document, None, crate::imposition::BookletFold::Side);
+        let offset = PrinterOffset {right: 1.0, down: -0.5};
+        impose_formes(&input, &output, PageSize {width: 297.0, height: 210.0}, &formes, offset).unwrap();
+        let pdf = Document::load(&output).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 4);
+        let content = String::from_utf8(pdf.get_page_content(pages[0]).unwrap()).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("/P1 "));
+        // the backs shifted a millimetre left and half a millimetre up
+        let content = String::from_utf8(pdf.get_page_content(pages[1]).unwrap()).unwrap();
+        assert!(content.starts_with("1 0 0 1 -2.83 -1.42 cm\n"));
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let dir = env::temp_dir();
+        let input = dir.join("rust-signatures-test-contact-pages.pdf").to_string_lossy().to_string();
+        imposed_pdf(&input, 6);
+        // six pages in a signature of eight, the last two blank
+        let document = DocumentInfo::with_signature_size(1, 6, 8).unwrap();
+        let mut sheet = crate::contact::contact_sheets(&document, &input).remove(0);
+        sheet.path = dir.join("rust-signatures-test-contact.pdf").to_string_lossy().to_string();
+        contact_sheet(&input, &sheet, crate::contact::CONTACT_SHEET_SIZE).unwrap();
+        let pdf = Document::load(&sheet.path).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 1);
+        let content = String::from_utf8(pdf.get_page_content(pages[0]).unwrap()).unwrap();
+        assert!(content.contains("(Signature A: pages 1-6) Tj"));
+        assert_eq!(content.lines().filter(|line| line.ends_with(" Do Q")).count(), 6);
+        assert_eq!(content.lines().filter(|line| line.ends_with("(blank) Tj ET")).count(), 2);
+        assert_eq!(content.lines().filter(|line| line.ends_with(" re S")).count(), 8);
+    }
+
+    #[test]
+    fn test_make_pdfx() {
+        let dir = env::temp_dir();
+        let path = dir.join("rust-signatures-test-pdfx.pdf").to_string_lossy().to_string();
+        imposed_pdf(&path, 3);
+        // RGB text and a half-transparent graphics state on the second
+        // page, and an unembedded font on the third
+        let mut pdf = Document::load(&path).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        let content = pdf.add_object(Stream::new(dictionary! {}, b"1 0 0 rg 0 0 10 10 re f".to_vec()));
+        let state = dictionary! {"ExtGState" => dictionary! {"GS1" => dictionary! {"ca" => 0.5}}};
+        let page = pdf.get_dictionary_mut(pages[1]).unwrap();
+        page.set("Contents", content);
+        page.set("Resources", state);
+        page.set("Annots", Vec::<Object>::new());
+        let font = dictionary! {"Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica"};
+        pdf.get_dictionary_mut(pages[2]).unwrap().set("Resources", dictionary! {"Font" => dictionary! {"F1" => font}});
+        pdf.save(&path).unwrap();
+
+        let blockers = make_pdfx(&path).unwrap();
+        assert_eq!(
+            blockers,
+            vec![
+                PdfxBlocker::Transparency(2),
+                PdfxBlocker::RgbColour(2),
+                PdfxBlocker::UnembeddedFont("Helvetica".to_string()),
+            ],
+        );
+        let pdf = Document::load(&path).unwrap();
+        assert_eq!(pdf.version, "1.3");
+        let intents = pdf.catalog().unwrap().get(b"OutputIntents").unwrap().as_array().unwrap();
+        assert_eq!(intents[0].as_dict().unwrap().get(b"S").unwrap().as_name().unwrap(), b"GTS_PDFX");
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        let page = pdf.get_dictionary(pages[1]).unwrap();
+        assert_eq!(rectangle(page.get(b"TrimBox").unwrap()), Some([0.0, 0.0, 842.0, 595.0]));
+        assert!(page.get(b"Annots").is_err());
+        let info = pdf.get_dictionary(pdf.trailer.get(b"Info").unwrap().as_reference().unwrap()).unwrap();
+        assert_eq!(info.get(b"GTS_PDFXConformance").unwrap().as_str().unwrap(), PDFX_VERSION.as_bytes());
+        assert!(pdf.trailer.get(b"ID").is_ok());
+    }
+
+    #[test]
+    fn test_pad() {
+        let dir = env::temp_dir();
+        let input = dir.join("rust-signatures-test-unpadded.pdf").to_string_lossy().to_string();
+        let output = dir.join("rust-signatures-test-padded.pdf").to_string_lossy().to_string();
+        imposed_pdf(&input, 6);
+        pad(&input, &output, &[Some(2), None]).unwrap();
+        let pdf = Document::load(&output).unwrap();
+        let pages: Vec<ObjectId> = pdf.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 8);
+        // the filler shares the second page's content, the blank has none
+        let page = |i: usize| pdf.get_dictionary(pages[i]).unwrap();
+        assert_eq!(page(6).get(b"Contents").unwrap(), page(1).get(b"Contents").unwrap());
+        assert!(page(7).get(b"Contents").is_err());
+        assert_eq!(rectangle(page(7).get(b"MediaBox").unwrap()), Some([0.0, 0.0, 842.0, 595.0]));
+        assert!(pad(&input, &output, &[Some(7)]).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+// PDF/X-1a for the signature files going to a commercial printer, for builds
+// with the pdf feature: each file is marked with an output intent, given
+// trim boxes and flattened of annotations and forms. What marking can't put
+// right, transparency, RGB colour and fonts that aren't embedded, is found
+// and reported, to be fixed in the source before the files go out.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::split::SplitFile;
+use crate::DocumentInfo;
+
+// Constants
+pub const PDFX_VERSION: &str = "PDF/X-1a:2001";
+// the output condition marked, until the printer gives theirs
+pub const OUTPUT_CONDITION_PLACEHOLDER: &str = "Custom";
+
+
+// Custom errors
+#[cfg(not(feature = "pdf"))]
+#[derive(Debug)]
+pub(crate) struct PdfxNotBuiltError;
+
+#[cfg(not(feature = "pdf"))]
+impl Error for PdfxNotBuiltError {}
+
+#[cfg(not(feature = "pdf"))]
+impl fmt::Display for PdfxNotBuiltError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Writing {} files needs a build with --features pdf.", PDFX_VERSION)
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfxBlocker {
+    // on a page of the document: soft masks, constant alpha below 1, blend
+    // modes or transparency groups
+    Transparency(u32),
+    RgbColour(u32),
+    // by its base font name
+    UnembeddedFont(String),
+    Encrypted,
+}
+
+impl fmt::Display for PdfxBlocker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PdfxBlocker::Transparency(page) => write!(f, "page {} uses transparency, which has to be flattened", page),
+            PdfxBlocker::RgbColour(page) => write!(f, "page {} uses RGB colour, which has to be CMYK or grey", page),
+            PdfxBlocker::UnembeddedFont(name) => write!(f, "the font {} isn't embedded", name),
+            PdfxBlocker::Encrypted => write!(f, "the file is encrypted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfxReport {
+    pub signature_key: String,
+    pub path: String,
+    pub blockers: Vec<PdfxBlocker>,
+}
+
+
+// Work
+pub fn document_pages(document: &DocumentInfo, file: &SplitFile, blockers: Vec<PdfxBlocker>) -> Vec<PdfxBlocker> {
+    // from the file's pages, counted from 1, to the document's
+    let page = |page: u32| document.shown_page(file.first_page + page - 1);
+    blockers
+        .into_iter()
+        .map(|blocker| match blocker {
+            PdfxBlocker::Transparency(number) => PdfxBlocker::Transparency(page(number)),
+            PdfxBlocker::RgbColour(number) => PdfxBlocker::RgbColour(page(number)),
+            blocker => blocker,
+        })
+        .collect()
+}
+
+#[cfg(feature = "pdf")]
+pub fn convert(document: &DocumentInfo, files: &[SplitFile]) -> Result<Vec<PdfxReport>, Box<dyn Error>> {
+    // each signature's file marked in place
+    files
+        .iter()
+        .map(|file| {
+            Ok(PdfxReport {
+                signature_key: file.signature_key.clone(),
+                path: file.path.clone(),
+                blockers: document_pages(document, file, crate::pdf::make_pdfx(&file.path)?),
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn convert(_document: &DocumentInfo, _files: &[SplitFile]) -> Result<Vec<PdfxReport>, Box<dyn Error>> {
+    Err(PdfxNotBuiltError.into())
+}
+
+pub fn display_pdfx_reports(reports: &[PdfxReport]) {
+    for report in reports {
+        if report.blockers.is_empty() {
+            println!("Signature {} ({}): marked {}", report.signature_key, report.path, PDFX_VERSION);
+            continue;
+        }
+        println!(
+            "Signature {} ({}): marked {}, but won't comply until fixed at source:",
+            report.signature_key, report.path, PDFX_VERSION,
+        );
+        for blocker in &report.blockers {
+            println!("  {}", blocker);
+        }
+    }
+    println!("Output intent: {}, to be replaced with the printer's output condition", OUTPUT_CONDITION_PLACEHOLDER);
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_pages() {
+        // the second signature's file starts at page 17
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let files = crate::split::split_files(&document, "book.pdf");
+        let blockers = vec![
+            PdfxBlocker::Transparency(1),
+            PdfxBlocker::RgbColour(4),
+            PdfxBlocker::UnembeddedFont("Helvetica".to_string()),
+        ];
+        let blockers = document_pages(&document, &files[1], blockers);
+        assert_eq!(blockers[0], PdfxBlocker::Transparency(17));
+        assert_eq!(format!("{}", blockers[1]), "page 20 uses RGB colour, which has to be CMYK or grey");
+        assert_eq!(format!("{}", blockers[2]), "the font Helvetica isn't embedded");
+    }
+
+    #[cfg(not(feature = "pdf"))]
+    #[test]
+    fn test_convert_not_built() {
+        let document = DocumentInfo::new(1, 16).unwrap();
+        match convert(&document, &crate::split::split_files(&document, "book.pdf")) {
+            Ok(_) => panic!("Should have errored because pdf isn't built in!"),
+            Err(result_error) => {
+                assert_eq!(format!("{}", result_error), "Writing PDF/X-1a:2001 files needs a build with --features pdf.")
+            },
+        }
+    }
+}
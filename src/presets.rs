@@ -0,0 +1,173 @@
+// Starting points for kinds of book with settled ways of being made, so the
+// usual constraints don't have to be given one by one each time. Anything
+// given on the command line as well is added to the preset's. Conventions
+// are the same for the way a country's printers key their signatures and
+// sign their sheets, which go together.
+
+use crate::constraints::Constraint;
+use crate::labels::{Alphabet, Continuation, LabelScheme};
+use crate::stamp::SheetSigning;
+use crate::DocumentInfo;
+
+// Constants
+// small gatherings sewn flat open better on a music stand
+const MUSIC_PAGES_PER_SIGNATURE: u32 = 8;
+// in millimetres, the least most universities allow at the bound edge
+const THESIS_BINDING_MARGIN: f64 = 40.0;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    Music,
+    Thesis,
+}
+
+impl Preset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Music => "Music score",
+            Preset::Thesis => "Thesis or dissertation",
+        }
+    }
+
+    pub fn constraints(&self) -> Vec<Constraint> {
+        match self {
+            Preset::Music => vec![Constraint::MaxSize(MUSIC_PAGES_PER_SIGNATURE)],
+            // the title page, page 1, is a recto whatever the size
+            Preset::Thesis => Vec::new(),
+        }
+    }
+
+    pub fn binding_margin(&self) -> Option<f64> {
+        // the margin the pages have to leave at the bound edge, if any
+        match self {
+            Preset::Music => None,
+            Preset::Thesis => Some(THESIS_BINDING_MARGIN),
+        }
+    }
+
+    pub fn notes(&self) -> &'static str {
+        // how the book is put together, beyond what the plan covers
+        match self {
+            Preset::Music => {
+                "Sew on tapes with a flat, unrounded spine, or use a spiral or concertina binding, so the \
+                 score lies flat on the stand. Mark each page turned from mid-piece with --page-turn."
+            },
+            Preset::Thesis => {
+                "Number the front matter in roman numerals with --front-matter, leave the verso after the \
+                 title page blank, and add --one-sided where the regulations ask for printing on one side."
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Convention {
+    BritishTrade,
+    German,
+    French,
+}
+
+impl Convention {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Convention::BritishTrade => "British trade",
+            Convention::German => "German",
+            Convention::French => "French",
+        }
+    }
+
+    pub fn label_scheme(&self) -> LabelScheme {
+        // British printers leave out J, V and W, and double the letters
+        // after Z; the continental 23-letter alphabet has no J, U or W, and
+        // German printers carry on Aa, Bb where French ones double
+        let (skip_letters, continuation) = match self {
+            Convention::BritishTrade => (vec!['J', 'V', 'W'], Continuation::Doubled),
+            Convention::German => (vec!['J', 'U', 'W'], Continuation::Paired),
+            Convention::French => (vec!['J', 'U', 'W'], Continuation::Doubled),
+        };
+        LabelScheme {alphabet: Alphabet::Latin, continuation, skip_letters, ..LabelScheme::default()}
+    }
+
+    pub fn sheet_signing(&self) -> SheetSigning {
+        // by signature key in Britain, by gathering number on the continent
+        match self {
+            Convention::BritishTrade => SheetSigning::Secondary,
+            Convention::German | Convention::French => SheetSigning::Star,
+        }
+    }
+}
+
+
+// Work
+pub fn binding_margin_note(document: &DocumentInfo, margin: f64) -> String {
+    // Folded, every page is bound at the fold in the middle of the sheet
+    // side; cut apart, each is bound at its own left edge.
+    if document.is_one_sided() {
+        format!("Binding margin: at least {}mm on the left of every page, the sheets cut down the middle", margin)
+    } else {
+        format!("Binding margin: at least {}mm each side of the fold, down the middle of every sheet side", margin)
+    }
+}
+
+pub fn display_preset(document: &DocumentInfo, preset: Preset) {
+    println!("Preset: {}", preset.name());
+    println!("{}", preset.notes());
+    if let Some(margin) = preset.binding_margin() {
+        println!("{}", binding_margin_note(document, margin));
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::choose_size;
+
+    #[test]
+    fn test_music_preset() {
+        let mut constraints = Preset::Music.constraints();
+        assert_eq!(choose_size(1, 120, &constraints).unwrap(), 8);
+        // a turn from page 9 can't open the second gathering of 8, or the third of 4
+        constraints.push(Constraint::PageTurn(9));
+        match choose_size(1, 120, &constraints) {
+            Ok(size) => panic!("Should have errored because page 9 opens a signature of 4 or 8! Got {}.", size),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "No signature size can have all of: signatures of at most 8 pages; \
+                 a page turn at page 9, away from a signature's first page. Relax one of them.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_binding_margin_note() {
+        let mut document = DocumentInfo::new(1, 120).unwrap();
+        let margin = Preset::Thesis.binding_margin().unwrap();
+        assert_eq!(
+            binding_margin_note(&document, margin),
+            "Binding margin: at least 40mm each side of the fold, down the middle of every sheet side",
+        );
+        document.set_one_sided(true);
+        assert!(binding_margin_note(&document, margin).contains("on the left of every page"));
+        assert_eq!(Preset::Music.binding_margin(), None);
+    }
+
+    #[test]
+    fn test_conventions() {
+        // 30 signatures: the letters run out after W or Z
+        let mut document = DocumentInfo::new(1, 480).unwrap();
+        document.relabel(&Convention::BritishTrade.label_scheme()).unwrap();
+        let keys: Vec<&str> = document.iter().map(|signature| signature.key()).collect();
+        assert_eq!((keys[9], keys[19], keys[20], keys[23], keys[29]), ("K", "U", "X", "AA", "GG"));
+        document.relabel(&Convention::German.label_scheme()).unwrap();
+        let keys: Vec<&str> = document.iter().map(|signature| signature.key()).collect();
+        assert_eq!((keys[19], keys[20], keys[22], keys[23]), ("V", "X", "Z", "Aa"));
+        document.relabel(&Convention::French.label_scheme()).unwrap();
+        assert_eq!(document.signatures()[23].key(), "AA");
+        assert_eq!(Convention::BritishTrade.sheet_signing(), SheetSigning::Secondary);
+        assert_eq!(Convention::French.sheet_signing(), SheetSigning::Star);
+    }
+}
@@ -0,0 +1,241 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::DocumentInfo;
+
+// Constants
+const UNSPECIFIED_STOCK: &str = "unspecified stock";
+// spoilage allowances are in hundredths of a percent
+const SPOILAGE_SCALE: u128 = 10_000;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct StockLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for StockLineError {}
+
+impl fmt::Display for StockLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the stocks file! Expected \"default: stock\", \"A, B: stock\" or \"tray 2: stock\", got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Default, PartialEq)]
+pub struct Stocks {
+    default: Option<String>,
+    signatures: Vec<(String, String)>,
+    // the printer tray each stock is loaded in, by stock
+    trays: Vec<(String, String)>,
+}
+
+impl Stocks {
+    pub fn from_file(path: &str) -> Result<Stocks, Box<dyn Error>> {
+        Stocks::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Stocks, Box<dyn Error>> {
+        // Read which stock, paper and sheet size, each signature is printed
+        // on, one entry per line:
+        //   default: 90gsm Munken Cream, SRA3
+        //   C, F: 150gsm Gloss Art, SRA3
+        // and, for printing, the tray each stock is loaded in:
+        //   tray 2: 150gsm Gloss Art, SRA3
+        // Blank lines and lines starting with # are ignored.
+        let mut stocks = Stocks::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_error = || StockLineError {line_number: i + 1, line: line.to_string()};
+            let (keys, stock) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), line[colon + 1..].trim().to_string()),
+                None => return Err(line_error().into()),
+            };
+            if keys.is_empty() || stock.is_empty() {
+                return Err(line_error().into());
+            }
+            if keys == "default" {
+                stocks.default = Some(stock);
+                continue;
+            }
+            if let Some(tray) = keys.strip_prefix("tray ") {
+                stocks.trays.push((stock, tray.trim().to_string()));
+                continue;
+            }
+            for key in keys.split(',') {
+                stocks.signatures.push((key.trim().to_string(), stock.clone()));
+            }
+        }
+        Ok(stocks)
+    }
+
+    pub(crate) fn stock_for(&self, signature_key: &str) -> &str {
+        self.signatures
+            .iter()
+            .rev() // later entries override earlier ones
+            .find(|(key, _)| key == signature_key)
+            .map(|(_, stock)| stock.as_str())
+            .or(self.default.as_deref())
+            .unwrap_or(UNSPECIFIED_STOCK)
+    }
+
+    pub(crate) fn tray_for(&self, stock: &str) -> Option<&str> {
+        self.trays
+            .iter()
+            .rev()
+            .find(|(tray_stock, _)| tray_stock == stock)
+            .map(|(_, tray)| tray.as_str())
+    }
+}
+
+// one stock's share of the press run: the signatures printed on it, with
+// how many sheets each needs
+#[derive(Debug, PartialEq)]
+pub struct StockRun {
+    pub stock: String,
+    pub signatures: Vec<(String, u32)>,
+}
+
+impl StockRun {
+    pub fn num_sheets(&self) -> u32 {
+        self.signatures.iter().map(|(_, sheets)| sheets).sum()
+    }
+}
+
+// the sheets of one stock to pull from inventory for the whole run
+#[derive(Debug, PartialEq)]
+pub struct StockPull {
+    pub stock: String,
+    pub sheets: u64,
+    // the sheets over and above the copies themselves, for spoilage
+    pub overage: u64,
+}
+
+
+// Work
+pub fn run_order(document: &DocumentInfo, stocks: &Stocks) -> Vec<StockRun> {
+    // Group the sheets by stock so each one only goes on the press once,
+    // running the stocks in the order they're first needed and keeping the
+    // signatures in order within each.
+    let mut runs: Vec<StockRun> = Vec::new();
+    for (key, leaves) in document.signature_leaves() {
+        let stock = stocks.stock_for(&key);
+        let sheets = leaves / 2;
+        match runs.iter_mut().find(|run| run.stock == stock) {
+            Some(run) => run.signatures.push((key, sheets)),
+            None => runs.push(StockRun {stock: stock.to_string(), signatures: vec![(key, sheets)]}),
+        }
+    }
+    runs
+}
+
+pub fn inventory(runs: &[StockRun], copies: u32, spoilage: u32) -> Vec<StockPull> {
+    // Each stock's sheets for every copy, plus the spoilage allowance
+    // rounded up to a whole sheet.
+    runs.iter()
+        .map(|run| {
+            let sheets = u64::from(run.num_sheets()) * u64::from(copies);
+            let overage = spoilage_overage(sheets, spoilage);
+            StockPull {stock: run.stock.clone(), sheets: sheets.saturating_add(overage), overage}
+        })
+        .collect()
+}
+
+pub(crate) fn spoilage_overage(count: u64, spoilage: u32) -> u64 {
+    // the spoilage allowance on top of count, rounded up to a whole one
+    let overage = (u128::from(count) * u128::from(spoilage)).div_ceil(SPOILAGE_SCALE);
+    u64::try_from(overage).unwrap_or(u64::MAX)
+}
+
+pub fn display_inventory(pulls: &[StockPull], copies: u32) {
+    println!("Sheets to pull for {} {}:", copies, if copies == 1 { "copy" } else { "copies" });
+    for pull in pulls {
+        println!("{}: {} sheets, including {} for spoilage", pull.stock, pull.sheets, pull.overage);
+    }
+    println!("#####################################");
+}
+
+pub fn display_run_order(document: &DocumentInfo, runs: &[StockRun]) {
+    println!("Press run order:");
+    for (i, run) in runs.iter().enumerate() {
+        println!("{}. {}: {} sheets", i + 1, run.stock, run.num_sheets());
+        for (key, sheets) in &run.signatures {
+            println!("   Signature {}: {} sheets", document.label_scheme().display_key(key), sheets);
+        }
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stocks_parse() {
+        let stocks = Stocks::parse("# plates on art paper\ndefault: 90gsm cream, SRA3\n\nB, D: 150gsm gloss, SRA3\n").unwrap();
+        assert_eq!(stocks.stock_for("A"), "90gsm cream, SRA3");
+        assert_eq!(stocks.stock_for("B"), "150gsm gloss, SRA3");
+        assert_eq!(stocks.stock_for("D"), "150gsm gloss, SRA3");
+        assert_eq!(Stocks::default().stock_for("A"), "unspecified stock");
+        assert_eq!(stocks.tray_for("150gsm gloss, SRA3"), None);
+
+        let stocks = Stocks::parse("default: cream\ntray 2: gloss\ntray manual: card\n").unwrap();
+        assert_eq!(stocks.tray_for("gloss"), Some("2"));
+        assert_eq!(stocks.tray_for("card"), Some("manual"));
+        assert_eq!(stocks.tray_for("cream"), None);
+
+        match Stocks::parse("default 90gsm\n") {
+            Ok(_) => panic!("Stocks::parse should have rejected a line without a colon"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Couldn't read line 1 of the stocks file! Expected \"default: stock\", \"A, B: stock\" or \"tray 2: stock\", got: default 90gsm",
+            ),
+        }
+        assert!(Stocks::parse("B:\n").is_err());
+    }
+
+    #[test]
+    fn test_run_order() {
+        // A to D are 4 sheets each, E is 2
+        let document = DocumentInfo::new(1, 72).unwrap();
+        let stocks = Stocks::parse("default: cream\nB, D: gloss\n").unwrap();
+        let runs = run_order(&document, &stocks);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].stock, "cream");
+        assert_eq!(
+            runs[0].signatures,
+            vec![("A".to_string(), 4), ("C".to_string(), 4), ("E".to_string(), 2)],
+        );
+        assert_eq!(runs[0].num_sheets(), 10);
+        assert_eq!(runs[1].stock, "gloss");
+        assert_eq!(runs[1].num_sheets(), 8);
+    }
+
+    #[test]
+    fn test_inventory() {
+        // 10 sheets of cream and 8 of gloss a copy
+        let document = DocumentInfo::new(1, 72).unwrap();
+        let runs = run_order(&document, &Stocks::parse("default: cream\nB, D: gloss\n").unwrap());
+        let pulls = inventory(&runs, 250, 500);
+        assert_eq!(pulls[0], StockPull {stock: "cream".to_string(), sheets: 2625, overage: 125});
+        assert_eq!(pulls[1], StockPull {stock: "gloss".to_string(), sheets: 2100, overage: 100});
+        // part sheets of spoilage round up
+        assert_eq!(inventory(&runs, 3, 250)[0], StockPull {stock: "cream".to_string(), sheets: 31, overage: 1});
+        assert_eq!(inventory(&runs, 3, 0)[0].overage, 0);
+    }
+}
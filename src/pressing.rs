@@ -0,0 +1,81 @@
+// Grouping the run's text blocks into batches for the standing press, as
+// many as fit at once, each left under pressure to dry before the next goes
+// in, so the binding can be scheduled round the press.
+
+// Constants
+// overnight, the usual time under pressure after gluing up or casing in
+pub const DEFAULT_PRESSING_HOURS: u32 = 12;
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressingBatch {
+    pub first_copy: u32,
+    pub last_copy: u32,
+    // counted from the first batch going in
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl PressingBatch {
+    pub fn num_copies(&self) -> u32 {
+        self.last_copy - self.first_copy + 1
+    }
+}
+
+
+// Work
+pub fn pressing_batches(copies: u32, capacity: u32, hours: u32) -> Vec<PressingBatch> {
+    // the copies in order, capacity at a time, one batch after another
+    let capacity = capacity.max(1);
+    (0..copies.div_ceil(capacity))
+        .map(|i| PressingBatch {
+            first_copy: i * capacity + 1,
+            last_copy: ((i + 1) * capacity).min(copies),
+            start_hour: i.saturating_mul(hours),
+            end_hour: (i + 1).saturating_mul(hours),
+        })
+        .collect()
+}
+
+pub fn display_pressing_batches(batches: &[PressingBatch], capacity: u32, hours: u32) {
+    println!(
+        "Pressing in {} {} of up to {} text blocks, {} hours each:",
+        batches.len(),
+        if batches.len() == 1 { "batch" } else { "batches" },
+        capacity,
+        hours,
+    );
+    for (i, batch) in batches.iter().enumerate() {
+        println!(
+            "Batch {}: copies {} to {} ({}), hours {} to {}",
+            i + 1,
+            batch.first_copy,
+            batch.last_copy,
+            batch.num_copies(),
+            batch.start_hour,
+            batch.end_hour,
+        );
+    }
+    if let Some(last) = batches.last() {
+        println!("All pressed after {} hours", last.end_hour);
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressing_batches() {
+        let batches = pressing_batches(150, 40, 12);
+        assert_eq!(batches.len(), 4);
+        assert_eq!(batches[0], PressingBatch {first_copy: 1, last_copy: 40, start_hour: 0, end_hour: 12});
+        assert_eq!(batches[3], PressingBatch {first_copy: 121, last_copy: 150, start_hour: 36, end_hour: 48});
+        assert_eq!(batches[3].num_copies(), 30);
+        assert_eq!(pressing_batches(10, 40, 12).len(), 1);
+        assert!(pressing_batches(0, 40, 12).is_empty());
+    }
+}
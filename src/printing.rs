@@ -0,0 +1,136 @@
+// Sending the imposed signatures to a CUPS printer, with each run drawing
+// its paper from the tray its stock is loaded in, so inserts on another
+// stock print without anyone swapping paper mid-run.
+
+use crate::commands::shell_line;
+use crate::imposition::{self, BookletFold};
+use crate::press::Stocks;
+use crate::DocumentInfo;
+
+// Constants
+// what the equivalent pdfjam command names the imposed PDF
+const IMPOSED_FILE: &str = "signatures.pdf";
+
+
+// Data structs
+// consecutive signatures printed from the same tray, by the imposed PDF's
+// sheet sides, numbered from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintRun {
+    pub signature_keys: Vec<String>,
+    pub first_side: u32,
+    pub last_side: u32,
+    pub tray: Option<String>,
+}
+
+
+// Work
+pub fn print_runs(document: &DocumentInfo, stocks: &Stocks) -> Vec<PrintRun> {
+    // The signatures in book order, so the printed sheets come off the
+    // printer ready to gather, starting a new run wherever the tray changes.
+    let mut runs: Vec<PrintRun> = Vec::new();
+    let mut side = 0;
+    for signature in document.signatures() {
+        let num_sides = imposition::signature_formes(document, signature).len() as u32;
+        let tray = stocks.tray_for(stocks.stock_for(signature.key())).map(|tray| tray.to_string());
+        match runs.last_mut() {
+            Some(run) if run.tray == tray => {
+                run.signature_keys.push(signature.key().to_string());
+                run.last_side += num_sides;
+            },
+            _ => runs.push(PrintRun {
+                signature_keys: vec![signature.key().to_string()],
+                first_side: side + 1,
+                last_side: side + num_sides,
+                tray,
+            }),
+        }
+        side += num_sides;
+    }
+    runs
+}
+
+pub fn print_commands(runs: &[PrintRun], fold: BookletFold) -> Vec<Vec<String>> {
+    // An lp command per run. A tray given as a number is asked for by its
+    // IPP name, e.g. tray-2; anything else, like manual, is passed as is.
+    runs.iter()
+        .map(|run| {
+            let mut command = vec![
+                "lp".to_string(),
+                "-o".to_string(),
+                format!("sides={}", fold.duplex()),
+                "-o".to_string(),
+                format!("page-ranges={}-{}", run.first_side, run.last_side),
+            ];
+            if let Some(tray) = &run.tray {
+                let source = if tray.chars().all(|c| c.is_ascii_digit()) { format!("tray-{}", tray) } else { tray.clone() };
+                command.push("-o".to_string());
+                command.push(format!("media-source={}", source));
+            }
+            command.push(IMPOSED_FILE.to_string());
+            command
+        })
+        .collect()
+}
+
+pub fn print_script(document: &DocumentInfo, runs: &[PrintRun], fold: BookletFold) -> String {
+    let mut script = String::new();
+    script.push_str(&format!("# Print {} with CUPS, in signature order.\n", IMPOSED_FILE));
+    script.push_str(&document.job().header("# "));
+    for (run, command) in runs.iter().zip(print_commands(runs, fold)) {
+        let keys: Vec<String> = run.signature_keys.iter().map(|key| document.label_scheme().display_key(key)).collect();
+        script.push_str(&format!(
+            "# Signature{} {}, from {}\n",
+            if keys.len() == 1 { "" } else { "s" },
+            keys.join(", "),
+            run.tray.as_ref().map_or("the default tray".to_string(), |tray| format!("tray {}", tray)),
+        ));
+        script.push_str(&format!("{}\n", shell_line(&command)));
+    }
+    script
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_print_runs() {
+        // A to D are 8 sheet sides each, E is 4
+        let document = DocumentInfo::new(1, 72).unwrap();
+        let stocks = Stocks::parse("default: cream\nC: gloss\ntray 1: cream\ntray manual: gloss\n").unwrap();
+        let runs = print_runs(&document, &stocks);
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0], PrintRun {
+            signature_keys: vec!["A".to_string(), "B".to_string()],
+            first_side: 1,
+            last_side: 16,
+            tray: Some("1".to_string()),
+        });
+        assert_eq!(runs[1].first_side, 17);
+        assert_eq!(runs[2].last_side, 36);
+
+        let commands = print_commands(&runs, BookletFold::Side);
+        assert_eq!(
+            commands[0].join(" "),
+            "lp -o sides=two-sided-short-edge -o page-ranges=1-16 -o media-source=tray-1 signatures.pdf",
+        );
+        assert!(commands[1].contains(&"media-source=manual".to_string()));
+
+        // without trays everything prints in one run
+        let runs = print_runs(&document, &Stocks::default());
+        assert_eq!(runs.len(), 1);
+        assert_eq!(
+            print_script(&document, &runs, BookletFold::Top),
+            "# Print signatures.pdf with CUPS, in signature order.\n\
+             # Signatures A, B, C, D, E, from the default tray\n\
+             lp -o sides=two-sided-long-edge -o page-ranges=1-36 signatures.pdf\n",
+        );
+
+        // a named tray is quoted for the shell
+        let stocks = Stocks::parse("default: cream\ntray Tray 2: cream\n").unwrap();
+        let runs = print_runs(&document, &stocks);
+        assert!(print_script(&document, &runs, BookletFold::Top).contains("-o 'media-source=Tray 2' signatures.pdf\n"));
+    }
+}
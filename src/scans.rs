@@ -0,0 +1,288 @@
+// For digitising a book from its unbound signatures: each sheet is scanned
+// side by side in the order the sheets come, outer forme first, and the
+// scans have to be cut in two and put back into reading order.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::deimpose;
+use crate::imposition::{self, SheetSide, Side, TwoUpError};
+use crate::page_count::{self, is_pdf_path};
+use crate::DocumentInfo;
+
+// Constants
+const SCAN_PREFIX: &str = "scan-";
+const PAGE_PREFIX: &str = "page-";
+const IMAGE_EXTENSION: &str = "jpg";
+// file numbers are padded to at least this many digits
+const MIN_FILE_NUMBER_WIDTH: usize = 3;
+const SCAN_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "tif", "tiff"];
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Half {
+    Left,
+    Right,
+}
+
+// where to find a page of the document among the scans, numbered from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannedPage {
+    pub page: u32,
+    pub scan: u32,
+    pub half: Half,
+}
+
+// a scan image found in a directory, with the number at the end of its name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanFile {
+    pub name: String,
+    pub number: Option<u32>,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanCheck {
+    pub expected: u32,
+    pub found: u32,
+    // scan numbers in the plan with no file numbered so
+    pub missing: Vec<u32>,
+    // files the same as the one before, by name
+    pub duplicates: Vec<(String, String)>,
+}
+
+impl ScanCheck {
+    pub fn is_ok(&self) -> bool {
+        self.expected == self.found && self.missing.is_empty() && self.duplicates.is_empty()
+    }
+}
+
+
+// Work
+pub fn scan_map(document: &DocumentInfo) -> Result<Vec<ScannedPage>, TwoUpError> {
+    // The scans are the imposed sheet sides in order, so they cut into the
+    // same halves as an imposed PDF; blank halves are left out.
+    Ok(deimpose::reading_order(document)?
+        .into_iter()
+        .zip(document.first_page()..)
+        .map(|(half, page)| ScannedPage {
+            page,
+            scan: half.div_ceil(2),
+            half: if half % 2 == 1 { Half::Left } else { Half::Right },
+        })
+        .collect())
+}
+
+pub fn display_scan_map(document: &DocumentInfo, sides: &[SheetSide], map: &[ScannedPage]) {
+    println!("Scan order:");
+    for (i, side) in sides.iter().enumerate() {
+        let forme = if side.side == Side::Front { "outer" } else { "inner" };
+        let page = |page: Option<u32>| page.map_or("blank".to_string(), |page| document.shown_page(page).to_string());
+        println!(
+            "Scan {}: signature {} sheet {} {}, left {}, right {}",
+            i + 1,
+            document.label_scheme().display_key(&side.signature_key),
+            side.sheet + 1,
+            forme,
+            page(side.left),
+            page(side.right),
+        );
+    }
+    println!("#####################################");
+    println!("Reading order:");
+    for scanned in map {
+        println!(
+            "Page {}: scan {} {}",
+            document.shown_page(scanned.page),
+            scanned.scan,
+            if scanned.half == Half::Left { "left" } else { "right" },
+        );
+    }
+    println!("#####################################");
+}
+
+pub fn reorder_script(document: &DocumentInfo, map: &[ScannedPage]) -> String {
+    // An ImageMagick script cutting scan-001.jpg and so on into
+    // page-001.jpg and so on in reading order. A PDF of the scans is
+    // better put in order with the deimpose subcommand.
+    let last_scan = map.iter().map(|scanned| scanned.scan).max().unwrap_or(0);
+    let scan_width = last_scan.to_string().len().max(MIN_FILE_NUMBER_WIDTH);
+    let page_width = document.shown_page(document.last_page()).to_string().len().max(MIN_FILE_NUMBER_WIDTH);
+    let mut script = String::new();
+    script.push_str("#!/bin/sh\n");
+    script.push_str(&format!(
+        "# Cut each scan of a sheet side, {}1.{} on, into its two pages in reading order.\n",
+        SCAN_PREFIX,
+        IMAGE_EXTENSION,
+    ));
+    script.push_str(&format!(
+        "# For a PDF of the scans use: rust-signatures deimpose {} {} scans.pdf reading.pdf{}\n",
+        document.shown_page(document.first_page()),
+        document.shown_page(document.last_page()),
+        if document.is_zero_indexed() { " --zero-indexed" } else { "" },
+    ));
+    script.push_str("set -e\n");
+    for scanned in map {
+        script.push_str(&format!(
+            "magick {}{:0scan_width$}.{ext} -gravity {} -crop 50%x100%+0+0 +repage {}{:0page_width$}.{ext}\n",
+            SCAN_PREFIX,
+            scanned.scan,
+            if scanned.half == Half::Left { "West" } else { "East" },
+            PAGE_PREFIX,
+            document.shown_page(scanned.page),
+            scan_width = scan_width,
+            page_width = page_width,
+            ext = IMAGE_EXTENSION,
+        ));
+    }
+    script
+}
+
+pub fn read_scans(path: &str) -> Result<Vec<ScanFile>, Box<dyn Error>> {
+    // the scan images in a directory, in name order
+    let mut scans = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        let is_scan = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| SCAN_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+        if !is_scan {
+            continue;
+        }
+        let name = path.file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let hash = crate::fnv1a(&fs::read(&path)?);
+        scans.push(ScanFile {number: file_number(&path), name, hash});
+    }
+    scans.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(scans)
+}
+
+pub fn check_scans(document: &DocumentInfo, path: &str) -> Result<ScanCheck, Box<dyn Error>> {
+    // A PDF can only be counted; a directory of numbered images shows
+    // which scans are missing, and identical files show doubled scans.
+    let expected = imposition::impose(document)?.len() as u32;
+    if is_pdf_path(path) {
+        let found = page_count::page_count(path)?;
+        return Ok(ScanCheck {expected, found, missing: Vec::new(), duplicates: Vec::new()});
+    }
+    Ok(check_scan_files(expected, &read_scans(path)?))
+}
+
+pub fn check_scan_files(expected: u32, scans: &[ScanFile]) -> ScanCheck {
+    let numbers: Option<Vec<u32>> = scans.iter().map(|scan| scan.number).collect();
+    let missing = match numbers {
+        Some(numbers) => (1..=expected).filter(|number| !numbers.contains(number)).collect(),
+        None => Vec::new(),
+    };
+    let duplicates = scans
+        .windows(2)
+        .filter(|pair| pair[0].hash == pair[1].hash)
+        .map(|pair| (pair[0].name.clone(), pair[1].name.clone()))
+        .collect();
+    ScanCheck {expected, found: scans.len() as u32, missing, duplicates}
+}
+
+pub fn display_scan_check(document: &DocumentInfo, sides: &[SheetSide], check: &ScanCheck, scans: &[ScanFile]) {
+    let describe = |side: &SheetSide| {
+        format!(
+            "signature {} sheet {} {}",
+            document.label_scheme().display_key(&side.signature_key),
+            side.sheet + 1,
+            side.side.forme(),
+        )
+    };
+    println!("Scans expected: {} ({} sheets, both sides)", check.expected, document.num_sheets());
+    println!("Scans found: {}", check.found);
+    for number in &check.missing {
+        println!("Scan {} is missing: {}", number, describe(&sides[*number as usize - 1]));
+    }
+    for (first, second) in &check.duplicates {
+        // place the doubled scan by its number if it has one
+        let side = scans
+            .iter()
+            .find(|scan| &scan.name == second)
+            .and_then(|scan| scan.number)
+            .and_then(|number| sides.get(number as usize - 1));
+        match side {
+            Some(side) => println!("{} is the same as {}, so {} may be missing", second, first, describe(side)),
+            None => println!("{} is the same as {}", second, first),
+        }
+    }
+    if check.found > check.expected {
+        println!("There are {} more scans than the plan has sheet sides.", check.found - check.expected);
+    } else if check.found < check.expected && check.missing.is_empty() {
+        println!("There are {} fewer scans than the plan has sheet sides.", check.expected - check.found);
+    }
+    if check.is_ok() {
+        println!("Every sheet side has been scanned once.");
+    }
+    println!("#####################################");
+}
+
+fn file_number(path: &Path) -> Option<u32> {
+    // the digits at the end of the file name, e.g. 12 from scan-012.jpg
+    let stem = path.file_stem()?.to_str()?;
+    let digits: String = stem.chars().rev().take_while(|c| c.is_ascii_digit()).collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_map() {
+        // scans: - 1 | 2 - | 6 3 | 4 5
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let map = scan_map(&document).unwrap();
+        assert_eq!(map.len(), 6);
+        assert_eq!(map[0], ScannedPage {page: 1, scan: 1, half: Half::Right});
+        assert_eq!(map[1], ScannedPage {page: 2, scan: 2, half: Half::Left});
+        assert_eq!(map[5], ScannedPage {page: 6, scan: 3, half: Half::Left});
+    }
+
+    #[test]
+    fn test_check_scan_files() {
+        let scan = |name: &str, number: Option<u32>, hash: u64| ScanFile {name: name.to_string(), number, hash};
+        let scans = vec![
+            scan("scan-001.jpg", Some(1), 1),
+            scan("scan-002.jpg", Some(2), 2),
+            scan("scan-003.jpg", Some(3), 2),
+        ];
+        let check = check_scan_files(4, &scans);
+        assert_eq!(check.found, 3);
+        assert_eq!(check.missing, vec![4]);
+        assert_eq!(check.duplicates, vec![("scan-002.jpg".to_string(), "scan-003.jpg".to_string())]);
+        assert!(!check.is_ok());
+
+        // unnumbered files can only be counted
+        let scans = vec![scan("front.png", None, 1), scan("back.png", None, 2)];
+        let check = check_scan_files(2, &scans);
+        assert!(check.is_ok());
+
+        assert_eq!(file_number(Path::new("scans/scan-012.jpg")), Some(12));
+        assert_eq!(file_number(Path::new("scans/cover.jpg")), None);
+    }
+
+    #[test]
+    fn test_reorder_script() {
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let script = reorder_script(&document, &scan_map(&document).unwrap());
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("rust-signatures deimpose 1 6 scans.pdf reading.pdf\n"));
+        assert!(script.contains("\nmagick scan-001.jpg -gravity East -crop 50%x100%+0+0 +repage page-001.jpg\n"));
+        assert!(script.ends_with("magick scan-003.jpg -gravity West -crop 50%x100%+0+0 +repage page-006.jpg\n"));
+        assert_eq!(script.matches("magick ").count(), 6);
+
+        // the page numbers are only right counted from 0 with the flag
+        let mut document = DocumentInfo::new(1, 16).unwrap();
+        document.set_zero_indexed(true);
+        let script = reorder_script(&document, &scan_map(&document).unwrap());
+        assert!(script.contains("rust-signatures deimpose 0 15 scans.pdf reading.pdf --zero-indexed\n"));
+        assert!(script.ends_with("page-015.jpg\n"));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::imposition::{self, PageSize, Side, TwoUpError};
+use crate::DocumentInfo;
+
+
+// Work
+pub fn scribus_script(document: &DocumentInfo, page_size: PageSize) -> Result<String, TwoUpError> {
+    // Build a Scribus Python script with one Scribus page per sheet side and
+    // an empty image frame, named after its page number, for every page.
+    // The Scribus pages are two of the book's pages wide.
+    let sides = imposition::impose(document)?;
+    let mut script = String::new();
+    script.push_str("# Scribus imposition script generated by rust-signatures.\n");
+    script.push_str(&document.job().header("# "));
+    script.push_str(&format!(
+        "# Pages {} to {}: {} signatures, {} sheets, {} sheet sides.\n",
+        document.shown_page(document.first_page()),
+        document.shown_page(document.last_page()),
+        document.num_signatures(),
+        document.num_sheets(),
+        sides.len(),
+    ));
+    script.push_str("# Run it from Script > Execute Script... and fill the frames with your pages.\n");
+    script.push_str("import scribus\n\n");
+    script.push_str(&format!("PAGE_WIDTH = {:?}\n", page_size.width));
+    script.push_str(&format!("PAGE_HEIGHT = {:?}\n", page_size.height));
+    script.push_str("SHEET_SIDES = [\n");
+    script.push_str("    # (signature, sheet, side, left page, right page)\n");
+    for side in &sides {
+        script.push_str(&format!(
+            "    (\"{}\", {}, \"{}\", {}, {}),\n",
+            side.signature_key,
+            side.sheet + 1,
+            match side.side {
+                Side::Front => "front",
+                Side::Back => "back",
+            },
+            python_page(side.left.map(|page| document.shown_page(page))),
+            python_page(side.right.map(|page| document.shown_page(page))),
+        ));
+    }
+    script.push_str("]\n\n");
+    script.push_str("scribus.newDocument(\n");
+    script.push_str("    (PAGE_WIDTH * 2, PAGE_HEIGHT), (0, 0, 0, 0), scribus.PORTRAIT, 1,\n");
+    script.push_str("    scribus.UNIT_MILLIMETERS, scribus.PAGE_1, 0, len(SHEET_SIDES),\n");
+    script.push_str(")\n");
+    script.push_str("for i, (signature, sheet, side, left, right) in enumerate(SHEET_SIDES):\n");
+    script.push_str("    scribus.gotoPage(i + 1)\n");
+    script.push_str("    for x, page in ((0, left), (PAGE_WIDTH, right)):\n");
+    script.push_str("        if page is None:\n");
+    script.push_str("            continue  # blank\n");
+    script.push_str("        scribus.createImage(x, 0, PAGE_WIDTH, PAGE_HEIGHT, \"Page %d\" % page)\n");
+    Ok(script)
+}
+
+fn python_page(page: Option<u32>) -> String {
+    match page {
+        Some(page) => page.to_string(),
+        None => "None".to_string(),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scribus_script() {
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let script = scribus_script(&document, PageSize::default()).unwrap();
+        assert!(script.contains("# Pages 1 to 6: 1 signatures, 2 sheets, 4 sheet sides.\n"));
+        assert!(script.contains("PAGE_WIDTH = 148.0\nPAGE_HEIGHT = 210.0\n"));
+        assert!(script.contains(
+            "    (\"A\", 1, \"front\", None, 1),\n    (\"A\", 1, \"back\", 2, None),\n    (\"A\", 2, \"front\", 6, 3),\n"
+        ));
+        assert!(script.contains("scribus.createImage(x, 0, PAGE_WIDTH, PAGE_HEIGHT, \"Page %d\" % page)"));
+
+        // octavo sheets don't fit the two-page Scribus sheet sides
+        let mut document = DocumentInfo::with_signature_size(1, 16, 16).unwrap();
+        document.set_fold_scheme(imposition::FoldScheme::Octavo).unwrap();
+        assert!(scribus_script(&document, PageSize::default()).is_err());
+    }
+}
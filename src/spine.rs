@@ -0,0 +1,166 @@
+// The width of the spine, from the leaves of the plan and the thickness of
+// the paper, and with any single leaves hinged onto guards, the extra
+// thickness of the guards, so the cover can be cut to fit. For a case
+// binding, the text block is rounded and backed, throwing out a shoulder
+// each side as wide as the boards are thick.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::imposition::PageSize;
+use crate::DocumentInfo;
+
+// Constants
+// a flat back, the spine of the case as wide as the text block's
+pub(crate) const FLAT_SPINE: f64 = 1.0;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct GuardedLeavesError {
+    guarded_leaves: u32,
+    num_leaves: u32,
+}
+
+impl Error for GuardedLeavesError {}
+
+impl fmt::Display for GuardedLeavesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Can't guard {} single leaves in a plan of {} leaves!",
+            self.guarded_leaves,
+            self.num_leaves,
+        )
+    }
+}
+
+
+// Data structs
+// thicknesses in millimetres
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpineConfig {
+    // one leaf of the text paper
+    pub caliper: f64,
+    // single leaves to be hinged onto guards, two to a guard
+    pub guarded_leaves: u32,
+    // one thickness of the guard paper, the text paper's if not given
+    pub guard_caliper: Option<f64>,
+    // the boards of a case, whose thickness the shoulders are backed out to
+    pub board_thickness: Option<f64>,
+    // how much longer the rounded spine is round its curve than straight
+    // across, e.g. 1.1
+    pub rounding: Option<f64>,
+}
+
+// in millimetres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spine {
+    pub text_block: f64,
+    pub guards: u32,
+    pub guard_allowance: f64,
+    pub width: f64,
+    // each side, when backed
+    pub shoulder: f64,
+    pub backed_width: f64,
+    // round the curve of the rounded spine, to cut the case's spine to
+    pub spine_arc: f64,
+    // a cover wrapping round the text block: both boards' pages and the spine
+    pub cover: PageSize,
+}
+
+
+// Work
+pub fn spine(document: &DocumentInfo, config: &SpineConfig, page_size: PageSize) -> Result<Spine, Box<dyn Error>> {
+    // A guard is a folded strip of paper with a single leaf hinged onto
+    // each half, so a pair of single leaves sew as a folded sheet. Each
+    // guard adds its two thicknesses at the spine.
+    let num_leaves = document.num_leaves();
+    if config.guarded_leaves > num_leaves {
+        return Err((GuardedLeavesError {guarded_leaves: config.guarded_leaves, num_leaves}).into());
+    }
+    let text_block = f64::from(num_leaves) * config.caliper;
+    let guards = config.guarded_leaves.div_ceil(2);
+    let guard_allowance = f64::from(guards) * 2.0 * config.guard_caliper.unwrap_or(config.caliper);
+    let width = text_block + guard_allowance;
+    let shoulder = config.board_thickness.unwrap_or(0.0);
+    let backed_width = width + 2.0 * shoulder;
+    let spine_arc = backed_width * config.rounding.unwrap_or(FLAT_SPINE);
+    Ok(Spine {
+        text_block,
+        guards,
+        guard_allowance,
+        width,
+        shoulder,
+        backed_width,
+        spine_arc,
+        cover: PageSize {width: 2.0 * page_size.width + spine_arc, height: page_size.height},
+    })
+}
+
+pub fn display_spine(document: &DocumentInfo, config: &SpineConfig, spine: &Spine) {
+    println!("Text block: {} leaves x {}mm = {:.2}mm", document.num_leaves(), config.caliper, spine.text_block);
+    if spine.guards > 0 {
+        println!(
+            "Guards: {} for {} single leaves, adding {:.2}mm",
+            spine.guards,
+            config.guarded_leaves,
+            spine.guard_allowance,
+        );
+    }
+    println!("Spine width: {:.2}mm", spine.width);
+    if spine.shoulder > 0.0 {
+        println!(
+            "Backed: shoulders of {:.2}mm each side, the board thickness, for a {:.2}mm spine",
+            spine.shoulder,
+            spine.backed_width,
+        );
+    }
+    if let Some(rounding) = config.rounding {
+        println!("Rounded: {:.2}mm round the spine (x{})", spine.spine_arc, rounding);
+    }
+    println!("Cover: {:.1}x{:.1}mm", spine.cover.width, spine.cover.height);
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spine() {
+        // 60 pages make 30 leaves
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let config = SpineConfig {caliper: 0.1, guarded_leaves: 5, guard_caliper: Some(0.05), ..SpineConfig::default()};
+        let spine = spine(&document, &config, page_size).unwrap();
+        assert_eq!(spine.guards, 3);
+        assert!((spine.text_block - 3.0).abs() < 1e-9);
+        assert!((spine.guard_allowance - 0.3).abs() < 1e-9);
+        assert!((spine.width - 3.3).abs() < 1e-9);
+        assert!((spine.cover.width - 299.3).abs() < 1e-9);
+        assert!((spine.backed_width - spine.width).abs() < 1e-9);
+
+        let config = SpineConfig {caliper: 0.1, guarded_leaves: 31, ..SpineConfig::default()};
+        match super::spine(&document, &config, page_size) {
+            Ok(_) => panic!("Should have errored because there are only 30 leaves!"),
+            Err(result_error) => {
+                assert_eq!(format!("{}", result_error), "Can't guard 31 single leaves in a plan of 30 leaves!")
+            },
+        }
+    }
+
+    #[test]
+    fn test_spine_backed() {
+        // 30 leaves at 0.4mm, 12mm flat, backed for 2.5mm boards
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let page_size = PageSize {width: 148.0, height: 210.0};
+        let config = SpineConfig {caliper: 0.4, board_thickness: Some(2.5), rounding: Some(1.1), ..SpineConfig::default()};
+        let spine = spine(&document, &config, page_size).unwrap();
+        assert!((spine.shoulder - 2.5).abs() < 1e-9);
+        assert!((spine.backed_width - 17.0).abs() < 1e-9);
+        assert!((spine.spine_arc - 18.7).abs() < 1e-9);
+        assert!((spine.cover.width - 314.7).abs() < 1e-9);
+    }
+}
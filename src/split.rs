@@ -0,0 +1,146 @@
+// Splitting a reading-order PDF into one file per signature, for printers
+// who impose each signature themselves, with a manifest so the files that
+// reach the printer can be checked against the ones made here.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::DocumentInfo;
+
+// Constants
+const MANIFEST_SUFFIX: &str = "-manifest.txt";
+
+
+// Data structs
+// one signature's file, with the document's page numbers it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitFile {
+    pub path: String,
+    pub signature_key: String,
+    pub first_page: u32,
+    pub last_page: u32,
+}
+
+
+// Work
+pub fn split_files(document: &DocumentInfo, input: &str) -> Vec<SplitFile> {
+    // book.pdf splits into book-A.pdf, book-B.pdf and so on beside it
+    let stem = input_stem(input);
+    document
+        .signatures()
+        .iter()
+        .map(|signature| SplitFile {
+            path: format!("{}-{}.pdf", stem, signature.key()),
+            signature_key: signature.key().to_string(),
+            first_page: signature.first_page(),
+            last_page: signature.last_page(),
+        })
+        .collect()
+}
+
+pub fn manifest_path(input: &str) -> String {
+    format!("{}{}", input_stem(input), MANIFEST_SUFFIX)
+}
+
+pub fn split_commands(input: &str, files: &[SplitFile]) -> Vec<Vec<String>> {
+    // the mutool invocations pulling out each signature's pages
+    files
+        .iter()
+        .map(|file| {
+            vec![
+                "mutool".to_string(),
+                "merge".to_string(),
+                "-o".to_string(),
+                file.path.clone(),
+                input.to_string(),
+                format!("{}-{}", file.first_page, file.last_page),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(feature = "pdf")]
+pub fn split(input: &str, files: &[SplitFile]) -> Result<(), Box<dyn Error>> {
+    for file in files {
+        crate::pdf::extract_pages(input, &file.path, file.first_page, file.last_page)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn split(input: &str, files: &[SplitFile]) -> Result<(), Box<dyn Error>> {
+    for command in split_commands(input, files) {
+        crate::deimpose::run(&command)?;
+    }
+    Ok(())
+}
+
+pub fn manifest(document: &DocumentInfo, files: &[SplitFile]) -> Result<String, Box<dyn Error>> {
+    // One line per file, tab separated: the file name, the signature, its
+    // first and last pages, and the 64-bit FNV-1a hash of the file in hex.
+    let mut manifest = String::from("file\tsignature\tfirst_page\tlast_page\tfnv1a64\n");
+    for file in files {
+        let name = Path::new(&file.path).file_name().map_or(file.path.clone(), |name| name.to_string_lossy().to_string());
+        manifest.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{:016x}\n",
+            name,
+            file.signature_key,
+            document.shown_page(file.first_page),
+            document.shown_page(file.last_page),
+            crate::fnv1a(&fs::read(&file.path)?),
+        ));
+    }
+    Ok(manifest)
+}
+
+pub(crate) fn input_stem(input: &str) -> String {
+    // the input's path without its .pdf
+    let extension = Path::new(input).extension().map_or(0, |extension| extension.len() + 1);
+    input[..input.len() - extension].to_string()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::shell_line;
+    use std::env;
+
+    #[test]
+    fn test_split_files() {
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let files = split_files(&document, "books/angler.pdf");
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[2],
+            SplitFile {path: "books/angler-C.pdf".to_string(), signature_key: "C".to_string(), first_page: 33, last_page: 40},
+        );
+        assert_eq!(manifest_path("books/angler.PDF"), "books/angler-manifest.txt");
+        assert_eq!(
+            split_commands("books/angler.pdf", &files)[1].join(" "),
+            "mutool merge -o books/angler-B.pdf books/angler.pdf 17-32",
+        );
+        // paths the shell would split or expand are quoted
+        let files = split_files(&document, "my books/$angler.pdf");
+        assert_eq!(
+            shell_line(&split_commands("my books/$angler.pdf", &files)[0]),
+            "mutool merge -o 'my books/$angler-A.pdf' 'my books/$angler.pdf' 1-16",
+        );
+    }
+
+    #[test]
+    fn test_manifest() {
+        let dir = env::temp_dir();
+        let path = dir.join("rust-signatures-test-manifest-A.pdf").to_string_lossy().to_string();
+        fs::write(&path, "a").unwrap();
+        let document = DocumentInfo::new(1, 12).unwrap();
+        let files = vec![SplitFile {path: path.clone(), signature_key: "A".to_string(), first_page: 1, last_page: 12}];
+        assert_eq!(
+            manifest(&document, &files).unwrap(),
+            "file\tsignature\tfirst_page\tlast_page\tfnv1a64\n\
+             rust-signatures-test-manifest-A.pdf\tA\t1\t12\taf63dc4c8601ec8c\n",
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
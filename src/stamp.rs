@@ -0,0 +1,557 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+
+use crate::imposition;
+use crate::labels::LabelScheme;
+use crate::DocumentInfo;
+
+// Constants
+const RUNNING_HEAD_FONT: &str = "Times-Italic";
+const RUNNING_HEAD_FONT_SIZE: u32 = 9;
+const WATERMARK_FONT: &str = "Helvetica-Bold";
+const WATERMARK_FONT_SIZE: u32 = 72;
+const SHEET_MARK_FONT: &str = "Times-Roman";
+const SHEET_MARK_FONT_SIZE: u32 = 8;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct RunningHeadsLineError {
+    line_number: usize,
+    line: String,
+}
+
+impl Error for RunningHeadsLineError {}
+
+impl fmt::Display for RunningHeadsLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Couldn't read line {} of the running heads file! Expected \"title: text\" or \"first-last: text\", got: {}",
+            self.line_number,
+            self.line,
+        )
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FolioPosition {
+    FootCentre,
+    FootOuter,
+    HeadOuter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumeralStyle {
+    Arabic,
+    LowerRoman,
+    UpperRoman,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolioStyle {
+    pub position: FolioPosition,
+    pub font: String,
+    pub font_size: u32,
+    pub numerals: NumeralStyle,
+}
+
+impl Default for FolioStyle {
+    fn default() -> FolioStyle {
+        FolioStyle {
+            position: FolioPosition::FootCentre,
+            font: "Times-Roman".to_string(),
+            font_size: 10,
+            numerals: NumeralStyle::Arabic,
+        }
+    }
+}
+
+// how the first page of each sheet is signed to guide folding, shown for
+// the sheets of the first gathering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetSigning {
+    // by gathering number, the continental way: 1, 1*, 1**
+    Star,
+    // 1, 1a, 1b
+    Letters,
+    // by signature key, the English way: A, A2, A3
+    Secondary,
+}
+
+// the mark on the first page of one sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetMark {
+    pub signature_key: String,
+    pub sheet: u32,
+    pub page: u32,
+    pub text: String,
+}
+
+// the direction line at the foot of a gathering's first page: the volume,
+// if any, on the left and the signature mark on the right
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectionLine {
+    pub page: u32,
+    pub volume: Option<String>,
+    pub signature_mark: String,
+}
+
+impl DirectionLine {
+    pub fn text(&self) -> String {
+        // the whole line as one piece of text, for typesetting
+        match &self.volume {
+            Some(volume) => format!("{}\t{}", volume, self.signature_mark),
+            None => self.signature_mark.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    HeadLeft,
+    HeadCentre,
+    HeadRight,
+    FootLeft,
+    FootCentre,
+    FootRight,
+    Diagonal,
+}
+
+impl Placement {
+    pub(crate) fn describe(&self) -> &'static str {
+        match self {
+            Placement::HeadLeft => "head left",
+            Placement::HeadCentre => "head centre",
+            Placement::HeadRight => "head right",
+            Placement::FootLeft => "foot left",
+            Placement::FootCentre => "foot centre",
+            Placement::FootRight => "foot right",
+            Placement::Diagonal => "centre, rotated 45°",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stamp {
+    pub page: u32,
+    pub text: String,
+    pub placement: Placement,
+    pub font: String,
+    pub font_size: u32,
+    pub proof_only: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RunningHeads {
+    title: Option<String>,
+    chapters: Vec<(u32, u32, String)>,
+}
+
+impl RunningHeads {
+    pub fn from_file(path: &str) -> Result<RunningHeads, Box<dyn Error>> {
+        RunningHeads::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<RunningHeads, Box<dyn Error>> {
+        // Read the page to text mapping, one entry per line:
+        //   title: The Compleat Angler
+        //   1-20: The First Day
+        //   21: The Second Day
+        // Blank lines and lines starting with # are ignored.
+        let mut running_heads = RunningHeads::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_error = || RunningHeadsLineError {line_number: i + 1, line: line.to_string()};
+            let (key, text) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), line[colon + 1..].trim().to_string()),
+                None => return Err(line_error().into()),
+            };
+            if key == "title" {
+                running_heads.title = Some(text);
+                continue;
+            }
+            let (first, last) = match key.find('-') {
+                Some(dash) => (key[..dash].trim().parse(), key[dash + 1..].trim().parse()),
+                None => (key.parse(), key.parse()),
+            };
+            match (first, last) {
+                (Ok(first), Ok(last)) if first <= last => running_heads.chapters.push((first, last, text)),
+                _ => return Err(line_error().into()),
+            }
+        }
+        Ok(running_heads)
+    }
+
+    fn text_for(&self, page: u32) -> Option<&str> {
+        // title on versos, the chapter covering the page on rectos
+        if !is_recto(page) {
+            return self.title.as_deref();
+        }
+        self.chapters
+            .iter()
+            .rev() // later entries override earlier ones
+            .find(|(first, last, _)| *first <= page && page <= *last)
+            .map(|(_, _, text)| text.as_str())
+    }
+}
+
+
+// Work
+pub fn folio_stamps(document: &DocumentInfo, style: &FolioStyle) -> Vec<Stamp> {
+    // Work out the folio number to stamp on each page of the document,
+    // putting "outer" positions on the right of rectos and the left of versos.
+    let mut stamps = Vec::new();
+    for page in document.first_page()..=document.last_page() {
+        let recto = is_recto(page);
+        let placement = match style.position {
+            FolioPosition::FootCentre => Placement::FootCentre,
+            FolioPosition::FootOuter if recto => Placement::FootRight,
+            FolioPosition::FootOuter => Placement::FootLeft,
+            FolioPosition::HeadOuter if recto => Placement::HeadRight,
+            FolioPosition::HeadOuter => Placement::HeadLeft,
+        };
+        stamps.push(Stamp {
+            page,
+            text: folio_text(page, style.numerals),
+            placement,
+            font: style.font.clone(),
+            font_size: style.font_size,
+            proof_only: false,
+        });
+    }
+    stamps
+}
+
+pub fn running_head_stamps(document: &DocumentInfo, running_heads: &RunningHeads) -> Vec<Stamp> {
+    // Work out the running head to stamp at the top of each page, leaving
+    // out pages that have no text (e.g. chapter openings mapped to "").
+    let mut stamps = Vec::new();
+    for page in document.first_page()..=document.last_page() {
+        match running_heads.text_for(page) {
+            Some(text) if !text.is_empty() => stamps.push(Stamp {
+                page,
+                text: text.to_string(),
+                placement: Placement::HeadCentre,
+                font: RUNNING_HEAD_FONT.to_string(),
+                font_size: RUNNING_HEAD_FONT_SIZE,
+                proof_only: false,
+            }),
+            _ => {},
+        }
+    }
+    stamps
+}
+
+pub fn watermark_stamps(document: &DocumentInfo, text: &str) -> Vec<Stamp> {
+    // Stamp the watermark diagonally across every page. These only go on
+    // proofs, never on the final imposed output.
+    (document.first_page()..=document.last_page())
+        .map(|page| Stamp {
+            page,
+            text: text.to_string(),
+            placement: Placement::Diagonal,
+            font: WATERMARK_FONT.to_string(),
+            font_size: WATERMARK_FONT_SIZE,
+            proof_only: true,
+        })
+        .collect()
+}
+
+pub fn sheet_marks(document: &DocumentInfo, signing: SheetSigning) -> Vec<SheetMark> {
+    // Sign the first page of every sheet, which is printed on the front, so
+    // the rectos of the first half of each gathering however often the
+    // sheets are folded.
+    // Gatherings are numbered from 1.
+    let mut marks = Vec::new();
+    let mut gathering = 0;
+    for forme in imposition::impose_formes(document) {
+        if forme.side != imposition::Side::Front {
+            continue;
+        }
+        if forme.sheet == 0 {
+            gathering += 1;
+        }
+        let text = match (signing, forme.sheet) {
+            (SheetSigning::Secondary, 0) => forme.signature_key.clone(),
+            (SheetSigning::Secondary, sheet) => format!("{}{}", forme.signature_key, sheet + 1),
+            (_, 0) => gathering.to_string(),
+            (SheetSigning::Star, sheet) => format!("{}{}", gathering, "*".repeat(sheet as usize)),
+            (SheetSigning::Letters, sheet) => {
+                format!("{}{}", gathering, LabelScheme::default().key(sheet - 1).to_lowercase())
+            },
+        };
+        if let Some(page) = forme.rows.iter().flatten().filter_map(|cell| cell.page).min() {
+            marks.push(SheetMark {signature_key: forme.signature_key, sheet: forme.sheet, page, text});
+        }
+    }
+    marks
+}
+
+pub fn sheet_mark_stamps(marks: &[SheetMark]) -> Vec<Stamp> {
+    // sheet marks go at the foot, to the right, like signature marks
+    marks
+        .iter()
+        .map(|mark| Stamp {
+            page: mark.page,
+            text: mark.text.clone(),
+            placement: Placement::FootRight,
+            font: SHEET_MARK_FONT.to_string(),
+            font_size: SHEET_MARK_FONT_SIZE,
+            proof_only: false,
+        })
+        .collect()
+}
+
+pub fn direction_lines(document: &DocumentInfo, volume: Option<&str>) -> Vec<DirectionLine> {
+    document
+        .signatures()
+        .iter()
+        .map(|signature| DirectionLine {
+            page: signature.first_page(),
+            volume: volume.map(str::to_string),
+            signature_mark: signature.key().to_string(),
+        })
+        .collect()
+}
+
+pub fn direction_line_stamps(lines: &[DirectionLine]) -> Vec<Stamp> {
+    // set in the same small type as the sheet marks
+    let mut stamps = Vec::new();
+    for line in lines {
+        let mut stamp = |text: &str, placement| stamps.push(Stamp {
+            page: line.page,
+            text: text.to_string(),
+            placement,
+            font: SHEET_MARK_FONT.to_string(),
+            font_size: SHEET_MARK_FONT_SIZE,
+            proof_only: false,
+        });
+        if let Some(volume) = &line.volume {
+            stamp(volume, Placement::FootLeft);
+        }
+        stamp(&line.signature_mark, Placement::FootRight);
+    }
+    stamps
+}
+
+pub fn display_direction_lines(document: &DocumentInfo, lines: &[DirectionLine]) {
+    // e.g. "Page 17: Vol. II at foot left, B at foot right"
+    println!("Direction lines:");
+    for line in lines {
+        let mark = format!("{} at foot right", document.label_scheme().display_key(&line.signature_mark));
+        match &line.volume {
+            Some(volume) => println!("Page {}: {} at foot left, {}", document.shown_page(line.page), volume, mark),
+            None => println!("Page {}: {}", document.shown_page(line.page), mark),
+        }
+    }
+    println!("#####################################");
+}
+
+pub fn display_sheet_marks(document: &DocumentInfo, marks: &[SheetMark]) {
+    // one line per signature, e.g. "Signature A: 1 on page 1, 1* on page 3"
+    println!("Sheet marks:");
+    let mut line = String::new();
+    for (i, mark) in marks.iter().enumerate() {
+        if mark.sheet == 0 {
+            line = format!("Signature {}: ", document.label_scheme().display_key(&mark.signature_key));
+        } else {
+            line.push_str(", ");
+        }
+        line.push_str(&format!("{} on page {}", mark.text, document.shown_page(mark.page)));
+        if marks.get(i + 1).is_none_or(|next| next.sheet == 0) {
+            println!("{}", line);
+        }
+    }
+    println!("#####################################");
+}
+
+pub fn display_stamps(document: &DocumentInfo, stamps: &[Stamp]) {
+    println!("Stamps:");
+    for stamp in stamps {
+        println!(
+            "Page {}: \"{}\" at {} ({} {}pt){}",
+            document.shown_page(stamp.page),
+            stamp.text,
+            stamp.placement.describe(),
+            stamp.font,
+            stamp.font_size,
+            if stamp.proof_only { ", proofs only" } else { "" },
+        );
+    }
+    println!("#####################################");
+}
+
+pub(crate) fn is_recto(page: u32) -> bool {
+    // odd pages fall on the right hand side of an opening
+    page % 2 == 1
+}
+
+fn folio_text(page: u32, numerals: NumeralStyle) -> String {
+    match numerals {
+        NumeralStyle::Arabic => page.to_string(),
+        NumeralStyle::LowerRoman => to_roman(page).to_lowercase(),
+        NumeralStyle::UpperRoman => to_roman(page),
+    }
+}
+
+fn to_roman(number: u32) -> String {
+    let numerals = [
+        (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"),
+        (100, "C"), (90, "XC"), (50, "L"), (40, "XL"),
+        (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+    ];
+    let mut roman = String::new();
+    let mut remaining = number;
+    for &(value, numeral) in numerals.iter() {
+        while remaining >= value {
+            roman.push_str(numeral);
+            remaining -= value;
+        }
+    }
+    roman
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_roman() {
+        assert_eq!(to_roman(1), "I");
+        assert_eq!(to_roman(4), "IV");
+        assert_eq!(to_roman(9), "IX");
+        assert_eq!(to_roman(14), "XIV");
+        assert_eq!(to_roman(40), "XL");
+        assert_eq!(to_roman(1994), "MCMXCIV");
+    }
+
+    #[test]
+    fn test_folio_stamps() {
+        let document = DocumentInfo::new(3, 6).unwrap();
+        let stamps = folio_stamps(&document, &FolioStyle::default());
+        assert_eq!(stamps.len(), 4);
+        assert_eq!(stamps[0].page, 3);
+        assert_eq!(stamps[0].text, "3");
+        assert_eq!(stamps[0].placement, Placement::FootCentre);
+        assert_eq!(stamps[0].font, "Times-Roman");
+
+        // outer positions alternate between rectos and versos
+        let style = FolioStyle {
+            position: FolioPosition::FootOuter,
+            numerals: NumeralStyle::LowerRoman,
+            ..FolioStyle::default()
+        };
+        let stamps = folio_stamps(&document, &style);
+        assert_eq!(stamps[0].text, "iii");
+        assert_eq!(stamps[0].placement, Placement::FootRight);
+        assert_eq!(stamps[1].text, "iv");
+        assert_eq!(stamps[1].placement, Placement::FootLeft);
+    }
+
+    #[test]
+    fn test_running_heads_parse() {
+        let contents = "# The Compleat Angler\ntitle: The Compleat Angler\n\n1-4: The First Day\n5: \n";
+        let running_heads = RunningHeads::parse(contents).unwrap();
+        assert_eq!(running_heads.title, Some("The Compleat Angler".to_string()));
+        assert_eq!(
+            running_heads.chapters,
+            vec![(1, 4, "The First Day".to_string()), (5, 5, "".to_string())],
+        );
+
+        let result = RunningHeads::parse("title: A Book\n9-3: Backwards\n");
+        match result {
+            Ok(_) => panic!("Should have errored because the range is backwards!"),
+            Err(result_error) => {
+                let error_msg = format!("{}", result_error);
+                assert!(error_msg.starts_with("Couldn't read line 2 of the running heads file!"));
+            },
+        }
+    }
+
+    #[test]
+    fn test_running_head_stamps() {
+        let running_heads = RunningHeads::parse("title: A Book\n1-2: Preface\n3: \n4-8: Chapter One\n").unwrap();
+        let document = DocumentInfo::new(1, 6).unwrap();
+        let stamps = running_head_stamps(&document, &running_heads);
+        let texts: Vec<(u32, &str)> = stamps.iter().map(|stamp| (stamp.page, stamp.text.as_str())).collect();
+        assert_eq!(
+            texts,
+            vec![(1, "Preface"), (2, "A Book"), (4, "A Book"), (5, "Chapter One"), (6, "A Book")],
+        );
+        assert_eq!(stamps[0].placement, Placement::HeadCentre);
+        assert!(!stamps[0].proof_only);
+    }
+
+    #[test]
+    fn test_watermark_stamps() {
+        let document = DocumentInfo::new(5, 8).unwrap();
+        let stamps = watermark_stamps(&document, "PROOF");
+        assert_eq!(stamps.len(), 4);
+        for (stamp, page) in stamps.iter().zip(5..) {
+            assert_eq!(stamp.page, page);
+            assert_eq!(stamp.text, "PROOF");
+            assert_eq!(stamp.placement, Placement::Diagonal);
+            assert!(stamp.proof_only);
+        }
+    }
+
+    #[test]
+    fn test_sheet_marks() {
+        // two full 4-sheet signatures, then a 2-sheet one
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let marks = sheet_marks(&document, SheetSigning::Star);
+        let texts: Vec<(u32, &str)> = marks.iter().map(|mark| (mark.page, mark.text.as_str())).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (1, "1"), (3, "1*"), (5, "1**"), (7, "1***"),
+                (17, "2"), (19, "2*"), (21, "2**"), (23, "2***"),
+                (33, "3"), (35, "3*"),
+            ],
+        );
+        let marks = sheet_marks(&document, SheetSigning::Letters);
+        assert_eq!(marks[1].text, "1a");
+        assert_eq!(marks[3].text, "1c");
+        assert_eq!(marks[9].text, "3a");
+        assert_eq!(marks[9].signature_key, "C");
+        let marks = sheet_marks(&document, SheetSigning::Secondary);
+        let texts: Vec<&str> = marks.iter().map(|mark| mark.text.as_str()).collect();
+        assert_eq!(texts, vec!["A", "A2", "A3", "A4", "B", "B2", "B3", "B4", "C", "C2"]);
+
+        let stamps = sheet_mark_stamps(&marks);
+        assert_eq!(stamps[0].placement, Placement::FootRight);
+        assert_eq!(stamps[0].text, "A");
+
+        // octavo sheets are signed once each, on their first page
+        let mut document = DocumentInfo::with_signature_size(1, 64, 32).unwrap();
+        document.set_fold_scheme(imposition::FoldScheme::Octavo).unwrap();
+        let marks = sheet_marks(&document, SheetSigning::Secondary);
+        let texts: Vec<(u32, &str)> = marks.iter().map(|mark| (mark.page, mark.text.as_str())).collect();
+        assert_eq!(texts, vec![(1, "A"), (9, "A2"), (33, "B"), (41, "B2")]);
+    }
+
+    #[test]
+    fn test_direction_lines() {
+        let document = DocumentInfo::new(1, 40).unwrap();
+        let lines = direction_lines(&document, None);
+        let pages: Vec<(u32, &str)> = lines.iter().map(|line| (line.page, line.signature_mark.as_str())).collect();
+        assert_eq!(pages, vec![(1, "A"), (17, "B"), (33, "C")]);
+        assert_eq!(lines[1].text(), "B");
+        assert_eq!(direction_line_stamps(&lines).len(), 3);
+
+        let lines = direction_lines(&document, Some("Vol. II"));
+        assert_eq!(lines[1].text(), "Vol. II\tB");
+        let stamps = direction_line_stamps(&lines);
+        assert_eq!(stamps.len(), 6);
+        assert_eq!((stamps[2].page, stamps[2].text.as_str(), stamps[2].placement), (17, "Vol. II", Placement::FootLeft));
+        assert_eq!((stamps[3].page, stamps[3].text.as_str(), stamps[3].placement), (17, "B", Placement::FootRight));
+    }
+}
@@ -0,0 +1,123 @@
+// Rendering a plan through a shop's own Tera template, for builds with the
+// templates feature, so reports can match existing paperwork exactly.
+//
+// Templates see first_page, last_page, num_pages, num_sheets,
+// num_signatures, pages_per_signature, num_blank_pages, sheet_utilisation,
+// collation, job (with whichever of job, title, author, client and due are
+// given) and signatures, each with key, first_page, last_page, num_pages
+// and num_sheets.
+
+use std::error::Error;
+#[cfg(not(feature = "templates"))]
+use std::fmt;
+use std::fs;
+
+use crate::DocumentInfo;
+
+
+// Custom errors
+#[cfg(not(feature = "templates"))]
+#[derive(Debug)]
+pub(crate) struct TemplatesNotBuiltError;
+
+#[cfg(not(feature = "templates"))]
+impl Error for TemplatesNotBuiltError {}
+
+#[cfg(not(feature = "templates"))]
+impl fmt::Display for TemplatesNotBuiltError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rendering a --template needs a build with --features templates.")
+    }
+}
+
+
+// Work
+pub fn render_file(document: &DocumentInfo, path: &str) -> Result<String, Box<dyn Error>> {
+    render(document, &fs::read_to_string(path)?)
+}
+
+#[cfg(feature = "templates")]
+pub fn render(document: &DocumentInfo, template: &str) -> Result<String, Box<dyn Error>> {
+    use std::collections::BTreeMap;
+    use tera::{Context, Tera, Value};
+
+    let mut context = Context::new();
+    context.insert("first_page", &document.shown_page(document.first_page()));
+    context.insert("last_page", &document.shown_page(document.last_page()));
+    context.insert("num_pages", &document.num_pages());
+    context.insert("num_sheets", &document.num_sheets());
+    context.insert("num_signatures", &document.num_signatures());
+    context.insert("pages_per_signature", &document.pages_per_signature());
+    context.insert("num_blank_pages", &document.num_blank_pages());
+    context.insert("sheet_utilisation", &document.sheet_utilisation());
+    context.insert("collation", &document.collation_formula());
+    let job = &document.job();
+    // only the fields given, so templates can default the rest
+    let job: BTreeMap<&str, &str> = vec![
+        ("job", &job.job_id),
+        ("title", &job.title),
+        ("author", &job.author),
+        ("client", &job.client),
+        ("due", &job.due),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_deref().map(|value| (name, value)))
+    .collect();
+    context.insert("job", &job);
+    let signatures: Vec<BTreeMap<&str, Value>> = document
+        .signatures()
+        .iter()
+        .map(|signature| {
+            let num_pages = signature.last_page() - signature.first_page() + 1;
+            vec![
+                ("key", Value::from(document.label_scheme().display_key(signature.key()))),
+                ("first_page", Value::from(document.shown_page(signature.first_page()))),
+                ("last_page", Value::from(document.shown_page(signature.last_page()))),
+                ("num_pages", Value::from(num_pages)),
+                ("num_sheets", Value::from(num_pages.div_ceil(crate::DOC_PAGES_PER_SHEET))),
+            ]
+            .into_iter()
+            .collect()
+        })
+        .collect();
+    context.insert("signatures", &signatures);
+    Ok(Tera::one_off(template, &context, false)?)
+}
+
+#[cfg(not(feature = "templates"))]
+pub fn render(_document: &DocumentInfo, _template: &str) -> Result<String, Box<dyn Error>> {
+    Err(TemplatesNotBuiltError.into())
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "templates")]
+    #[test]
+    fn test_render() {
+        let mut document = DocumentInfo::new(1, 40).unwrap();
+        document.set_job(crate::job::JobInfo {job_id: Some("J-1041".to_string()), ..Default::default()});
+        let template = "{{ job.job }}: {{ num_sheets }} sheets, {{ collation }}\n\
+                        {% for signature in signatures %}{{ signature.key }} {{ signature.first_page }}-{{ signature.last_page }}\n{% endfor %}\
+                        {{ job.client | default(value=\"no client\") }}";
+        assert_eq!(
+            render(&document, template).unwrap(),
+            "J-1041: 10 sheets, A\u{2013}B\u{2078} C\u{2074}\nA 1-16\nB 17-32\nC 33-40\nno client",
+        );
+        assert!(render(&document, "{{ nothing.here }}").is_err());
+    }
+
+    #[cfg(not(feature = "templates"))]
+    #[test]
+    fn test_render() {
+        match render(&DocumentInfo::new(1, 40).unwrap(), "{{ num_sheets }}") {
+            Ok(_) => panic!("Should have errored because templates aren't built in!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Rendering a --template needs a build with --features templates.",
+            ),
+        }
+    }
+}
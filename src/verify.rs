@@ -0,0 +1,247 @@
+// Checking an imposed PDF against the plan by reading its page numbers back:
+// a few sheet sides are rasterized with mutool and read with tesseract, for
+// builds with the ocr feature, and the numbers found in each half of the
+// sheet compared with the pages the plan puts there.
+
+use std::error::Error;
+#[cfg(not(feature = "ocr"))]
+use std::fmt;
+
+#[cfg(feature = "ocr")]
+use crate::imposition;
+use crate::imposition::{Cell, SheetSide};
+use crate::DocumentInfo;
+
+// Constants
+// the first, middle and last sheet sides are read
+pub const DEFAULT_SAMPLE_SIDES: usize = 3;
+#[cfg(feature = "ocr")]
+const RASTER_DPI: u32 = 150;
+
+
+// Custom errors
+#[cfg(not(feature = "ocr"))]
+#[derive(Debug)]
+pub(crate) struct OcrNotBuiltError;
+
+#[cfg(not(feature = "ocr"))]
+impl Error for OcrNotBuiltError {}
+
+#[cfg(not(feature = "ocr"))]
+impl fmt::Display for OcrNotBuiltError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Reading page numbers off an imposed PDF needs a build with --features ocr.")
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellCheck {
+    // the sheet side's page in the imposed PDF, from 1
+    pub side: usize,
+    pub signature_key: String,
+    pub cell: Cell,
+    // the page number the plan prints there, if it isn't a blank
+    pub expected: Option<u32>,
+    pub found: Vec<u32>,
+}
+
+impl CellCheck {
+    pub fn is_ok(&self) -> bool {
+        // a blank may still carry a stray number, so only pages are checked
+        match self.expected {
+            Some(page) => self.found.contains(&page),
+            None => true,
+        }
+    }
+}
+
+
+// Work
+pub fn sample_sides(num_sides: usize, samples: usize) -> Vec<usize> {
+    // evenly spread from the first side to the last, counted from 0
+    if num_sides == 0 || samples == 0 {
+        return Vec::new();
+    }
+    if samples == 1 || num_sides == 1 {
+        return vec![0];
+    }
+    let mut sides: Vec<usize> = (0..samples).map(|i| i * (num_sides - 1) / (samples - 1)).collect();
+    sides.dedup();
+    sides
+}
+
+pub fn numbers_in_halves(tsv: &str) -> (Vec<u32>, Vec<u32>) {
+    // Tesseract's TSV output has a row for the page, level 1, giving its
+    // width, and a row for each word, level 5, giving where it is. Words
+    // that are whole numbers go to whichever half their middle falls in.
+    let mut page_width = 0;
+    let mut words = Vec::new();
+    for line in tsv.lines().skip(1) {
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() < 12 {
+            continue;
+        }
+        let (left, width) = match (columns[6].parse::<u32>(), columns[8].parse::<u32>()) {
+            (Ok(left), Ok(width)) => (left, width),
+            _ => continue,
+        };
+        match columns[0] {
+            "1" => page_width = width,
+            "5" => {
+                let text = columns[11].trim().trim_matches(|c: char| !c.is_ascii_digit());
+                if let Ok(number) = text.parse::<u32>() {
+                    words.push((left + width / 2, number));
+                }
+            },
+            _ => (),
+        }
+    }
+    let in_half = |left: bool| -> Vec<u32> {
+        words.iter().filter(|(middle, _)| (2 * middle < page_width) == left).map(|(_, number)| *number).collect()
+    };
+    (in_half(true), in_half(false))
+}
+
+pub fn check_side(document: &DocumentInfo, index: usize, side: &SheetSide, tsv: &str) -> Vec<CellCheck> {
+    // the numbers read off each half against the page the plan put there
+    let (left, right) = numbers_in_halves(tsv);
+    vec![(Cell::Left, side.left, left), (Cell::Right, side.right, right)]
+        .into_iter()
+        .map(|(cell, page, found)| CellCheck {
+            side: index + 1,
+            signature_key: side.signature_key.clone(),
+            cell,
+            expected: page.map(|page| document.shown_page(page)),
+            found,
+        })
+        .collect()
+}
+
+#[cfg(feature = "ocr")]
+pub fn verify_imposed(document: &DocumentInfo, imposed: &str, samples: usize) -> Result<Vec<CellCheck>, Box<dyn Error>> {
+    // Each sampled side is drawn to a PNG in the temporary directory, read,
+    // and the PNG tidied up afterwards.
+    let sides = imposition::impose(document)?;
+    let mut checks = Vec::new();
+    for index in sample_sides(sides.len(), samples) {
+        let png = std::env::temp_dir().join(format!("rust-signatures-verify-{}-{}.png", std::process::id(), index + 1));
+        let png = png.to_string_lossy().to_string();
+        let draw: Vec<String> = vec![
+            "mutool".to_string(),
+            "draw".to_string(),
+            "-r".to_string(),
+            RASTER_DPI.to_string(),
+            "-o".to_string(),
+            png.clone(),
+            imposed.to_string(),
+            (index + 1).to_string(),
+        ];
+        crate::deimpose::run(&draw)?;
+        // sparse text, so page numbers on their own are picked up
+        let read: Vec<String> = vec![
+            "tesseract".to_string(),
+            png.clone(),
+            "stdout".to_string(),
+            "--psm".to_string(),
+            "11".to_string(),
+            "tsv".to_string(),
+        ];
+        let tsv = crate::deimpose::read_output(&read);
+        std::fs::remove_file(&png)?;
+        checks.extend(check_side(document, index, &sides[index], &tsv?));
+    }
+    Ok(checks)
+}
+
+#[cfg(not(feature = "ocr"))]
+pub fn verify_imposed(_document: &DocumentInfo, _imposed: &str, _samples: usize) -> Result<Vec<CellCheck>, Box<dyn Error>> {
+    Err(OcrNotBuiltError.into())
+}
+
+pub fn display_verification(checks: &[CellCheck]) {
+    for check in checks {
+        let cell = match check.cell {
+            Cell::Left => "left",
+            Cell::Right => "right",
+        };
+        let found: Vec<String> = check.found.iter().map(|number| number.to_string()).collect();
+        let found = if found.is_empty() { "nothing".to_string() } else { found.join(", ") };
+        match check.expected {
+            Some(page) if check.is_ok() => println!("Side {} ({}), {}: page {} found", check.side, check.signature_key, cell, page),
+            Some(page) => println!("Side {} ({}), {}: expected page {}, read {}", check.side, check.signature_key, cell, page, found),
+            None => println!("Side {} ({}), {}: blank", check.side, check.signature_key, cell),
+        }
+    }
+    let mismatches = checks.iter().filter(|check| !check.is_ok()).count();
+    if mismatches == 0 {
+        println!("All {} sampled cells match the plan", checks.len());
+    } else {
+        println!("{} of {} sampled cells don't match the plan", mismatches, checks.len());
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV_HEADER: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext";
+
+    #[test]
+    fn test_sample_sides() {
+        assert_eq!(sample_sides(8, 3), vec![0, 3, 7]);
+        assert_eq!(sample_sides(2, 3), vec![0, 1]);
+        assert_eq!(sample_sides(1, 3), vec![0]);
+        assert!(sample_sides(0, 3).is_empty());
+    }
+
+    #[test]
+    fn test_numbers_in_halves() {
+        let tsv = [
+            TSV_HEADER,
+            "1\t1\t0\t0\t0\t0\t0\t0\t2480\t1754\t-1\t",
+            "5\t1\t1\t1\t1\t1\t600\t1650\t40\t30\t91\t16",
+            "5\t1\t1\t1\t1\t2\t900\t200\t120\t30\t88\tChapter",
+            "5\t1\t2\t1\t1\t1\t1840\t1650\t20\t30\t93\t-1-",
+        ]
+        .join("\n");
+        assert_eq!(numbers_in_halves(&tsv), (vec![16], vec![1]));
+        assert_eq!(numbers_in_halves(TSV_HEADER), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_check_side() {
+        // the outer side of the first sheet of 16 pages: 16 1
+        let document = DocumentInfo::new(1, 16).unwrap();
+        let sides = crate::imposition::impose(&document).unwrap();
+        let tsv = [
+            TSV_HEADER,
+            "1\t1\t0\t0\t0\t0\t0\t0\t2480\t1754\t-1\t",
+            "5\t1\t1\t1\t1\t1\t600\t1650\t40\t30\t91\t16",
+            "5\t1\t2\t1\t1\t1\t1840\t1650\t20\t30\t93\t7",
+        ]
+        .join("\n");
+        let checks = check_side(&document, 0, &sides[0], &tsv);
+        assert_eq!(checks[0].expected, Some(16));
+        assert!(checks[0].is_ok());
+        assert_eq!(checks[1].expected, Some(1));
+        assert_eq!(checks[1].found, vec![7]);
+        assert!(!checks[1].is_ok());
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[test]
+    fn test_verify_imposed_not_built() {
+        let document = DocumentInfo::new(1, 16).unwrap();
+        match verify_imposed(&document, "booklet.pdf", DEFAULT_SAMPLE_SIDES) {
+            Ok(_) => panic!("Should have errored because ocr isn't built in!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Reading page numbers off an imposed PDF needs a build with --features ocr.",
+            ),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// Planning several documents bound together as one volume, e.g. a year's
+// issues of a journal: the parts are numbered on from one another, planned
+// as a whole, and each signature reported with the parts it holds pages of.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use crate::constraints::parse_page_span;
+use crate::page_count::{self, is_pdf_path};
+use crate::DocumentInfo;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct PartError {
+    value: String,
+}
+
+impl Error for PartError {}
+
+impl fmt::Display for PartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Parts should be a PDF or a span of pages, e.g. issue-1.pdf or 1-48. Got: {}", self.value)
+    }
+}
+
+
+// Data structs
+// one of the documents in the volume, at the pages of the volume it takes up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: String,
+    pub first_page: u32,
+    pub last_page: u32,
+}
+
+impl Part {
+    pub fn num_pages(&self) -> u32 {
+        self.last_page - self.first_page + 1
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Volume {
+    pub parts: Vec<Part>,
+}
+
+impl Volume {
+    pub fn new(parts: &[(String, u32)]) -> Volume {
+        // each part, by name and page count, following on from the last
+        let mut volume = Volume::default();
+        let mut first_page = 1;
+        for (name, num_pages) in parts {
+            let last_page = first_page + num_pages - 1;
+            volume.parts.push(Part {name: name.clone(), first_page, last_page});
+            first_page = last_page + 1;
+        }
+        volume
+    }
+
+    pub fn read(values: &[String]) -> Result<Volume, Box<dyn Error>> {
+        // --part values, each a PDF to count the pages of or a span of pages
+        let parts = values.iter().enumerate().map(|(i, value)| read_part(i, value)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Volume::new(&parts))
+    }
+
+    pub fn aligned(&self, pages_per_signature: u32) -> Volume {
+        // Each part moved on to start a fresh signature, with blank pages
+        // after the one before, so the parts can be taken apart again at
+        // the sewing.
+        let mut volume = Volume::default();
+        let mut first_page = 1;
+        for part in &self.parts {
+            let last_page = first_page + part.num_pages() - 1;
+            volume.parts.push(Part {name: part.name.clone(), first_page, last_page});
+            first_page = last_page.div_ceil(pages_per_signature) * pages_per_signature + 1;
+        }
+        volume
+    }
+
+    pub fn num_pages(&self) -> u32 {
+        self.parts.last().map_or(0, |part| part.last_page)
+    }
+
+    pub fn blank_pages(&self) -> Vec<(String, u32, u32)> {
+        // the blank pages after each part that's followed by any, by part
+        self.parts
+            .windows(2)
+            .filter(|pair| pair[1].first_page > pair[0].last_page + 1)
+            .map(|pair| (pair[0].name.clone(), pair[0].last_page + 1, pair[1].first_page - 1))
+            .collect()
+    }
+
+    pub fn parts_in(&self, first_page: u32, last_page: u32) -> Vec<&Part> {
+        // the parts with any of their pages between first_page and last_page
+        self.parts
+            .iter()
+            .filter(|part| part.first_page <= last_page && part.last_page >= first_page)
+            .collect()
+    }
+}
+
+// the parts one signature holds pages of
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartSpan {
+    pub signature_key: String,
+    pub parts: Vec<String>,
+}
+
+
+// Work
+fn read_part(i: usize, value: &str) -> Result<(String, u32), Box<dyn Error>> {
+    // issue-1.pdf is named issue-1; a span like 1-48 is named for its place,
+    // e.g. "part 2"
+    if is_pdf_path(value) {
+        let name = Path::new(value)
+            .file_stem()
+            .map_or_else(|| value.to_string(), |stem| stem.to_string_lossy().into_owned());
+        return Ok((name, page_count::page_count(value)?));
+    }
+    match parse_page_span(value) {
+        Some((from, to)) => Ok((format!("part {}", i + 1), to - from + 1)),
+        None => Err((PartError {value: value.to_string()}).into()),
+    }
+}
+
+pub fn part_spans(document: &DocumentInfo, volume: &Volume) -> Vec<PartSpan> {
+    document
+        .signatures()
+        .iter()
+        .map(|signature| PartSpan {
+            signature_key: signature.key().to_string(),
+            parts: volume
+                .parts_in(signature.first_page(), signature.last_page())
+                .iter()
+                .map(|part| part.name.clone())
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn display_part_spans(document: &DocumentInfo, volume: &Volume, spans: &[PartSpan]) {
+    // the parts and their pages in the volume, then the parts in each
+    // signature, e.g. "Signature D: issue-1, issue-2"
+    println!("Parts bound in this volume:");
+    for part in &volume.parts {
+        println!(
+            "{}: pages {} to {}",
+            part.name,
+            document.shown_page(part.first_page),
+            document.shown_page(part.last_page),
+        );
+    }
+    for (name, first_page, last_page) in volume.blank_pages() {
+        println!(
+            "Blank pages {} to {} after {}",
+            document.shown_page(first_page),
+            document.shown_page(last_page),
+            name,
+        );
+    }
+    for span in spans {
+        println!("Signature {}: {}", document.label_scheme().display_key(&span.signature_key), span.parts.join(", "));
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volume() {
+        let volume = Volume::read(&["1-40".to_string(), "5-28".to_string()]).unwrap();
+        assert_eq!(volume.num_pages(), 64);
+        assert_eq!(volume.parts[1], Part {name: "part 2".to_string(), first_page: 41, last_page: 64});
+        match Volume::read(&["issue-1".to_string()]) {
+            Ok(_) => panic!("Should have errored because issue-1 is neither a PDF nor a span of pages!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "Parts should be a PDF or a span of pages, e.g. issue-1.pdf or 1-48. Got: issue-1",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_part_spans() {
+        let volume = Volume::new(&[("one".to_string(), 40), ("two".to_string(), 24)]);
+        let document = DocumentInfo::new(1, volume.num_pages()).unwrap();
+        let spans = part_spans(&document, &volume);
+        let parts: Vec<Vec<String>> = spans.iter().map(|span| span.parts.clone()).collect();
+        assert_eq!(parts, vec![vec!["one"], vec!["one"], vec!["one", "two"], vec!["two"]]);
+        assert_eq!(spans[2].signature_key, "C");
+    }
+
+    #[test]
+    fn test_volume_aligned() {
+        let volume = Volume::new(&[("one".to_string(), 40), ("two".to_string(), 24), ("three".to_string(), 16)]);
+        let aligned = volume.aligned(16);
+        let pages: Vec<(u32, u32)> = aligned.parts.iter().map(|part| (part.first_page, part.last_page)).collect();
+        assert_eq!(pages, vec![(1, 40), (49, 72), (81, 96)]);
+        assert_eq!(aligned.blank_pages(), vec![("one".to_string(), 41, 48), ("two".to_string(), 73, 80)]);
+        assert!(volume.blank_pages().is_empty());
+
+        let document = DocumentInfo::new(1, aligned.num_pages()).unwrap();
+        let parts: Vec<usize> = part_spans(&document, &aligned).iter().map(|span| span.parts.len()).collect();
+        assert_eq!(parts, vec![1; 6]);
+    }
+}
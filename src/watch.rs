@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// Constants
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct NothingToWatchError;
+
+impl Error for NothingToWatchError {}
+
+impl fmt::Display for NothingToWatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "--watch needs an input file to watch, e.g. a PDF to plan, --labels-file, --running-heads or --stocks.")
+    }
+}
+
+
+// Work
+pub fn check_watchable(paths: &[String]) -> Result<(), Box<dyn Error>> {
+    if paths.is_empty() {
+        return Err(NothingToWatchError.into());
+    }
+    Ok(())
+}
+
+pub fn wait_for_change(paths: &[String]) -> Vec<String> {
+    // Poll the files' modification times until at least one changes, and
+    // return the ones that did. A file that goes missing, e.g. while an
+    // editor saves it, counts as a change once it comes back.
+    let before = modified_times(paths);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let after = modified_times(paths);
+        let changed = changed_paths(paths, &before, &after);
+        if !changed.is_empty() && after.iter().all(Option::is_some) {
+            return changed;
+        }
+    }
+}
+
+fn modified_times(paths: &[String]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+fn changed_paths(paths: &[String], before: &[Option<SystemTime>], after: &[Option<SystemTime>]) -> Vec<String> {
+    paths
+        .iter()
+        .zip(before.iter().zip(after))
+        .filter(|(_, (before, after))| before != after)
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_paths() {
+        let paths = vec!["labels.txt".to_string(), "heads.txt".to_string()];
+        let earlier = Some(SystemTime::UNIX_EPOCH);
+        let later = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        assert!(changed_paths(&paths, &[earlier, earlier], &[earlier, earlier]).is_empty());
+        assert_eq!(changed_paths(&paths, &[earlier, earlier], &[earlier, later]), vec!["heads.txt"]);
+        assert_eq!(changed_paths(&paths, &[earlier, None], &[earlier, later]), vec!["heads.txt"]);
+        assert!(check_watchable(&[]).is_err());
+        assert!(check_watchable(&paths).is_ok());
+    }
+}
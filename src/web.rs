@@ -0,0 +1,242 @@
+// Planning for a web press, fed from a reel rather than with sheets. Each
+// turn of the cylinder prints one cutoff, the width of the web and as long as
+// the cylinder is round; the web is slit lengthwise into ribbons and each
+// ribbon is cut across into the plan's sheets.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::imposition::{self, PageSize, Side};
+use crate::multi_up::Strip;
+use crate::press;
+use crate::DocumentInfo;
+
+// Constants
+const MILLIMETRES_PER_METRE: f64 = 1000.0;
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct WebTooNarrowError {
+    web: WebPress,
+    sheet: PageSize,
+}
+
+impl Error for WebTooNarrowError {}
+
+impl fmt::Display for WebTooNarrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A {}x{}mm sheet doesn't fit a {}mm web with a {}mm cutoff, either way round.",
+            self.sheet.width,
+            self.sheet.height,
+            self.web.width,
+            self.web.cutoff,
+        )
+    }
+}
+
+
+// Data structs
+// in millimetres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WebPress {
+    pub width: f64,
+    pub cutoff: f64,
+}
+
+impl WebPress {
+    pub fn parse(value: &str) -> Result<WebPress, Box<dyn Error>> {
+        // WIDTHxCUTOFF, e.g. 520x630
+        let size = PageSize::parse(value)?;
+        Ok(WebPress {width: size.width, cutoff: size.height})
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RibbonLayout {
+    pub ribbons: u32,
+    pub ribbon_width: f64,
+    // sheets one after another along each ribbon, in one cutoff
+    pub sheets_per_ribbon: u32,
+    // whether the sheets run across the web, their height across the ribbon
+    pub turned: bool,
+}
+
+impl RibbonLayout {
+    pub fn sheets_per_cutoff(&self) -> u32 {
+        self.ribbons * self.sheets_per_ribbon
+    }
+}
+
+// the plan's sheets printed by one cutoff, ribbon by ribbon
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cutoff {
+    pub number: u32,
+    pub ribbons: Vec<Vec<Strip>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebPlan {
+    pub web: WebPress,
+    pub layout: RibbonLayout,
+    pub cutoffs: Vec<Cutoff>,
+}
+
+impl WebPlan {
+    pub fn signatures_per_cutoff(&self, document: &DocumentInfo) -> f64 {
+        let sheets_per_signature = document.pages_per_signature() / document.pages_per_sheet();
+        f64::from(self.layout.sheets_per_cutoff()) / f64::from(sheets_per_signature)
+    }
+
+    pub fn impressions(&self, copies: u32, spoilage: u32) -> u64 {
+        // every cutoff is printed once for each copy, and for spoilage
+        let copies = u64::from(copies);
+        copies.saturating_add(press::spoilage_overage(copies, spoilage))
+    }
+
+    pub fn web_length(&self, copies: u32, spoilage: u32) -> f64 {
+        // in millimetres
+        self.cutoffs.len() as f64 * self.impressions(copies, spoilage) as f64 * self.web.cutoff
+    }
+}
+
+
+// Work
+pub fn ribbon_layout(web: WebPress, sheet: PageSize) -> Result<RibbonLayout, Box<dyn Error>> {
+    // Whichever way round fits the most sheets into a cutoff, preferring
+    // the sheets as they are when it's a tie.
+    let layout = |across: f64, along: f64, turned: bool| RibbonLayout {
+        ribbons: (web.width / across).floor() as u32,
+        ribbon_width: across,
+        sheets_per_ribbon: (web.cutoff / along).floor() as u32,
+        turned,
+    };
+    let upright = layout(sheet.width, sheet.height, false);
+    let turned = layout(sheet.height, sheet.width, true);
+    let best = if turned.sheets_per_cutoff() > upright.sheets_per_cutoff() { turned } else { upright };
+    if best.sheets_per_cutoff() == 0 {
+        return Err((WebTooNarrowError {web, sheet}).into());
+    }
+    Ok(best)
+}
+
+pub fn web_plan(document: &DocumentInfo, web: WebPress, sheet: PageSize) -> Result<WebPlan, Box<dyn Error>> {
+    // The plan's sheets in order fill each cutoff a ribbon at a time,
+    // however many pages are on each.
+    let layout = ribbon_layout(web, sheet)?;
+    let sheets: Vec<Strip> = imposition::impose_formes(document)
+        .into_iter()
+        .filter(|forme| forme.side == Side::Front)
+        .map(|forme| Strip {signature_key: forme.signature_key, sheet: forme.sheet})
+        .collect();
+    let cutoffs = sheets
+        .chunks(layout.sheets_per_cutoff() as usize)
+        .enumerate()
+        .map(|(number, sheets)| Cutoff {
+            number: number as u32,
+            ribbons: sheets.chunks(layout.sheets_per_ribbon as usize).map(|ribbon| ribbon.to_vec()).collect(),
+        })
+        .collect();
+    Ok(WebPlan {web, layout, cutoffs})
+}
+
+pub fn display_web_plan(document: &DocumentInfo, plan: &WebPlan, copies: u32, spoilage: u32) {
+    let layout = plan.layout;
+    println!("Web press: {}mm web, {}mm cutoff", plan.web.width, plan.web.cutoff);
+    println!(
+        "{} ribbons of {}mm, {} sheets along each{}, {} sheets ({} signatures) per cutoff",
+        layout.ribbons,
+        layout.ribbon_width,
+        layout.sheets_per_ribbon,
+        if layout.turned { ", turned" } else { "" },
+        layout.sheets_per_cutoff(),
+        format_count(plan.signatures_per_cutoff(document)),
+    );
+    for cutoff in &plan.cutoffs {
+        let ribbons: Vec<String> = cutoff
+            .ribbons
+            .iter()
+            .map(|ribbon| {
+                let sheets: Vec<String> = ribbon
+                    .iter()
+                    .map(|strip| {
+                        format!("{} sheet {}", document.label_scheme().display_key(&strip.signature_key), strip.sheet + 1)
+                    })
+                    .collect();
+                sheets.join(", ")
+            })
+            .collect();
+        println!("Cutoff {}: {}", cutoff.number + 1, ribbons.join(" | "));
+    }
+    println!(
+        "Web needed for {} {}: {} cutoffs x {} impressions, {:.1}m",
+        copies,
+        if copies == 1 { "copy" } else { "copies" },
+        plan.cutoffs.len(),
+        plan.impressions(copies, spoilage),
+        plan.web_length(copies, spoilage) / MILLIMETRES_PER_METRE,
+    );
+    println!("#####################################");
+}
+
+fn format_count(count: f64) -> String {
+    // whole numbers as they are, anything else to two places
+    if count.fract() == 0.0 {
+        format!("{}", count)
+    } else {
+        format!("{:.2}", count)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ribbon_layout() {
+        // 210x148mm sheets: 2 across a 520mm web and 4 along a 600mm
+        // cutoff fits more than 3 across and 2 along
+        let web = WebPress::parse("520x600").unwrap();
+        let layout = ribbon_layout(web, PageSize {width: 210.0, height: 148.0}).unwrap();
+        assert_eq!(layout, RibbonLayout {ribbons: 2, ribbon_width: 210.0, sheets_per_ribbon: 4, turned: false});
+
+        let layout = ribbon_layout(web, PageSize {width: 296.0, height: 210.0}).unwrap();
+        assert_eq!(layout, RibbonLayout {ribbons: 2, ribbon_width: 210.0, sheets_per_ribbon: 2, turned: true});
+
+        match ribbon_layout(web, PageSize {width: 600.0, height: 700.0}) {
+            Ok(_) => panic!("Should have errored because the sheet is wider than the web!"),
+            Err(result_error) => assert_eq!(
+                format!("{}", result_error),
+                "A 600x700mm sheet doesn't fit a 520mm web with a 600mm cutoff, either way round.",
+            ),
+        }
+    }
+
+    #[test]
+    fn test_web_plan() {
+        // 60 pages on 15 sheets, 8 to a cutoff
+        let document = DocumentInfo::new(1, 60).unwrap();
+        let web = WebPress {width: 520.0, cutoff: 600.0};
+        let plan = web_plan(&document, web, PageSize {width: 210.0, height: 148.0}).unwrap();
+        assert_eq!(plan.cutoffs.len(), 2);
+        assert_eq!(plan.cutoffs[0].ribbons.len(), 2);
+        assert_eq!(plan.cutoffs[0].ribbons[1][0], Strip {signature_key: "B".to_string(), sheet: 0});
+        assert_eq!(plan.cutoffs[1].ribbons[1].len(), 3);
+        assert_eq!(plan.signatures_per_cutoff(&document), 2.0);
+        // 100 copies and 5% spoilage
+        assert_eq!(plan.impressions(100, 500), 105);
+        assert!((plan.web_length(100, 500) - 126_000.0).abs() < 1e-9);
+        assert_eq!(format_count(1.5), "1.50");
+
+        // 64 pages in octavo are 4 sheets, each a whole signature
+        let mut document = DocumentInfo::with_signature_size(1, 64, 16).unwrap();
+        document.set_fold_scheme(crate::imposition::FoldScheme::Octavo).unwrap();
+        let web = WebPress {width: 600.0, cutoff: 1200.0};
+        let plan = web_plan(&document, web, PageSize {width: 592.0, height: 420.0}).unwrap();
+        assert_eq!(plan.cutoffs.iter().flat_map(|cutoff| cutoff.ribbons.iter().flatten()).count(), 4);
+        assert_eq!(plan.signatures_per_cutoff(&document), f64::from(plan.layout.sheets_per_cutoff()));
+    }
+}
@@ -0,0 +1,177 @@
+// Zines from a single sheet: the 8-page mini zine, printed on one side,
+// cut once and folded, and the quarter-letter booklet, printed both sides
+// and folded twice. Everything is said in terms of the one sheet and its
+// folds, for people who've never planned a book.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::chase::{ChaseFormat, ChasePage, Forme};
+use crate::imposition::PageSize;
+
+// Constants
+pub const ZINE_PAGES: u32 = 8;
+const ZINE_SUFFIX: &str = "-zine.pdf";
+// US letter, in millimetres
+const LETTER: PageSize = PageSize {width: 215.9, height: 279.4};
+
+
+// Custom errors
+#[derive(Debug)]
+pub(crate) struct ZineTooLongError {
+    num_pages: u32,
+}
+
+impl Error for ZineTooLongError {}
+
+impl fmt::Display for ZineTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A zine from one sheet holds {} pages, not {}!", ZINE_PAGES, self.num_pages)
+    }
+}
+
+#[cfg(not(feature = "pdf"))]
+#[derive(Debug)]
+pub(crate) struct ZinePdfError;
+
+#[cfg(not(feature = "pdf"))]
+impl Error for ZinePdfError {}
+
+#[cfg(not(feature = "pdf"))]
+impl fmt::Display for ZinePdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Laying out the zine's sheet needs a build with --features pdf.")
+    }
+}
+
+
+// Data structs
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ZineFormat {
+    #[default]
+    Mini,
+    QuarterLetter,
+}
+
+impl ZineFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ZineFormat::Mini => "8-page mini zine",
+            ZineFormat::QuarterLetter => "Quarter-letter booklet",
+        }
+    }
+
+    pub fn sheet(&self) -> PageSize {
+        // the mini zine's sheet lies on its side, four pages across
+        match self {
+            ZineFormat::Mini => PageSize {width: LETTER.height, height: LETTER.width},
+            ZineFormat::QuarterLetter => LETTER,
+        }
+    }
+
+    pub fn sides(&self) -> Vec<Forme> {
+        // The pages on each printed side, top row first, the top row head
+        // down. The quarter-letter booklet is a quarto.
+        match self {
+            ZineFormat::Mini => {
+                let row = |pages: [u32; 4], head_down: bool| pages.iter().map(|&page| ChasePage {page, head_down}).collect();
+                vec![Forme {rows: vec![row([5, 4, 3, 2], true), row([6, 7, 8, 1], false)]}]
+            },
+            ZineFormat::QuarterLetter => vec![ChaseFormat::Quarto.outer_forme(), ChaseFormat::Quarto.inner_forme()],
+        }
+    }
+
+    pub fn instructions(&self, path: &str) -> Vec<String> {
+        match self {
+            ZineFormat::Mini => vec![
+                format!("Print {} on one side of a letter or A4 sheet, landscape.", path),
+                "Fold the sheet in half top to bottom, then in quarters side to side, creasing each fold, and \
+                 open it out flat."
+                    .to_string(),
+                "Fold it in half side to side, and cut along the middle crease from the fold to the next \
+                 crease, halfway across."
+                    .to_string(),
+                "Open it out, fold it in half top to bottom, and push the ends together so the cut opens up \
+                 and the pages fold round into a booklet."
+                    .to_string(),
+                "Fold it flat with page 1 on the front.".to_string(),
+            ],
+            ZineFormat::QuarterLetter => vec![
+                format!("Print {} on both sides of a letter sheet, flipping on the long edge.", path),
+                "Fold the sheet in half top to bottom, with the upside-down pages to the back.".to_string(),
+                "Fold it in half again side to side, with page 1 on the front.".to_string(),
+                "Trim the folded edge along the top so the pages open, and staple through the middle fold."
+                    .to_string(),
+            ],
+        }
+    }
+}
+
+
+// Work
+pub fn zine_path(input: &str) -> String {
+    format!("{}{}", crate::split::input_stem(input), ZINE_SUFFIX)
+}
+
+pub fn check_pages(num_pages: u32) -> Result<u32, Box<dyn Error>> {
+    // fewer pages leave the last ones blank
+    if num_pages > ZINE_PAGES {
+        return Err((ZineTooLongError {num_pages}).into());
+    }
+    Ok(num_pages)
+}
+
+#[cfg(feature = "pdf")]
+pub fn zine(format: ZineFormat, input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    crate::pdf::impose_formes(input, output, format.sheet(), &format.sides(), crate::impose::PrinterOffset::default())
+}
+
+#[cfg(not(feature = "pdf"))]
+pub fn zine(_format: ZineFormat, _input: &str, _output: &str) -> Result<(), Box<dyn Error>> {
+    Err(ZinePdfError.into())
+}
+
+pub fn display_zine(format: ZineFormat, output: &str) {
+    println!("{}", format.name());
+    println!("#####################################");
+    let names = ["Front of the sheet", "Back of the sheet"];
+    for (name, side) in names.iter().zip(format.sides()) {
+        println!("{}:", name);
+        print!("{}", side.draw());
+    }
+    println!("^ and v point to the top of each page.");
+    println!("#####################################");
+    for (i, step) in format.instructions(output).iter().enumerate() {
+        println!("{}. {}", i + 1, step);
+    }
+    println!("#####################################");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zine_sides() {
+        // every page once, on one side for the mini zine and two for the booklet
+        for &(format, num_sides) in [(ZineFormat::Mini, 1), (ZineFormat::QuarterLetter, 2)].iter() {
+            let sides = format.sides();
+            assert_eq!(sides.len(), num_sides);
+            let mut pages: Vec<u32> = sides.iter().flat_map(|side| side.rows.iter().flatten().map(|cell| cell.page)).collect();
+            pages.sort();
+            assert_eq!(pages, (1..=ZINE_PAGES).collect::<Vec<u32>>());
+        }
+        assert_eq!(ZineFormat::Mini.sheet(), PageSize {width: 279.4, height: 215.9});
+        assert_eq!(zine_path("issue-3.pdf"), "issue-3-zine.pdf");
+    }
+
+    #[test]
+    fn test_check_pages() {
+        assert_eq!(check_pages(6).unwrap(), 6);
+        match check_pages(12) {
+            Ok(_) => panic!("Should have errored because 12 pages won't fit on one sheet!"),
+            Err(result_error) => assert_eq!(format!("{}", result_error), "A zine from one sheet holds 8 pages, not 12!"),
+        }
+    }
+}